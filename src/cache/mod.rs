@@ -0,0 +1,125 @@
+use crate::config::{self, CacheConfig};
+use crate::error::Result;
+use chrono::{DateTime, Duration, Utc};
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// A raw backend response stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResponse {
+    created_at: DateTime<Utc>,
+    response: String,
+}
+
+/// Return the cached response for this subject and prompt if it is recent enough
+pub fn load(config: &CacheConfig, subject_id: Uuid, prompt: &str) -> Option<String> {
+    let path = entry_path(config, subject_id, prompt).ok()?;
+    let content = fs::read_to_string(path).ok()?;
+    let cached: CachedResponse = serde_json::from_str(&content).ok()?;
+    let max_age = Duration::hours(i64::from(config.max_age_hours));
+    (Utc::now() - cached.created_at < max_age).then_some(cached.response)
+}
+
+/// Store a raw backend response for this subject and prompt
+pub fn store(config: &CacheConfig, subject_id: Uuid, prompt: &str, response: &str) -> Result<()> {
+    let path = entry_path(config, subject_id, prompt)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let cached = CachedResponse {
+        created_at: Utc::now(),
+        response: response.to_string(),
+    };
+    fs::write(path, serde_json::to_string(&cached)?)?;
+    Ok(())
+}
+
+/// Drop every cached response for a subject
+pub fn invalidate(config: &CacheConfig, subject_id: Uuid) -> Result<()> {
+    let dir = directory(config)?.join(subject_id.to_string());
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Drop all cached responses and return how many were removed
+pub fn clear(config: &CacheConfig) -> Result<usize> {
+    let dir = directory(config)?;
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        // Only subject directories; anything else in the directory isn't ours
+        let is_subject_dir = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| Uuid::parse_str(name).is_ok());
+        if is_subject_dir && path.is_dir() {
+            removed += fs::read_dir(&path)?.count();
+            fs::remove_dir_all(&path)?;
+        }
+    }
+    Ok(removed)
+}
+
+/// A configured cache_dir may be shared (e.g. ~/.cache), so responses get their own subdirectory
+fn directory(config: &CacheConfig) -> Result<PathBuf> {
+    match &config.cache_dir {
+        Some(dir) => Ok(dir.join("headsup-responses")),
+        None => Ok(config::cache_dir()?.join("responses")),
+    }
+}
+
+/// `<cache dir>/<subject id>/<SHA-256 of the prompt>.json`
+fn entry_path(config: &CacheConfig, subject_id: Uuid, prompt: &str) -> Result<PathBuf> {
+    let hash: String = digest::digest(&digest::SHA256, prompt.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(directory(config)?
+        .join(subject_id.to_string())
+        .join(format!("{}.json", hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = CacheConfig {
+            enabled: true,
+            max_age_hours: 1,
+            cache_dir: Some(dir.path().to_path_buf()),
+        };
+        let id = Uuid::new_v4();
+
+        store(&config, id, "prompt", "{\"ok\":true}").unwrap();
+        assert_eq!(load(&config, id, "prompt").as_deref(), Some("{\"ok\":true}"));
+        assert_eq!(load(&config, id, "other prompt"), None);
+
+        config.max_age_hours = 0;
+        assert_eq!(load(&config, id, "prompt"), None);
+
+        invalidate(&config, id).unwrap();
+        config.max_age_hours = 1;
+        assert_eq!(load(&config, id, "prompt"), None);
+        assert_eq!(clear(&config).unwrap(), 0);
+
+        // clear leaves other programs' files alone, even inside the responses directory
+        let foreign = dir.path().join("headsup-responses").join("other-tool");
+        fs::create_dir_all(&foreign).unwrap();
+        store(&config, id, "prompt", "{}").unwrap();
+        assert_eq!(clear(&config).unwrap(), 1);
+        assert!(foreign.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -2,7 +2,7 @@ mod process;
 mod prompt;
 mod response;
 
-pub use process::execute_claude;
+pub use process::{claude_version, execute_claude};
 pub use prompt::*;
 pub use response::*;
 
@@ -10,52 +10,48 @@ use crate::config::{ClaudeConfig, Subject, SubjectType};
 use crate::error::Result;
 use crate::state::SubjectState;
 
-/// Check a subject and return the response
+/// Check a subject and return the parsed response along with the raw output
 pub async fn check_subject(
     config: &ClaudeConfig,
     subject: &Subject,
     state: Option<&SubjectState>,
-) -> Result<ClaudeResponse> {
+) -> Result<(ClaudeResponse, String)> {
     match subject.subject_type {
         SubjectType::Release => {
             let release_state = state.and_then(|s| match s {
                 SubjectState::Release(rs) => Some(rs),
                 _ => None,
             });
-            let prompt = build_release_prompt(subject, release_state);
+            let prompt = with_context(config, build_release_prompt(subject, release_state, true), state);
             let raw = execute_claude(config, &prompt).await?;
             let response = parse_release_response(&raw)?;
-            Ok(ClaudeResponse::Release(response))
+            Ok((ClaudeResponse::Release(response), raw))
         }
         SubjectType::Question => {
             let question_state = state.and_then(|s| match s {
                 SubjectState::Question(qs) => Some(qs),
                 _ => None,
             });
-            let prompt = build_question_prompt(subject, question_state);
+            let prompt = with_context(config, build_question_prompt(subject, question_state, true), state);
             let raw = execute_claude(config, &prompt).await?;
             let response = parse_question_response(&raw)?;
-            Ok(ClaudeResponse::Question(response))
+            Ok((ClaudeResponse::Question(response), raw))
         }
         SubjectType::Recurring => {
             let recurring_state = state.and_then(|s| match s {
                 SubjectState::Recurring(rs) => Some(rs),
                 _ => None,
             });
-            let prompt = build_recurring_prompt(subject, recurring_state);
+            let prompt = with_context(config, build_recurring_prompt(subject, recurring_state, true), state);
             let raw = execute_claude(config, &prompt).await?;
             let response = parse_recurring_response(&raw)?;
-            Ok(ClaudeResponse::Recurring(response))
+            Ok((ClaudeResponse::Recurring(response), raw))
         }
     }
 }
 
-/// Identify subjects based on user input
-pub async fn identify_subjects(
-    config: &ClaudeConfig,
-    user_input: &str,
-) -> Result<SubjectIdentificationResponse> {
-    let prompt = build_subject_identification_prompt(user_input);
-    let raw = execute_claude(config, &prompt).await?;
-    parse_subject_identification_response(&raw)
+/// Ask what the search terms find, as a one-line summary
+pub async fn verify_search_terms(config: &ClaudeConfig, search_terms: &[String]) -> Result<String> {
+    let raw = execute_claude(config, &build_search_verification_prompt(search_terms)).await?;
+    Ok(raw.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default().to_string())
 }
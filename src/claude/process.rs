@@ -76,6 +76,24 @@ fn execute_claude_sync(command: &str, model: &str, prompt: &str) -> Result<Strin
     }
 }
 
+/// Return the first line of `<command> --version`, or None if it can't be run
+pub fn claude_version(command: &str) -> Option<String> {
+    let (program, base_args) = parse_command(command);
+    let output = Command::new(&program)
+        .args(&base_args)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
+
 /// Parse a command string into program and arguments
 /// Handles cases like:
 /// - "claude"
@@ -1,5 +1,5 @@
-use crate::config::Subject;
-use crate::state::{QuestionState, RecurringState, ReleaseState};
+use crate::config::{ClaudeConfig, ContextStrategy, Subject, SubjectType};
+use crate::state::{QuestionState, RecurringState, ReleaseState, SubjectState};
 use chrono::{DateTime, Utc};
 
 /// Format a datetime as a human-readable relative time string
@@ -17,14 +17,57 @@ fn relative_time(dt: &DateTime<Utc>) -> String {
     }
 }
 
-/// Build the prompt for a release-type subject
-pub fn build_release_prompt(subject: &Subject, state: Option<&ReleaseState>) -> String {
-    let category = subject.category.as_ref().map(|c| c.to_string()).unwrap_or_else(|| "unknown".to_string());
-    let search_terms_section = if subject.search_terms.is_empty() {
+/// Build the CONTEXT and PRIVATE CONTEXT lines from the subject's notes
+fn notes_section(subject: &Subject) -> String {
+    let mut section = subject.notes.as_ref()
+        .map(|n| format!("CONTEXT: {}\n", n))
+        .unwrap_or_default();
+    if let Some(ref private) = subject.notes_private {
+        section.push_str(&format!("PRIVATE CONTEXT: {}\n", private));
+    }
+    section
+}
+
+/// Search terms to give the AI, falling back to terms derived from the subject
+fn search_terms_section(subject: &Subject) -> String {
+    let terms = subject.search_terms_effective();
+    let mut section = if terms.is_empty() {
         String::new()
     } else {
-        format!("SEARCH TERMS: {}\n", subject.search_terms.join(", "))
+        format!("SEARCH TERMS: {}\n", terms.join(", "))
     };
+    if !subject.search_terms_exclude.is_empty() {
+        section.push_str(&format!(
+            "EXCLUDE FROM SEARCH: {} (ignore pages about these)\n",
+            subject.search_terms_exclude.join(", ")
+        ));
+    }
+    section
+}
+
+/// First task step: `search_step` as is, or a training-data fallback when the backend cannot search
+fn research_step(search_available: bool, search_step: &str, topic: &str) -> String {
+    if search_available {
+        search_step.to_string()
+    } else {
+        format!(
+            "Without web access, recall what your training data says about {} and note in the summary that it may be outdated",
+            topic
+        )
+    }
+}
+
+/// Build the prompt for a release-type subject
+///
+/// Without `search_available` the prompt asks the model to answer from its training data.
+pub fn build_release_prompt(subject: &Subject, state: Option<&ReleaseState>, search_available: bool) -> String {
+    let category = subject.category.as_ref().map(|c| c.to_string()).unwrap_or_else(|| "unknown".to_string());
+    let search_terms_section = if search_available { search_terms_section(subject) } else { String::new() };
+    let research_step = research_step(
+        search_available,
+        "Search for recent news about this subject's release date",
+        "this subject's release date",
+    );
 
     let state_info = if let Some(s) = state {
         if let Some(ref date) = s.known_release_date {
@@ -39,9 +82,7 @@ pub fn build_release_prompt(subject: &Subject, state: Option<&ReleaseState>) ->
         "CURRENT KNOWN STATE:\n- No release date currently known".to_string()
     };
 
-    let notes_section = subject.notes.as_ref()
-        .map(|n| format!("CONTEXT: {}\n", n))
-        .unwrap_or_default();
+    let notes_section = notes_section(subject);
 
     let last_notification_section = if let Some(s) = state {
         if let (Some(ref summary), Some(ref notified_at)) = (&s.last_notified_summary, &s.last_notified) {
@@ -69,7 +110,7 @@ CATEGORY: {category}
 {state_info}
 {last_notification_section}
 TASK:
-1. Search for recent news about this subject's release date
+1. {research_step}
 2. Evaluate the credibility of sources (official > major outlets > rumors)
 3. Compare findings to the current known state
 4. Determine if the user should be notified
@@ -104,6 +145,7 @@ Respond with ONLY the JSON object, no other text."#,
         name = subject.name,
         category = category,
         search_terms_section = search_terms_section,
+        research_step = research_step,
         notes_section = notes_section,
         state_info = state_info,
         last_notification_section = last_notification_section,
@@ -111,13 +153,14 @@ Respond with ONLY the JSON object, no other text."#,
 }
 
 /// Build the prompt for a question-type subject
-pub fn build_question_prompt(subject: &Subject, state: Option<&QuestionState>) -> String {
-    let question = subject.question.as_ref().map(|q| q.as_str()).unwrap_or("Unknown question");
-    let search_terms_section = if subject.search_terms.is_empty() {
-        String::new()
-    } else {
-        format!("SEARCH TERMS: {}\n", subject.search_terms.join(", "))
-    };
+pub fn build_question_prompt(subject: &Subject, state: Option<&QuestionState>, search_available: bool) -> String {
+    let question = subject.question.as_deref().unwrap_or("Unknown question");
+    let search_terms_section = if search_available { search_terms_section(subject) } else { String::new() };
+    let research_step = research_step(
+        search_available,
+        "Search for recent information about this question",
+        "this question",
+    );
 
     let state_info = if let Some(s) = state {
         if let Some(ref answer) = s.current_answer {
@@ -132,9 +175,7 @@ pub fn build_question_prompt(subject: &Subject, state: Option<&QuestionState>) -
         "CURRENT KNOWN STATE:\n- No answer currently known".to_string()
     };
 
-    let notes_section = subject.notes.as_ref()
-        .map(|n| format!("CONTEXT: {}\n", n))
-        .unwrap_or_default();
+    let notes_section = notes_section(subject);
 
     let last_notification_section = if let Some(s) = state {
         if let (Some(ref summary), Some(ref notified_at)) = (&s.last_notified_summary, &s.last_notified) {
@@ -161,7 +202,7 @@ QUESTION: {question}
 {state_info}
 {last_notification_section}
 TASK:
-1. Search for recent information about this question
+1. {research_step}
 2. Evaluate the credibility of sources
 3. Determine if there's a definitive/official answer
 4. Compare findings to the current known state
@@ -195,6 +236,7 @@ of the same facts is NOT a change. Examples:
 Respond with ONLY the JSON object, no other text."#,
         question = question,
         search_terms_section = search_terms_section,
+        research_step = research_step,
         notes_section = notes_section,
         state_info = state_info,
         last_notification_section = last_notification_section,
@@ -202,13 +244,14 @@ Respond with ONLY the JSON object, no other text."#,
 }
 
 /// Build the prompt for a recurring-type subject
-pub fn build_recurring_prompt(subject: &Subject, state: Option<&RecurringState>) -> String {
-    let event_name = subject.event_name.as_ref().map(|e| e.as_str()).unwrap_or("Unknown event");
-    let search_terms_section = if subject.search_terms.is_empty() {
-        String::new()
-    } else {
-        format!("SEARCH TERMS: {}\n", subject.search_terms.join(", "))
-    };
+pub fn build_recurring_prompt(subject: &Subject, state: Option<&RecurringState>, search_available: bool) -> String {
+    let event_name = subject.event_name.as_deref().unwrap_or("Unknown event");
+    let search_terms_section = if search_available { search_terms_section(subject) } else { String::new() };
+    let research_step = research_step(
+        search_available,
+        "Search for information about the next upcoming occurrence of this event",
+        "the next upcoming occurrence of this event",
+    );
 
     let state_info = if let Some(s) = state {
         let mut info = String::from("CURRENT KNOWN STATE:\n");
@@ -229,8 +272,15 @@ pub fn build_recurring_prompt(subject: &Subject, state: Option<&RecurringState>)
         "CURRENT KNOWN STATE:\n- No event information currently known".to_string()
     };
 
-    let notes_section = subject.notes.as_ref()
-        .map(|n| format!("CONTEXT: {}\n", n))
+    let notes_section = notes_section(subject);
+
+    let rss_section = subject.rss_feed.as_ref()
+        .map(|url| format!(
+            "RSS FEED: {}\nFetch this feed and take its most recent entry as the latest occurrence. \
+Compare that entry against the current known state and only treat it as new if it is newer \
+than what was last communicated.\n",
+            url
+        ))
         .unwrap_or_default();
 
     let last_notification_section = if let Some(s) = state {
@@ -254,11 +304,11 @@ pub fn build_recurring_prompt(subject: &Subject, state: Option<&RecurringState>)
     format!(r#"You are researching the next occurrence of a recurring event.
 
 EVENT: {event_name}
-{search_terms_section}{notes_section}
+{search_terms_section}{rss_section}{notes_section}
 {state_info}
 {last_notification_section}
 TASK:
-1. Search for information about the next upcoming occurrence of this event
+1. {research_step}
 2. Evaluate the credibility of sources
 3. Compare findings to the current known state
 4. Determine if the user should be notified
@@ -292,12 +342,77 @@ of the same facts is NOT a change. Examples:
 Respond with ONLY the JSON object, no other text."#,
         event_name = event_name,
         search_terms_section = search_terms_section,
+        research_step = research_step,
+        rss_section = rss_section,
         notes_section = notes_section,
         state_info = state_info,
         last_notification_section = last_notification_section,
     )
 }
 
+/// Prepend findings from earlier checks according to the configured context strategy
+pub fn with_context(config: &ClaudeConfig, prompt: String, state: Option<&SubjectState>) -> String {
+    let Some(state) = state else {
+        return prompt;
+    };
+
+    let context = match config.context_window_strategy {
+        ContextStrategy::Stateless => None,
+        ContextStrategy::Summary => state.context_summary().map(|s| s.to_string()),
+        ContextStrategy::Rolling => {
+            let mut exchanges: Vec<String> = state.history().iter()
+                .rev()
+                .filter(|e| e.event == "check")
+                .filter_map(|e| {
+                    let raw = e.raw_response.as_deref()?;
+                    let summary = serde_json::from_str::<serde_json::Value>(raw).ok()?
+                        .get("summary")?
+                        .as_str()?
+                        .to_string();
+                    Some(format!("- {}: {}", e.timestamp.format("%Y-%m-%d"), summary))
+                })
+                .take(config.context_exchanges as usize)
+                .collect();
+            exchanges.reverse();
+            if exchanges.is_empty() { None } else { Some(exchanges.join("\n")) }
+        }
+    };
+
+    match context {
+        Some(context) => format!(
+            "PREVIOUS FINDINGS (from earlier checks, verify before relying on them):\n{}\n\n{}",
+            context, prompt
+        ),
+        None => prompt,
+    }
+}
+
+/// Build the check prompt for any subject type
+pub fn build_check_prompt(subject: &Subject, state: Option<&SubjectState>, search_available: bool) -> String {
+    match (subject.subject_type, state) {
+        (SubjectType::Release, Some(SubjectState::Release(rs))) => build_release_prompt(subject, Some(rs), search_available),
+        (SubjectType::Release, _) => build_release_prompt(subject, None, search_available),
+        (SubjectType::Question, Some(SubjectState::Question(qs))) => build_question_prompt(subject, Some(qs), search_available),
+        (SubjectType::Question, _) => build_question_prompt(subject, None, search_available),
+        (SubjectType::Recurring, Some(SubjectState::Recurring(rs))) => build_recurring_prompt(subject, Some(rs), search_available),
+        (SubjectType::Recurring, _) => build_recurring_prompt(subject, None, search_available),
+    }
+}
+
+/// Combine several user queries into one identification input
+pub fn combine_identification_queries(queries: &[String]) -> String {
+    let items: Vec<String> = queries
+        .iter()
+        .enumerate()
+        .map(|(i, q)| format!("{}. {}", i + 1, q))
+        .collect();
+    format!(
+        "{} separate items, return exactly one best match for each, in the same order: {}",
+        queries.len(),
+        items.join("; ")
+    )
+}
+
 /// Build the prompt for AI-assisted subject addition (does NOT reveal current state)
 pub fn build_subject_identification_prompt(user_input: &str) -> String {
     format!(r#"The user wants to add a subject to track for release date monitoring or question answering.
@@ -331,3 +446,13 @@ Respond with ONLY the JSON object, no other text."#,
         user_input = user_input
     )
 }
+
+/// Build a lightweight prompt that shows what a set of search terms currently finds
+pub fn build_search_verification_prompt(search_terms: &[String]) -> String {
+    format!(r#"What is the latest news about: {terms}?
+
+Search the web and answer in ONE short sentence (under 25 words) summarizing the most relevant recent finding.
+Respond with ONLY that sentence, no other text."#,
+        terms = search_terms.join(", ")
+    )
+}
@@ -1,3 +1,4 @@
+use crate::config::SubjectType;
 use crate::error::{HeadsupError, Result};
 use crate::state::{Confidence, DatePrecision, ReleaseStatus};
 use serde::{Deserialize, Serialize};
@@ -72,6 +73,27 @@ pub enum ClaudeResponse {
     Recurring(RecurringResponse),
 }
 
+impl ClaudeResponse {
+    /// Source URL cited by the response, if any
+    pub fn source_url(&self) -> Option<&str> {
+        let url = match self {
+            ClaudeResponse::Release(r) => r.source_url.as_deref(),
+            ClaudeResponse::Question(r) => r.source_url.as_deref(),
+            ClaudeResponse::Recurring(r) => r.source_url.as_deref(),
+        };
+        url.filter(|u| !u.trim().is_empty())
+    }
+
+    /// Confidence of the response
+    pub fn confidence(&self) -> Confidence {
+        match self {
+            ClaudeResponse::Release(r) => r.confidence,
+            ClaudeResponse::Question(r) => r.confidence,
+            ClaudeResponse::Recurring(r) => r.confidence,
+        }
+    }
+}
+
 /// Parse a release response from Claude's raw output
 pub fn parse_release_response(raw: &str) -> Result<ReleaseResponse> {
     let json_str = extract_json(raw)?;
@@ -93,6 +115,15 @@ pub fn parse_recurring_response(raw: &str) -> Result<RecurringResponse> {
         .map_err(|e| HeadsupError::ClaudeParseError(format!("Invalid recurring response: {}", e)))
 }
 
+/// Parse raw output as the response type expected for a subject
+pub fn parse_response(subject_type: SubjectType, raw: &str) -> Result<ClaudeResponse> {
+    Ok(match subject_type {
+        SubjectType::Release => ClaudeResponse::Release(parse_release_response(raw)?),
+        SubjectType::Question => ClaudeResponse::Question(parse_question_response(raw)?),
+        SubjectType::Recurring => ClaudeResponse::Recurring(parse_recurring_response(raw)?),
+    })
+}
+
 /// Parse a subject identification response from Claude's raw output
 pub fn parse_subject_identification_response(raw: &str) -> Result<SubjectIdentificationResponse> {
     let json_str = extract_json(raw)?;
@@ -101,7 +132,7 @@ pub fn parse_subject_identification_response(raw: &str) -> Result<SubjectIdentif
 }
 
 /// Extract JSON from Claude's response, handling potential markdown code blocks
-fn extract_json(raw: &str) -> Result<String> {
+pub fn extract_json(raw: &str) -> Result<String> {
     let trimmed = raw.trim();
 
     // Try to extract from markdown code block
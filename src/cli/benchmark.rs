@@ -0,0 +1,124 @@
+use crate::claude::{build_question_prompt, build_recurring_prompt, build_release_prompt, extract_json};
+use crate::config::{Category, Subject, SubjectType};
+use crate::state::{Confidence, DatePrecision, QuestionState, RecurringState, ReleaseState, ReleaseStatus};
+use chrono::Utc;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Number of iterations per benchmarked function
+const ITERATIONS: usize = 1000;
+
+/// Representative raw responses for JSON extraction
+const FIXTURES: &[&str] = &[
+    r#"{"subject": "GTA 6", "found_release_date": "2026-05-26", "release_date_precision": "exact", "confidence": "official", "status": "announced", "summary": "Rockstar confirmed the date", "source_url": "https://www.rockstargames.com", "source_name": "Rockstar Newswire", "should_notify": true, "notify_reason": "New date"}"#,
+    "```json\n{\"question\": \"Who will be the next James Bond?\", \"found_answer\": null, \"confidence\": \"rumor\", \"is_definitive\": false, \"summary\": \"No official casting yet\", \"source_url\": null, \"source_name\": null, \"should_notify\": false, \"notify_reason\": null}\n```",
+    "Here is what I found:\n{\"event_name\": \"WWDC\", \"next_occurrence_date\": \"2026-06-08\", \"next_occurrence_name\": \"WWDC 2026\", \"date_precision\": \"exact\", \"confidence\": \"official\", \"summary\": \"Apple announced WWDC\", \"source_url\": \"https://developer.apple.com/wwdc\", \"source_name\": \"Apple\", \"should_notify\": true, \"notify_reason\": \"Date announced\"}\nLet me know if you need more.",
+];
+
+/// Benchmark prompt construction and response parsing without calling any backend
+pub fn run_benchmark() {
+    let release = Subject {
+        key: "gta-6".to_string(),
+        name: "GTA 6".to_string(),
+        subject_type: SubjectType::Release,
+        category: Some(Category::Game),
+        search_terms: vec!["GTA 6 release date".to_string(), "GTA VI launch".to_string()],
+        notes: Some("Rockstar's next major release".to_string()),
+        ..Default::default()
+    };
+    let release_state = ReleaseState {
+        last_checked: Some(Utc::now()),
+        known_release_date: Some("2026".to_string()),
+        release_date_precision: DatePrecision::Year,
+        confidence: Confidence::Reliable,
+        status: ReleaseStatus::Announced,
+        last_notified: Some(Utc::now()),
+        last_notified_summary: Some("Expected in 2026".to_string()),
+        last_notified_value: Some("2026".to_string()),
+        ..Default::default()
+    };
+
+    let question = Subject {
+        key: "bond".to_string(),
+        name: "Next James Bond".to_string(),
+        subject_type: SubjectType::Question,
+        question: Some("Who will be the next James Bond actor?".to_string()),
+        search_terms: vec!["next James Bond actor".to_string()],
+        ..Default::default()
+    };
+    let question_state = QuestionState {
+        last_checked: Some(Utc::now()),
+        current_answer: Some("Not yet announced".to_string()),
+        confidence: Confidence::Rumor,
+        ..Default::default()
+    };
+
+    let recurring = Subject {
+        key: "wwdc".to_string(),
+        name: "Apple WWDC".to_string(),
+        subject_type: SubjectType::Recurring,
+        event_name: Some("WWDC".to_string()),
+        ..Default::default()
+    };
+    let recurring_state = RecurringState {
+        last_checked: Some(Utc::now()),
+        next_occurrence_date: Some("2026-06".to_string()),
+        date_precision: DatePrecision::Month,
+        last_occurrence_date: Some("2025-06-09".to_string()),
+        occurrence_count: 3,
+        ..Default::default()
+    };
+
+    println!(
+        "{:<24} {:>12} {:>12} {:>12}  ({} iterations)",
+        "FUNCTION", "MEDIAN", "P95", "P99", ITERATIONS
+    );
+    println!("{}", "-".repeat(80));
+
+    report("build_release_prompt", measure(|| {
+        black_box(build_release_prompt(&release, Some(&release_state), true));
+    }));
+    report("build_question_prompt", measure(|| {
+        black_box(build_question_prompt(&question, Some(&question_state), true));
+    }));
+    report("build_recurring_prompt", measure(|| {
+        black_box(build_recurring_prompt(&recurring, Some(&recurring_state), true));
+    }));
+    report("extract_json", measure(|| {
+        for fixture in FIXTURES {
+            let _ = black_box(extract_json(fixture));
+        }
+    }));
+}
+
+/// Run a function repeatedly and return the sorted timings
+fn measure<F: FnMut()>(mut f: F) -> Vec<Duration> {
+    let mut timings: Vec<Duration> = (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    timings.sort();
+    timings
+}
+
+fn report(name: &str, sorted: Vec<Duration>) {
+    println!(
+        "{:<24} {:>12} {:>12} {:>12}",
+        name,
+        format!("{:.2?}", percentile(&sorted, 0.50)),
+        format!("{:.2?}", percentile(&sorted, 0.95)),
+        format!("{:.2?}", percentile(&sorted, 0.99)),
+    );
+}
+
+/// Nearest-rank percentile of an already sorted list of timings
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx]
+}
@@ -0,0 +1,20 @@
+use crate::cache;
+use crate::cli::CacheCommands;
+use crate::config;
+use crate::error::Result;
+use crate::ui;
+
+/// Run cache subcommands
+pub fn run_cache(command: CacheCommands) -> Result<()> {
+    match command {
+        CacheCommands::Clear => clear_cache(),
+    }
+}
+
+fn clear_cache() -> Result<()> {
+    // Clear the default location even when the cache is not configured
+    let cache_config = config::load_config()?.cache.unwrap_or_default();
+    let removed = cache::clear(&cache_config)?;
+    ui::print_success(&format!("Removed {} cached responses", removed));
+    Ok(())
+}
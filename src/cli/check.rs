@@ -1,43 +1,352 @@
+use crate::cli::{benchmark, compare, MetricsFormat};
+use crate::cli::fixtures::{self, FixtureWriter};
+use crate::cache;
 use crate::claude::{self, ClaudeResponse, QuestionResponse, RecurringResponse, ReleaseResponse};
-use crate::config::{self, Backend, Config, Subject};
+use crate::config::{self, Backend, CacheConfig, Config, ContextStrategy, Subject, SubjectPatch, SubjectType};
 use crate::email::{self, build_question_email, build_recurring_email, build_release_email};
 use crate::error::{ExitStatus, HeadsupError, Result};
+use crate::metrics::{prometheus, pushgateway};
+use crate::ollama;
+use crate::openai;
 use crate::perplexity;
+use crate::report;
+use crate::sync;
 use crate::state::{
-    self, DatePrecision, HistoryEntry, PendingNotification, State, SubjectState,
+    self, Confidence, DatePrecision, HistoryEntry, PendingNotification, ReleaseStatus, State,
+    SubjectState,
 };
 use crate::ui;
-use chrono::Utc;
+use crate::util::network;
+use crate::util::rate_limiter::RateLimiter;
+use crate::util::scheduler::SmartScheduler;
+use crate::telegram;
+use crate::webhook::{self, FailurePayload};
+use chrono::{DateTime, Datelike, Local, Utc};
 use futures::future::join_all;
+use fs2::FileExt;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
 
 /// Result of checking a single subject
+#[derive(Debug, Serialize)]
 pub struct CheckResult {
+    pub subject_id: Uuid,
+    pub subject_name: String,
     pub success: bool,
     pub notified: bool,
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<&'static str>,
+    /// Notification for the webhook and Telegram channels, sent once all subjects are processed
+    #[serde(skip)]
+    pub channel_notification: Option<PendingNotification>,
+    /// Backend that answered, or the last one tried when every backend failed
+    pub backend_used: String,
+}
+
+/// Where a check sends the notifications it triggers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delivery {
+    /// Queue for 'headsup notify' (--no-notify)
+    Queue,
+    /// Send by email, and to the webhook when one is configured
+    Email,
+    /// Send only to the configured webhook (--webhook-only)
+    WebhookOnly,
+}
+
+/// Index of the first subject checked with each prompt in this run
+type PromptCache = HashMap<String, usize>;
+
+/// A subject with its backend result, latency in milliseconds, and the backend that produced it
+type CheckOutcome = (Subject, Result<(ClaudeResponse, String)>, u64, Backend);
+
+/// Default line written to --status-file
+const DEFAULT_STATUS_FORMAT: &str = "headsup: {{subjects}} subjects, {{pending}} pending, last run {{last_run}}";
+
+/// How long to wait for another run to release the result file
+const RESULT_FILE_LOCK_TIMEOUT_SECS: u64 = 5;
+
+/// How much earlier than its check interval a subject already counts as due
+const INTERVAL_TOLERANCE_MINUTES: i64 = 5;
+
+/// Parse a `--tag-all` value, rejecting tags `Subject::validate` would refuse
+pub fn parse_tag(value: &str) -> std::result::Result<String, String> {
+    if Subject::is_valid_tag(value) {
+        Ok(value.to_string())
+    } else {
+        Err(format!("Tag '{}' must be 1-32 lowercase letters, numbers, and hyphens", value))
+    }
+}
+
+/// Options for a check run
+#[derive(Debug, Default)]
+pub struct CheckOptions {
+    /// Check only this subject (by key or UUID)
+    pub subject_key: Option<String>,
+    /// Check only subjects with this tag
+    pub tag: Option<String>,
+    /// Check only subjects in this group
+    pub group: Option<String>,
+    pub dry_run: bool,
+    pub force: bool,
+    pub no_notify: bool,
+    /// Send notifications to the configured webhook only, skipping email
+    pub webhook_only: bool,
+    /// Measure prompt construction and parsing instead of checking
+    pub benchmark: bool,
+    /// Per-subject AI timeout override in seconds
+    pub ai_timeout: Option<u64>,
+    /// Treat responses without a source URL as failures
+    pub fail_on_no_sources: bool,
+    /// Store the unparsed AI output in history
+    pub annotate_response: bool,
+    /// Skip subjects whose last check took longer than this many milliseconds
+    pub max_latency: Option<u64>,
+    /// Write an HTML report to this path after checking
+    pub report: Option<PathBuf>,
+    /// Save raw AI responses and prompts to this directory
+    pub save_fixtures: Option<PathBuf>,
+    /// Append a JSONL run summary to this file
+    pub result_file: Option<PathBuf>,
+    /// Tag the history entry of every successful check with this label
+    pub tag_all: Option<String>,
+    /// POST a JSON alert to this URL for each failed subject
+    pub webhook_on_failure: Option<String>,
+    /// Pull state from and push it to the configured S3 bucket
+    pub cloud_sync: bool,
+    /// Backend calls per minute for this run, overriding the config
+    pub rate_limit_backend: Option<u32>,
+    /// Maximum number of concurrent subject checks, overriding the config
+    pub parallel: Option<u32>,
+    /// Write run metrics in this format to this file
+    pub metrics: Option<(MetricsFormat, PathBuf)>,
+    /// Overwrite this file with a one-line status summary
+    pub status_file: Option<PathBuf>,
+    /// Template for the status file line
+    pub status_format: Option<String>,
+    /// Wait up to this many seconds for network connectivity before checking
+    pub wait_for_internet: Option<u64>,
+    /// Call the backend once per distinct prompt and share the response
+    pub cache_prompts: bool,
+    /// Replay responses saved in this directory instead of calling the backend
+    pub save_responses: Option<PathBuf>,
+    /// Push run metrics to a Prometheus Pushgateway
+    pub prometheus_push: bool,
+    /// Pushgateway URL, overriding settings.prometheus_pushgateway_url
+    pub pushgateway_url: Option<String>,
+    /// Print results as JSON instead of progress lines
+    pub json: bool,
+    /// Check with both backends and print a comparison instead of updating state
+    pub compare_backends: bool,
+    /// Ignore settings.ai_backend_fallback
+    pub no_fallback: bool,
+}
+
+/// Machine-readable summary of a check run
+#[derive(Debug, Serialize)]
+pub struct CheckSummary<'a> {
+    pub run_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub checked: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub notifications: usize,
+    pub results: &'a [CheckResult],
+}
+
+/// One subject's outcome in `check --json` output
+#[derive(Debug, Serialize)]
+pub struct CheckResultJson {
+    pub subject_key: String,
+    pub subject_name: String,
+    pub subject_type: SubjectType,
+    pub success: bool,
+    pub notified: bool,
+    pub error: Option<String>,
+    pub error_type: Option<&'static str>,
+    /// Release status, "answered"/"open" for questions, "scheduled"/"unknown" for recurring events
+    pub new_status: Option<String>,
+    pub source_url: Option<String>,
+}
+
+/// Extra information about a backend response recorded in history
+struct ResponseMetadata {
+    raw_output: Option<String>,
+    latency_ms: u64,
+    tags: Vec<String>,
+    backend: Backend,
 }
 
 /// Run the check command
-pub async fn run_check(
-    subject_key: Option<String>,
-    dry_run: bool,
-    _force: bool,
-    no_notify: bool,
-) -> Result<ExitStatus> {
-    let config = config::load_config()?;
+pub async fn run_check(options: CheckOptions) -> Result<ExitStatus> {
+    if options.benchmark {
+        benchmark::run_benchmark();
+        return Ok(ExitStatus::Success);
+    }
+
+    let CheckOptions {
+        subject_key,
+        tag,
+        group,
+        dry_run,
+        force,
+        no_notify,
+        webhook_only,
+        ai_timeout,
+        fail_on_no_sources,
+        annotate_response,
+        max_latency,
+        report,
+        save_fixtures,
+        result_file,
+        tag_all,
+        webhook_on_failure,
+        cloud_sync,
+        rate_limit_backend,
+        parallel,
+        metrics,
+        status_file,
+        status_format,
+        wait_for_internet,
+        cache_prompts,
+        save_responses,
+        prometheus_push,
+        pushgateway_url,
+        json,
+        compare_backends,
+        no_fallback,
+        ..
+    } = options;
+
+    // Keep stdout clean for the JSON array; errors still go to stderr
+    if json {
+        ui::set_quiet_mode(true);
+    }
+
+    if save_responses.is_some() && !dry_run {
+        return Err(HeadsupError::Config("--save-responses requires --dry-run".to_string()));
+    }
+
+    let mut config = config::load_config()?;
+
+    if webhook_only && config.webhook.is_none() {
+        return Err(HeadsupError::Config("--webhook-only requires a [webhook] section in the config".to_string()));
+    }
+    let delivery = if no_notify {
+        Delivery::Queue
+    } else if webhook_only {
+        Delivery::WebhookOnly
+    } else {
+        Delivery::Email
+    };
+
+    let pushgateway_url = if prometheus_push {
+        let url = pushgateway_url
+            .or_else(|| config.settings.prometheus_pushgateway_url.clone())
+            .ok_or_else(|| {
+                HeadsupError::Config(
+                    "--output prometheus-push requires --pushgateway-url or settings.prometheus_pushgateway_url".to_string(),
+                )
+            })?;
+        Some(url)
+    } else {
+        None
+    };
+
+    // Wait before taking the state lock so a slow network doesn't block other commands
+    if let Some(seconds) = wait_for_internet {
+        let url = config.settings.internet_check_url.as_deref().unwrap_or(network::DEFAULT_INTERNET_CHECK_URL);
+        ui::print_info(&format!("Waiting for internet connectivity ({})...", url));
+        network::wait_for_internet(url, Duration::from_secs(seconds)).await?;
+    }
+
     let (mut state, lock) = state::load_state()?;
 
+    let cloud_sync = if cloud_sync {
+        let sync = config.settings.cloud_sync.clone().ok_or_else(|| {
+            HeadsupError::Config("--cloud-sync requires a [settings.cloud_sync] section".to_string())
+        })?;
+        if sync::pull_state(&sync, &mut state)? {
+            ui::print_info("Merged newer subject state from cloud sync");
+        }
+        Some(sync)
+    } else {
+        None
+    };
+
+    // Archive subjects whose expiry date has passed before choosing what to check
+    let today_local = Local::now().date_naive();
+    if config.subjects.iter().any(|s| !s.archived && s.is_expired(today_local)) {
+        let expired: Vec<Subject> = if dry_run {
+            config.subjects.iter().filter(|s| !s.archived && s.is_expired(today_local)).cloned().collect()
+        } else {
+            config::archive_expired_subjects(today_local)?
+        };
+        for subject in &expired {
+            if dry_run {
+                ui::print_info(&format!("Would archive '{}' (expired {})", subject.name, subject.expiry_date.unwrap_or_default()));
+                continue;
+            }
+            ui::print_info(&format!("Archived '{}' (expired {})", subject.name, subject.expiry_date.unwrap_or_default()));
+            if let Some(subject_state) = state.subjects.get_mut(&subject.id) {
+                subject_state.set_archived_at(Some(Utc::now()));
+            }
+            if !no_notify {
+                add_expiry_notification(&config, subject, &mut state);
+            }
+        }
+        config.archive_expired(today_local);
+    }
+
+    // Apply per-run timeout override (never written back to config)
+    if let Some(seconds) = ai_timeout {
+        config.claude.timeout_seconds = seconds;
+        config.perplexity.timeout_seconds = seconds;
+        config.openai.timeout_seconds = seconds;
+        config.ollama.timeout_seconds = seconds;
+    }
+
     // Get backend-specific settings
-    let (total_run_timeout, max_searches) = match config.backend {
+    let (total_run_timeout, max_searches, max_calls_per_minute, max_parallel) = match config.backend {
         Backend::Claude => (
             config.claude.total_run_timeout_seconds,
             config.claude.max_searches_per_run,
+            config.claude.max_calls_per_minute,
+            config.claude.parallel,
         ),
         Backend::Perplexity => (
             config.perplexity.total_run_timeout_seconds,
             config.perplexity.max_searches_per_run,
+            None,
+            None,
+        ),
+        Backend::OpenAi => (
+            config.openai.total_run_timeout_seconds,
+            config.openai.max_searches_per_run,
+            None,
+            None,
+        ),
+        Backend::Ollama => (
+            config.ollama.total_run_timeout_seconds,
+            config.ollama.max_searches_per_run,
+            None,
+            None,
         ),
     };
+    let rate_limiter = rate_limit_backend
+        .or(max_calls_per_minute)
+        .map(|n| Arc::new(RateLimiter::new(n)));
+    let concurrency = parallel
+        .or(max_parallel)
+        .map(|n| Arc::new(Semaphore::new(n as usize)));
 
     // Start time for total timeout
     let start = Instant::now();
@@ -46,19 +355,117 @@ pub async fn run_check(
     } else {
         None
     };
+    let limits = CallLimits {
+        rate_limiter,
+        concurrency,
+        deadline: total_timeout.map(|timeout| start + timeout),
+    };
+
+    if let Some(ref name) = group {
+        if config.find_group(name).is_none() {
+            return Err(HeadsupError::Config(format!("No group named '{}'", name)));
+        }
+    }
 
     // Determine which subjects to check
     let subjects_to_check: Vec<&Subject> = if let Some(ref key) = subject_key {
         let subject = config
             .find_subject(key)
             .ok_or_else(|| HeadsupError::SubjectNotFound(key.clone()))?;
+        if subject.archived {
+            return Err(HeadsupError::Config(format!(
+                "'{}' is archived; run 'headsup subjects unarchive {}' to check it again",
+                subject.name, subject.key
+            )));
+        }
         vec![subject]
     } else {
-        config.subjects.iter().filter(|s| s.enabled).collect()
+        let today = Utc::now().weekday();
+        config
+            .subjects
+            .iter()
+            .filter(|s| s.enabled && !s.archived)
+            .filter(|s| tag.as_deref().is_none_or(|t| s.has_tag(t)))
+            .filter(|s| group.as_deref().is_none_or(|g| config.group_of(s).is_some_and(|sg| sg.name == g)))
+            .filter(|s| {
+                let paused = s.is_paused(Utc::now());
+                if paused {
+                    ui::print_info(&format!(
+                        "  Skipping '{}' (paused until {})",
+                        s.name,
+                        s.paused_until.unwrap_or_default().with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                    ));
+                }
+                !paused
+            })
+            .filter(|s| {
+                let allowed = s.can_check_on(today);
+                if !allowed {
+                    ui::print_info(&format!("  Skipping '{}' (not scheduled for {})", s.name, today));
+                }
+                allowed
+            })
+            .filter(|s| {
+                if !s.smart_check || force {
+                    return true;
+                }
+                let history = state.subjects.get(&s.id).map(|st| st.history()).unwrap_or_default();
+                let next = SmartScheduler::next_check_time(history);
+                let due = next <= Utc::now();
+                if !due {
+                    ui::print_info(&format!(
+                        "  Skipping '{}' (smart check, next on {})",
+                        s.name,
+                        next.with_timezone(&Local).format("%Y-%m-%d")
+                    ));
+                }
+                due
+            })
+            .filter(|s| {
+                let Some(interval) = config.check_interval_hours(s) else {
+                    return true;
+                };
+                if force {
+                    return true;
+                }
+                let Some(last_checked) = state.subjects.get(&s.id).and_then(|st| st.last_checked()) else {
+                    return true;
+                };
+                let due = interval_elapsed(last_checked, Utc::now(), interval);
+                if !due {
+                    ui::print_info(&format!(
+                        "  Skipping '{}' (checked {} hours ago, interval is {})",
+                        s.name, (Utc::now() - last_checked).num_hours(), interval
+                    ));
+                }
+                due
+            })
+            .filter(|s| {
+                let Some(max_ms) = max_latency.filter(|_| !force) else {
+                    return true;
+                };
+                let last_latency = state.subjects.get(&s.id)
+                    .and_then(|st| st.history().last())
+                    .and_then(|entry| entry.latency_ms);
+                match last_latency {
+                    Some(ms) if ms > max_ms => {
+                        ui::print_warning(&format!(
+                            "  Skipping '{}' (last check took {} ms, limit {} ms)",
+                            s.name, ms, max_ms
+                        ));
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect()
     };
 
     if subjects_to_check.is_empty() {
         ui::print_info("No subjects to check");
+        if json {
+            println!("[]");
+        }
         return Ok(ExitStatus::Success);
     }
 
@@ -68,15 +475,34 @@ pub async fn run_check(
         .take(max_searches as usize)
         .collect();
 
+    if compare_backends {
+        return compare::run_comparison(&config, &subjects_to_check, &state).await;
+    }
+
     ui::print_info(&format!(
-        "Checking {} subjects in parallel using {} backend...",
+        "Checking {} subjects in parallel{} using {} backend...",
         subjects_to_check.len(),
+        parallel.or(max_parallel).map(|n| format!(" ({} at a time)", n)).unwrap_or_default(),
         match config.backend {
             Backend::Claude => "Claude",
             Backend::Perplexity => "Perplexity",
+            Backend::OpenAi => "OpenAI",
+            Backend::Ollama => "Ollama",
         }
     ));
 
+    let fallbacks: Vec<Backend> = if no_fallback {
+        Vec::new()
+    } else {
+        let mut chain = vec![config.backend];
+        for backend in &config.settings.ai_backend_fallback {
+            if !chain.contains(backend) {
+                chain.push(*backend);
+            }
+        }
+        chain.split_off(1)
+    };
+
     // Clone data for parallel execution
     let config_clone = config.clone();
     let subjects_owned: Vec<Subject> = subjects_to_check.iter().map(|s| (*s).clone()).collect();
@@ -85,16 +511,53 @@ pub async fn run_check(
         .map(|s| state.subjects.get(&s.id).cloned())
         .collect();
 
+    // With --cache-prompts, subjects with an identical prompt reuse the first one's response
+    let duplicate_of: Vec<Option<usize>> = if cache_prompts {
+        let mut cache: PromptCache = HashMap::new();
+        subjects_owned
+            .iter()
+            .zip(&state_snapshots)
+            .enumerate()
+            .map(|(i, (subject, snapshot))| {
+                let prompt = subject_prompt(&config, subject, snapshot.as_ref());
+                match cache.get(&prompt) {
+                    Some(&first) => Some(first),
+                    None => {
+                        cache.insert(prompt, i);
+                        None
+                    }
+                }
+            })
+            .collect()
+    } else {
+        vec![None; subjects_owned.len()]
+    };
+
     // Create futures for parallel execution
     let futures: Vec<_> = subjects_owned
         .into_iter()
-        .zip(state_snapshots.into_iter())
-        .map(|(subject, state_snapshot)| {
+        .zip(state_snapshots)
+        .zip(duplicate_of.iter().map(Option::is_some))
+        .map(|((subject, state_snapshot), cached)| {
             let cfg = config_clone.clone();
+            let limits = limits.clone();
+            let save_responses = save_responses.clone();
+            let fallbacks = fallbacks.clone();
             async move {
+                if cached {
+                    return (subject, None, 0, cfg.backend);
+                }
+                if let Some(dir) = save_responses {
+                    ui::print_info(&format!("  Replaying '{}'...", subject.name));
+                    let result = fixtures::load_response(&dir, &subject);
+                    return (subject, Some(result), 0, cfg.backend);
+                }
                 ui::print_info(&format!("  Starting '{}'...", subject.name));
-                let result = check_subject_parallel(&cfg, &subject, state_snapshot.as_ref()).await;
-                (subject, result)
+                let started = Instant::now();
+                let (result, backend) =
+                    check_with_fallback(&cfg, &limits, &fallbacks, &subject, state_snapshot.as_ref()).await;
+                let latency_ms = started.elapsed().as_millis() as u64;
+                (subject, Some(result), latency_ms, backend)
             }
         })
         .collect();
@@ -113,32 +576,156 @@ pub async fn run_check(
         join_all(futures).await
     };
 
+    // Fill in responses for subjects that shared a prompt (the first one always comes earlier)
+    let mut resolved: Vec<CheckOutcome> = Vec::new();
+    for (i, (subject, result, latency_ms, backend)) in parallel_results.into_iter().enumerate() {
+        let entry = match (result, duplicate_of[i]) {
+            (Some(result), _) => (subject, result, latency_ms, backend),
+            (None, Some(first)) => {
+                let (_, first_result, first_latency, first_backend) = &resolved[first];
+                ui::print_info(&format!("  Reusing cached response for '{}'", subject.name));
+                let result = match first_result {
+                    Ok((response, raw)) => Ok((response.clone(), raw.clone())),
+                    Err(e) => Err(HeadsupError::Claude(format!("shared prompt failed: {}", e))),
+                };
+                (subject, result, *first_latency, *first_backend)
+            }
+            (None, None) => unreachable!("only cached subjects skip the backend call"),
+        };
+        resolved.push(entry);
+    }
+    let parallel_results = resolved;
+
+    let require_source = fail_on_no_sources || config.settings.require_source_url;
+    let store_raw = annotate_response || config.settings.store_raw_responses;
+
+    let mut fixture_writer = save_fixtures.as_deref().map(FixtureWriter::new).transpose()?;
+
     // Process results sequentially to update state
     let mut results: Vec<CheckResult> = Vec::new();
-    for (subject, check_result) in parallel_results {
+    let mut found_sources: Vec<(Uuid, String)> = Vec::new();
+    let mut released: Vec<Uuid> = Vec::new();
+    let mut latencies: HashMap<Uuid, u64> = HashMap::new();
+    let mut source_urls: HashMap<Uuid, String> = HashMap::new();
+    for (subject, check_result, latency_ms, backend) in parallel_results {
+        latencies.insert(subject.id, latency_ms);
+        if let Ok((response, _)) = &check_result {
+            if let Some(url) = response.source_url() {
+                source_urls.insert(subject.id, url.to_string());
+            }
+        }
+        if let (Some(writer), Ok((_, raw_output))) = (fixture_writer.as_mut(), &check_result) {
+            // State for this subject is untouched until it is processed below
+            let prompt = subject_prompt(&config, &subject, state.subjects.get(&subject.id));
+            writer.save(&subject, prompt, raw_output)?;
+        }
+
         match check_result {
-            Ok((response, _)) => {
+            Ok((response, _)) if require_source && response.source_url().is_none() => {
+                let result = process_failed_check(
+                    &config,
+                    &subject,
+                    HeadsupError::MissingSourceUrl,
+                    backend,
+                    &mut state,
+                    dry_run,
+                );
+                results.push(result);
+            }
+            Ok((response, raw_output)) => {
+                if let Some(source) = credible_source(&response) {
+                    found_sources.push((subject.id, source));
+                }
+                let is_released = matches!(&response, ClaudeResponse::Release(r) if r.status == ReleaseStatus::Released);
+                let metadata = ResponseMetadata {
+                    raw_output: if store_raw { Some(raw_output) } else { None },
+                    latency_ms,
+                    tags: tag_all.iter().cloned().collect(),
+                    backend,
+                };
                 let result = process_successful_check(
                     &config,
                     &subject,
                     response,
+                    &metadata,
                     &mut state,
                     dry_run,
-                    no_notify,
+                    delivery,
                 );
+                if is_released && result.success && config.settings.auto_archive_released && !dry_run {
+                    if let Some(subject_state) = state.subjects.get_mut(&subject.id) {
+                        subject_state.set_archived_at(Some(Utc::now()));
+                    }
+                    ui::print_info(&format!("  Archiving '{}' (released)", subject.name));
+                    released.push(subject.id);
+                }
                 results.push(result);
             }
             Err(e) => {
-                let result = process_failed_check(&config, &subject, e, &mut state, dry_run);
+                let result = process_failed_check(&config, &subject, e, backend, &mut state, dry_run);
                 results.push(result);
             }
         }
     }
 
+    if let Some(ref url) = webhook_on_failure {
+        send_failure_webhooks(url, &config, &state, &results).await;
+    }
+
+    send_channel_notifications(&config, &mut state, &mut results, delivery).await;
+
     // Update state
     state.last_run = Some(Utc::now());
+    state.record_check_run(
+        Local::now().date_naive(),
+        backend_name(config.backend),
+        results.len() as u32,
+        results.iter().filter(|r| !r.success).count() as u32,
+        results.iter().filter(|r| r.notified).count() as u32,
+    );
     if !dry_run {
-        state::save_state(&state, &lock)?;
+        state::save_state(&mut state, &lock)?;
+        let mut patches = found_source_patches(&config, &found_sources);
+        patches.extend(released.iter().map(|&id| (id, SubjectPatch::Archived(true))));
+        for result in &results {
+            let previous = config.subjects.iter()
+                .find(|s| s.id == result.subject_id)
+                .and_then(|s| s.last_error.as_deref());
+            if previous != result.error.as_deref() {
+                patches.push((result.subject_id, SubjectPatch::LastError(result.error.clone())));
+            }
+        }
+        // One in-place write per run, so an unattended check never reformats the user's config
+        config::patch_subjects(&patches)?;
+        if let Some(ref sync) = cloud_sync {
+            sync::push_state(sync)?;
+        }
+    }
+
+    if let (Some(writer), Some(dir)) = (fixture_writer, save_fixtures.as_ref()) {
+        let count = writer.finish()?;
+        ui::print_info(&format!("Fixture manifest lists {} responses in {}", count, dir.display()));
+    }
+
+    if let Some(path) = report {
+        fs::write(&path, report::generate_html_report(&config, &state, &results))?;
+        ui::print_info(&format!("Report written to {}", path.display()));
+    }
+
+    if let Some((MetricsFormat::Prometheus, path)) = metrics {
+        // Write then rename so a scraper never reads a partial file
+        let tmp = path.with_extension("prom.tmp");
+        fs::write(&tmp, prometheus::render(&config, &state, &results, &latencies))?;
+        fs::rename(&tmp, &path)?;
+    }
+
+    if let Some(ref url) = pushgateway_url {
+        let job = config.settings.prometheus_job_name.as_deref().unwrap_or(pushgateway::DEFAULT_JOB_NAME);
+        let metrics = prometheus::render(&config, &state, &results, &latencies);
+        match pushgateway::push(url, job, config.settings.prometheus_auth_command.as_deref(), metrics).await {
+            Ok(()) => ui::print_info(&format!("Pushed metrics to {}", url)),
+            Err(e) => ui::print_warning(&format!("Failed to push metrics: {}", e)),
+        }
     }
 
     // Determine exit status
@@ -146,6 +733,29 @@ pub async fn run_check(
     let failure_count = results.iter().filter(|r| !r.success).count();
     let notify_count = results.iter().filter(|r| r.notified).count();
 
+    if let Some(ref path) = result_file {
+        let summary = CheckSummary {
+            run_id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            checked: results.len(),
+            succeeded: success_count,
+            failed: failure_count,
+            notifications: notify_count,
+            results: &results,
+        };
+        append_result_file(path, &summary)?;
+    }
+
+    if let Some(ref path) = status_file {
+        let template = status_format.as_deref().unwrap_or(DEFAULT_STATUS_FORMAT);
+        fs::write(path, format!("{}\n", format_status_line(template, &config, &state)))?;
+    }
+
+    if json {
+        let output = results_json(&config, &state, &results, &source_urls);
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    }
+
     // Print summary
     ui::print_blank();
     ui::print_info(&format!(
@@ -155,6 +765,13 @@ pub async fn run_check(
         failure_count,
         notify_count
     ));
+    for result in results.iter().filter(|r| !r.success) {
+        ui::print_warning(&format!(
+            "  {} failed: {}",
+            result.subject_name,
+            result.error.as_deref().unwrap_or("unknown error")
+        ));
+    }
 
     if failure_count == 0 {
         Ok(ExitStatus::Success)
@@ -165,18 +782,351 @@ pub async fn run_check(
     }
 }
 
-/// Check a single subject using the configured backend (for parallel execution)
+/// Combine check results with subject details and the resulting state for `--json`
+fn results_json(
+    config: &Config,
+    state: &State,
+    results: &[CheckResult],
+    source_urls: &HashMap<Uuid, String>,
+) -> Vec<CheckResultJson> {
+    results
+        .iter()
+        .map(|result| {
+            let subject = config.subjects.iter().find(|s| s.id == result.subject_id);
+            let new_status = state.subjects.get(&result.subject_id).map(|s| match s {
+                SubjectState::Release(s) => s.status.to_string(),
+                SubjectState::Question(s) if s.is_definitive => "answered".to_string(),
+                SubjectState::Question(_) => "open".to_string(),
+                SubjectState::Recurring(s) if s.next_occurrence_date.is_some() => "scheduled".to_string(),
+                SubjectState::Recurring(_) => "unknown".to_string(),
+            });
+            CheckResultJson {
+                subject_key: subject.map(|s| s.key.clone()).unwrap_or_default(),
+                subject_name: result.subject_name.clone(),
+                subject_type: subject.map(|s| s.subject_type).unwrap_or_default(),
+                success: result.success,
+                notified: result.notified,
+                error: result.error.clone(),
+                error_type: result.error_type,
+                new_status,
+                source_url: source_urls.get(&result.subject_id).cloned(),
+            }
+        })
+        .collect()
+}
+
+/// Alert an operations webhook about each failed subject (errors are only logged)
+async fn send_failure_webhooks(url: &str, config: &Config, state: &State, results: &[CheckResult]) {
+    for result in results.iter().filter(|r| !r.success) {
+        let subject_key = config.subjects.iter()
+            .find(|s| s.id == result.subject_id)
+            .map(|s| s.key.clone())
+            .unwrap_or_default();
+        let payload = FailurePayload {
+            subject_key,
+            error: result.error.clone().unwrap_or_default(),
+            error_type: result.error_type.unwrap_or("unknown").to_string(),
+            consecutive_failures: state.consecutive_failures.get(&result.subject_id).copied().unwrap_or(1),
+        };
+
+        if let Err(e) = webhook::post_json(url, &payload).await {
+            ui::print_warning(&format!("  Failure webhook for '{}' failed: {}", result.subject_name, e));
+        }
+    }
+}
+
+/// Deliver the notifications collected during the run to the webhook and Telegram
+async fn send_channel_notifications(
+    config: &Config,
+    state: &mut State,
+    results: &mut [CheckResult],
+    delivery: Delivery,
+) {
+    for result in results.iter_mut() {
+        let Some(notification) = result.channel_notification.take() else {
+            continue;
+        };
+        let mut delivered = Vec::new();
+
+        if let Some(ref webhook_config) = config.webhook {
+            match webhook::send_notification(webhook_config, &notification, &config.subjects).await {
+                Ok(()) => delivered.push("webhook"),
+                Err(e) => ui::print_error(&format!("  Failed to post '{}' to webhook: {}", result.subject_name, e)),
+            }
+        }
+        if let Some(telegram_config) = config.telegram.as_ref().filter(|_| delivery != Delivery::WebhookOnly) {
+            match telegram::send_notification(telegram_config, &notification, &config.subjects).await {
+                Ok(()) => delivered.push("telegram"),
+                Err(e) => ui::print_error(&format!("  Failed to send '{}' to Telegram: {}", result.subject_name, e)),
+            }
+        }
+
+        if delivered.is_empty() {
+            continue;
+        }
+        state.link_notification(result.subject_id, notification.notification_id);
+        for channel in &delivered {
+            state.record_notification_channel(notification.notification_id, channel);
+        }
+        result.notified = true;
+        ui::print_success(&format!("  Sent '{}' to {}", result.subject_name, delivered.join(" and ")));
+    }
+}
+
+/// Fill the --status-file template placeholders
+fn format_status_line(template: &str, config: &Config, state: &State) -> String {
+    let last_run = state.last_run
+        .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_else(|| "never".to_string());
+
+    template
+        .replace("{{subjects}}", &config.subjects.len().to_string())
+        .replace("{{pending}}", &state.pending_notifications.len().to_string())
+        .replace("{{last_run}}", &last_run)
+}
+
+/// Append a run summary as one JSON line, locking the file against concurrent runs
+fn append_result_file(path: &Path, summary: &CheckSummary) -> Result<()> {
+    let line = serde_json::to_string(summary)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    let start = Instant::now();
+    while file.try_lock_exclusive().is_err() {
+        if start.elapsed() >= Duration::from_secs(RESULT_FILE_LOCK_TIMEOUT_SECS) {
+            return Err(HeadsupError::Io(std::io::Error::new(
+                std::io::ErrorKind::WouldBlock,
+                format!("Result file {} is locked by another process", path.display()),
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let written = writeln!(file, "{}", line);
+    let _ = file.unlock();
+    written?;
+    Ok(())
+}
+
+/// The prompt a subject is checked with, also used as the --cache-prompts key
+fn subject_prompt(config: &Config, subject: &Subject, state: Option<&SubjectState>) -> String {
+    backend_prompt(config, config.backend, subject, state)
+}
+
+/// The prompt a given backend is sent for a subject
+fn backend_prompt(config: &Config, backend: Backend, subject: &Subject, state: Option<&SubjectState>) -> String {
+    let search_available = match backend {
+        Backend::Claude | Backend::Perplexity => true,
+        Backend::OpenAi => config.openai.enable_web_search,
+        Backend::Ollama => false,
+    };
+    let prompt = claude::build_check_prompt(subject, state, search_available);
+    match backend {
+        Backend::Claude => claude::with_context(&config.claude, prompt, state),
+        Backend::Perplexity | Backend::OpenAi | Backend::Ollama => prompt,
+    }
+}
+
+/// Lowercase backend name as written in the config and recorded in history
+fn backend_name(backend: Backend) -> &'static str {
+    match backend {
+        Backend::Claude => "claude",
+        Backend::Perplexity => "perplexity",
+        Backend::OpenAi => "openai",
+        Backend::Ollama => "ollama",
+    }
+}
+
+/// Whether an error from one backend is worth retrying with the next one
+fn should_fall_back(error: &HeadsupError, on_timeout: bool) -> bool {
+    match error {
+        HeadsupError::Claude(_)
+        | HeadsupError::ClaudeParseError(_)
+        | HeadsupError::Perplexity(_)
+        | HeadsupError::OpenAi(_)
+        | HeadsupError::Ollama(_) => true,
+        HeadsupError::ClaudeTimeout(_)
+        | HeadsupError::PerplexityTimeout(_)
+        | HeadsupError::OpenAiTimeout(_)
+        | HeadsupError::OllamaTimeout(_) => on_timeout,
+        _ => false,
+    }
+}
+
+/// Whether a subject last checked at `last_checked` is due again under its check interval
+///
+/// A few minutes of slack keep runs scheduled exactly `interval_hours` apart from skipping a check.
+fn interval_elapsed(last_checked: DateTime<Utc>, now: DateTime<Utc>, interval_hours: u32) -> bool {
+    now - last_checked + chrono::Duration::minutes(INTERVAL_TOLERANCE_MINUTES)
+        >= chrono::Duration::hours(i64::from(interval_hours))
+}
+
+/// Limits shared by every backend call of a run, including retries and fallbacks
+#[derive(Clone, Default)]
+struct CallLimits {
+    rate_limiter: Option<Arc<RateLimiter>>,
+    concurrency: Option<Arc<Semaphore>>,
+    deadline: Option<Instant>,
+}
+
+impl CallLimits {
+    /// Wait for a concurrency slot and a rate limit token; the slot is held until the permit is dropped
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        // The semaphore is never closed
+        let permit = match &self.concurrency {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+        permit
+    }
+
+    /// Whether a call starting after `delay` still finishes before the total run timeout
+    fn allows_retry(&self, delay: Duration) -> bool {
+        self.deadline.is_none_or(|deadline| Instant::now() + delay < deadline)
+    }
+}
+
+/// Check with the configured backend, then with each fallback while the error allows it
+async fn check_with_fallback(
+    config: &Config,
+    limits: &CallLimits,
+    fallbacks: &[Backend],
+    subject: &Subject,
+    state: Option<&SubjectState>,
+) -> (Result<(ClaudeResponse, String)>, Backend) {
+    let mut backend = config.backend;
+    let mut result = check_subject_parallel(config, limits, backend, subject, state).await;
+    for &next in fallbacks {
+        match &result {
+            Err(e) if should_fall_back(e, config.settings.fallback_on_timeout) => {
+                ui::print_warning(&format!(
+                    "  '{}' failed with {} ({}), retrying with {}",
+                    subject.name,
+                    backend_name(backend),
+                    e,
+                    backend_name(next)
+                ));
+            }
+            _ => break,
+        }
+        backend = next;
+        result = check_subject_parallel(config, limits, backend, subject, state).await;
+    }
+    (result, backend)
+}
+
+/// Check a single subject using the given backend (for parallel execution)
 async fn check_subject_parallel(
     config: &Config,
+    limits: &CallLimits,
+    backend: Backend,
+    subject: &Subject,
+    state: Option<&SubjectState>,
+) -> Result<(ClaudeResponse, String)> {
+    let cached = response_cache(config).map(|cache| (cache, backend_prompt(config, backend, subject, state)));
+    if let Some((cache, prompt)) = &cached {
+        let hit = cache::load(cache, subject.id, prompt)
+            .and_then(|raw| claude::parse_response(subject.subject_type, &raw).ok().map(|r| (r, raw)));
+        if let Some(hit) = hit {
+            ui::print_info(&format!("  Using cached response for '{}'", subject.name));
+            return Ok(hit);
+        }
+    }
+
+    let result = check_with_retry(config, limits, backend, subject, state).await;
+    if let (Some((cache, prompt)), Ok((_, raw))) = (&cached, &result) {
+        if let Err(e) = cache::store(cache, subject.id, prompt, raw) {
+            ui::print_warning(&format!("  Could not cache the response for '{}': {}", subject.name, e));
+        }
+    }
+    result
+}
+
+/// Call the backend, retrying transient failures with exponential backoff
+///
+/// Every attempt waits for the run's limits, and no concurrency slot is held during the backoff.
+async fn check_with_retry(
+    config: &Config,
+    limits: &CallLimits,
+    backend: Backend,
     subject: &Subject,
     state: Option<&SubjectState>,
-) -> Result<(ClaudeResponse, bool)> {
-    let response = match config.backend {
-        Backend::Claude => claude::check_subject(&config.claude, subject, state).await?,
-        Backend::Perplexity => perplexity::check_subject(&config.perplexity, subject, state).await?,
+) -> Result<(ClaudeResponse, String)> {
+    let backoff = match backend {
+        Backend::Claude => Some(config.claude.backoff()),
+        Backend::Perplexity => Some(config.perplexity.backoff()),
+        Backend::OpenAi | Backend::Ollama => None,
     };
 
-    Ok((response, false))
+    let mut retry = 0;
+    loop {
+        let permit = limits.acquire().await;
+        let result = match backend {
+            Backend::Claude => claude::check_subject(&config.claude, subject, state).await,
+            Backend::Perplexity => perplexity::check_subject(&config.perplexity, subject, state).await,
+            Backend::OpenAi => openai::check_subject(&config.openai, subject, state).await,
+            Backend::Ollama => ollama::check_subject(&config.ollama, subject, state).await,
+        };
+        drop(permit);
+        let backoff = match (&result, backoff) {
+            (Err(e), Some(backoff)) if retry < backoff.attempts && is_transient(e) => backoff,
+            _ => return result,
+        };
+
+        let delay = backoff.delay(retry + 1);
+        if !limits.allows_retry(delay) {
+            return result;
+        }
+        retry += 1;
+        tracing::debug!(
+            "'{}' failed with {} ({}), retry {}/{} in {} ms",
+            subject.name,
+            backend_name(backend),
+            result.err().map(|e| e.to_string()).unwrap_or_default(),
+            retry,
+            backoff.attempts,
+            delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Whether a backend error may go away on its own, so the call is worth repeating
+fn is_transient(error: &HeadsupError) -> bool {
+    matches!(
+        error,
+        HeadsupError::Claude(_)
+            | HeadsupError::ClaudeTimeout(_)
+            | HeadsupError::Perplexity(_)
+            | HeadsupError::PerplexityTimeout(_)
+    )
+}
+
+/// The response cache, when configured and enabled
+fn response_cache(config: &Config) -> Option<&CacheConfig> {
+    config.cache.as_ref().filter(|cache| cache.enabled)
+}
+
+/// Return the source URL of a response if it comes from an official or reliable source
+fn credible_source(response: &ClaudeResponse) -> Option<String> {
+    if response.confidence().is_at_least(&Confidence::Reliable) {
+        response.source_url().map(|url| url.to_string())
+    } else {
+        None
+    }
+}
+
+/// Config changes remembering the latest credible source per subject
+fn found_source_patches(config: &Config, found_sources: &[(Uuid, String)]) -> Vec<(Uuid, SubjectPatch)> {
+    found_sources
+        .iter()
+        .filter(|(id, source)| {
+            config.subjects.iter().any(|s| s.id == *id && s.last_found_source.as_deref() != Some(source.as_str()))
+        })
+        .map(|(id, source)| (*id, SubjectPatch::LastFoundSource(source.clone())))
+        .collect()
 }
 
 /// Process a successful check result
@@ -184,39 +1134,73 @@ fn process_successful_check(
     config: &Config,
     subject: &Subject,
     response: ClaudeResponse,
+    metadata: &ResponseMetadata,
     state: &mut State,
     dry_run: bool,
-    no_notify: bool,
+    delivery: Delivery,
 ) -> CheckResult {
     let mut result = CheckResult {
+        subject_id: subject.id,
+        subject_name: subject.name.clone(),
         success: true,
         notified: false,
+        error: None,
+        error_type: None,
+        channel_notification: None,
+        backend_used: backend_name(metadata.backend).to_string(),
     };
 
     // Clone state for notification
     let previous_state = state.subjects.get(&subject.id).cloned();
 
     // Process response based on type
-    let notify_flag = match &response {
+    let processed = match &response {
         ClaudeResponse::Release(r) => {
-            process_release_response(config, subject, r, state, dry_run)
+            process_release_response(config, subject, r, metadata, state, dry_run)
         }
         ClaudeResponse::Question(r) => {
-            process_question_response(config, subject, r, state, dry_run)
+            process_question_response(config, subject, r, metadata, state, dry_run)
         }
         ClaudeResponse::Recurring(r) => {
-            process_recurring_response(config, subject, r, state, dry_run)
+            process_recurring_response(config, subject, r, metadata, state, dry_run)
         }
     };
+    // A state entry of the wrong type only fails this subject
+    let notify_flag = match processed {
+        Ok(flag) => flag,
+        Err(e) => return process_failed_check(config, subject, e, metadata.backend, state, dry_run),
+    };
+
+    if !dry_run {
+        state.record_success(subject.id);
+    }
 
+    // The state changed, so a cached response would be stale
     if notify_flag && !dry_run {
-        if no_notify || config.email.digest_mode {
-            add_pending_notification(subject, &response, state);
+        if let Some(cache) = response_cache(config) {
+            if let Err(e) = cache::invalidate(cache, subject.id) {
+                ui::print_warning(&format!("  Could not clear cached responses for '{}': {}", subject.name, e));
+            }
+        }
+    }
+
+    if notify_flag && !dry_run {
+        if delivery == Delivery::Queue || config.email.digest_mode {
+            add_pending_notification(config, subject, &response, state);
             let reason = if config.email.digest_mode { "digest mode" } else { "no-notify" };
             ui::print_info(&format!("  Queued '{}' for pending notifications ({})", subject.name, reason));
         } else {
+            if config.webhook.is_some() || config.telegram.is_some() {
+                result.channel_notification = Some(pending_notification(config, subject, &response));
+            }
+            if delivery == Delivery::WebhookOnly {
+                return result;
+            }
             match send_notification(config, subject, &response, previous_state.as_ref()) {
                 Ok(()) => {
+                    let notification_id = Uuid::new_v4();
+                    state.link_notification(subject.id, notification_id);
+                    state.record_notification_channel(notification_id, "email");
                     result.notified = true;
                     ui::print_success(&format!("  Notified about '{}'", subject.name));
                 }
@@ -236,17 +1220,64 @@ fn process_successful_check(
 
 /// Process a failed check result
 fn process_failed_check(
-    _config: &Config,
+    config: &Config,
     subject: &Subject,
     error: HeadsupError,
-    _state: &mut State,
-    _dry_run: bool,
+    backend: Backend,
+    state: &mut State,
+    dry_run: bool,
 ) -> CheckResult {
     ui::print_error(&format!("  '{}' error: {}", subject.name, error));
 
+    if !dry_run {
+        state.record_failure(subject.id);
+        let entry = HistoryEntry {
+            timestamp: Utc::now(),
+            event: state::FAILED_EVENT.to_string(),
+            details: serde_json::json!({
+                "error": error.to_string(),
+                "error_type": error.error_type(),
+                "backend": backend_name(backend),
+            }),
+            source_url: None,
+            raw_response: None,
+            raw_ai_output: None,
+            latency_ms: None,
+            tags: Vec::new(),
+        };
+        state.add_history(subject.id, entry, config);
+    }
+
     CheckResult {
+        subject_id: subject.id,
+        subject_name: subject.name.clone(),
         success: false,
         notified: false,
+        error: Some(error.to_string()),
+        error_type: Some(error.error_type()),
+        channel_notification: None,
+        backend_used: backend_name(backend).to_string(),
+    }
+}
+
+/// Apply the subject's (or its group's) notification criteria to the AI's decision to notify
+fn meets_criteria(
+    config: &Config,
+    subject: &Subject,
+    should_notify: bool,
+    confidence: Confidence,
+    precision: Option<DatePrecision>,
+    is_definitive: Option<bool>,
+) -> bool {
+    match config.notification_criteria(subject) {
+        Some(criteria) if should_notify && !criteria.allows(confidence, precision, is_definitive) => {
+            ui::print_info(&format!(
+                "  Not notifying about '{}' (below its notification criteria)",
+                subject.name
+            ));
+            false
+        }
+        _ => should_notify,
     }
 }
 
@@ -254,15 +1285,26 @@ fn process_release_response(
     config: &Config,
     subject: &Subject,
     response: &ReleaseResponse,
+    metadata: &ResponseMetadata,
     state: &mut State,
     dry_run: bool,
-) -> bool {
-    let release_state = state.get_or_create_release(subject.id);
-    let should_notify = response.should_notify;
+) -> Result<bool> {
+    let release_state = state.get_or_create_release(subject)?;
+    let should_notify = meets_criteria(
+        config,
+        subject,
+        response.should_notify,
+        response.confidence,
+        Some(response.release_date_precision),
+        None,
+    );
 
     if !dry_run {
         // Always update last_checked
         release_state.last_checked = Some(Utc::now());
+        if config.claude.context_window_strategy == ContextStrategy::Summary {
+            release_state.context_summary = Some(response.summary.clone());
+        }
 
         // Only update core fields when notifying (prevents drift from LLM rewording)
         if should_notify {
@@ -293,29 +1335,50 @@ fn process_release_response(
                 "confidence": response.confidence.to_string(),
                 "status": response.status.to_string(),
                 "should_notify": should_notify,
+                "backend": backend_name(metadata.backend),
             }),
             source_url: response.source_url.clone(),
             raw_response: Some(serde_json::to_string(response).unwrap_or_default()),
+            raw_ai_output: metadata.raw_output.clone(),
+            latency_ms: Some(metadata.latency_ms),
+            tags: metadata.tags.clone(),
         };
-        state.add_history(subject.id, entry, config.settings.max_history_entries);
+        state.add_history(subject.id, entry, config);
     }
 
-    should_notify
+    Ok(should_notify)
 }
 
 fn process_question_response(
     config: &Config,
     subject: &Subject,
     response: &QuestionResponse,
+    metadata: &ResponseMetadata,
     state: &mut State,
     dry_run: bool,
-) -> bool {
-    let question_state = state.get_or_create_question(subject.id);
-    let should_notify = response.should_notify;
+) -> Result<bool> {
+    let question_state = state.get_or_create_question(subject)?;
+    let should_notify = meets_criteria(
+        config,
+        subject,
+        response.should_notify,
+        response.confidence,
+        None,
+        Some(response.is_definitive),
+    );
 
     if !dry_run {
         // Always update last_checked
         question_state.last_checked = Some(Utc::now());
+        if config.claude.context_window_strategy == ContextStrategy::Summary {
+            question_state.context_summary = Some(response.summary.clone());
+        }
+
+        // Seed the hypothesis from config the first time it is set
+        if question_state.expected_answer.is_none() {
+            question_state.expected_answer = subject.initial_expected_answer.clone();
+        }
+        let expected_answer = question_state.expected_answer.clone();
 
         // Only update core fields when notifying (prevents drift from LLM rewording)
         if should_notify {
@@ -336,29 +1399,64 @@ fn process_question_response(
                 "confidence": response.confidence.to_string(),
                 "is_definitive": response.is_definitive,
                 "should_notify": should_notify,
+                "backend": backend_name(metadata.backend),
             }),
             source_url: response.source_url.clone(),
             raw_response: Some(serde_json::to_string(response).unwrap_or_default()),
+            raw_ai_output: metadata.raw_output.clone(),
+            latency_ms: Some(metadata.latency_ms),
+            tags: metadata.tags.clone(),
         };
-        state.add_history(subject.id, entry, config.settings.max_history_entries);
+        state.add_history(subject.id, entry, config);
+
+        // Record how a newly reported answer compares with the hypothesis
+        if let (true, Some(expected), Some(found)) =
+            (should_notify, expected_answer, response.found_answer.as_ref())
+        {
+            let entry = HistoryEntry {
+                timestamp: Utc::now(),
+                event: state::hypothesis_event(&expected, found).to_string(),
+                details: serde_json::json!({
+                    "expected_answer": expected,
+                    "found_answer": found,
+                }),
+                source_url: response.source_url.clone(),
+                raw_response: None,
+                raw_ai_output: None,
+                latency_ms: None,
+                tags: metadata.tags.clone(),
+            };
+            state.add_history(subject.id, entry, config);
+        }
     }
 
-    should_notify
+    Ok(should_notify)
 }
 
 fn process_recurring_response(
     config: &Config,
     subject: &Subject,
     response: &RecurringResponse,
+    metadata: &ResponseMetadata,
     state: &mut State,
     dry_run: bool,
-) -> bool {
-    let recurring_state = state.get_or_create_recurring(subject.id);
-    let should_notify = response.should_notify;
+) -> Result<bool> {
+    let recurring_state = state.get_or_create_recurring(subject)?;
+    let should_notify = meets_criteria(
+        config,
+        subject,
+        response.should_notify,
+        response.confidence,
+        Some(response.date_precision),
+        None,
+    );
 
     if !dry_run {
         // Always update last_checked
         recurring_state.last_checked = Some(Utc::now());
+        if config.claude.context_window_strategy == ContextStrategy::Summary {
+            recurring_state.context_summary = Some(response.summary.clone());
+        }
 
         // Only update core fields when notifying (prevents drift from LLM rewording)
         if should_notify {
@@ -389,14 +1487,18 @@ fn process_recurring_response(
                 "date_precision": response.date_precision.to_string(),
                 "confidence": response.confidence.to_string(),
                 "should_notify": should_notify,
+                "backend": backend_name(metadata.backend),
             }),
             source_url: response.source_url.clone(),
             raw_response: Some(serde_json::to_string(response).unwrap_or_default()),
+            raw_ai_output: metadata.raw_output.clone(),
+            latency_ms: Some(metadata.latency_ms),
+            tags: metadata.tags.clone(),
         };
-        state.add_history(subject.id, entry, config.settings.max_history_entries);
+        state.add_history(subject.id, entry, config);
     }
 
-    should_notify
+    Ok(should_notify)
 }
 
 fn send_notification(
@@ -411,7 +1513,7 @@ fn send_notification(
                 SubjectState::Release(rs) => Some(rs),
                 _ => None,
             });
-            build_release_email(subject, r, prev)
+            build_release_email(subject, r, prev, email::ics_reminder(config).as_ref())
         }
         ClaudeResponse::Question(r) => {
             let prev = previous_state.and_then(|s| match s {
@@ -425,42 +1527,145 @@ fn send_notification(
                 SubjectState::Recurring(rs) => Some(rs),
                 _ => None,
             });
-            build_recurring_email(subject, r, prev)
+            build_recurring_email(subject, r, prev, email::ics_reminder(config).as_ref())
         }
     };
 
     email::send_email(&config.email, &content)
 }
 
-fn add_pending_notification(subject: &Subject, response: &ClaudeResponse, state: &mut State) {
-    let (event_type, summary, source_url, payload) = match response {
+/// Queue a notice that a subject stopped being monitored because it expired
+fn add_expiry_notification(config: &Config, subject: &Subject, state: &mut State) {
+    let expiry_date = subject.expiry_date.map(|d| d.to_string()).unwrap_or_default();
+    let notification = PendingNotification {
+        notification_id: Uuid::new_v4(),
+        subject_id: subject.id,
+        event_type: "monitoring_expired".to_string(),
+        created_at: Utc::now(),
+        summary: format!(
+            "Monitoring of '{}' ended: its expiry date ({}) has passed. The subject was archived.",
+            subject.name, expiry_date
+        ),
+        source_url: None,
+        confidence: None,
+        payload: serde_json::json!({ "expiry_date": expiry_date }),
+        ttl_hours: subject.notification_ttl_hours.or(config.settings.default_notification_ttl_hours),
+        expires_at: None,
+    };
+    state.queue_notification(notification, config.settings.dedup_window_hours);
+}
+
+fn add_pending_notification(config: &Config, subject: &Subject, response: &ClaudeResponse, state: &mut State) {
+    let notification = pending_notification(config, subject, response);
+    let notification_id = state.queue_notification(notification, config.settings.dedup_window_hours);
+    state.link_notification(subject.id, notification_id);
+}
+
+/// Build the notification a response triggers
+fn pending_notification(config: &Config, subject: &Subject, response: &ClaudeResponse) -> PendingNotification {
+    let (event_type, summary, source_url, confidence, payload) = match response {
         ClaudeResponse::Release(r) => (
             "release_update".to_string(),
             r.summary.clone(),
             r.source_url.clone(),
+            r.confidence,
             serde_json::to_value(r).unwrap_or_default(),
         ),
         ClaudeResponse::Question(r) => (
             "question_update".to_string(),
             r.summary.clone(),
             r.source_url.clone(),
+            r.confidence,
             serde_json::to_value(r).unwrap_or_default(),
         ),
         ClaudeResponse::Recurring(r) => (
             "recurring_update".to_string(),
             r.summary.clone(),
             r.source_url.clone(),
+            r.confidence,
             serde_json::to_value(r).unwrap_or_default(),
         ),
     };
 
-    state.add_pending_notification(PendingNotification {
+    PendingNotification {
+        notification_id: Uuid::new_v4(),
         subject_id: subject.id,
         event_type,
         created_at: Utc::now(),
         summary,
         source_url,
+        confidence: Some(confidence),
         payload,
-    });
+        ttl_hours: subject.notification_ttl_hours.or(config.settings.default_notification_ttl_hours),
+        expires_at: imminent_expiry(config, response),
+    }
 }
 
+/// Hours an imminent-release notification stays queued; after that it is likely out of date
+const IMMINENT_NOTIFICATION_TTL_HOURS: i64 = 2;
+
+/// Expiry time for notifications about a release due within the imminent threshold
+fn imminent_expiry(config: &Config, response: &ClaudeResponse) -> Option<DateTime<Utc>> {
+    let ClaudeResponse::Release(r) = response else {
+        return None;
+    };
+    if r.release_date_precision != DatePrecision::Exact {
+        return None;
+    }
+    let release_date = email::ics::parse_exact_date(r.found_release_date.as_deref()?)?;
+    let days_until = (release_date - Local::now().date_naive()).num_days();
+    (0..=config.settings.imminent_threshold_days as i64)
+        .contains(&days_until)
+        .then(|| Utc::now() + chrono::Duration::hours(IMMINENT_NOTIFICATION_TTL_HOURS))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_elapsed() {
+        let last = Utc::now();
+        assert!(!interval_elapsed(last, last + chrono::Duration::hours(23), 24));
+        // Runs slightly earlier than the interval still count as due
+        assert!(interval_elapsed(last, last + chrono::Duration::minutes(23 * 60 + 58), 24));
+        assert!(interval_elapsed(last, last + chrono::Duration::hours(24), 24));
+        assert!(interval_elapsed(last, last + chrono::Duration::minutes(90), 1));
+        assert!(interval_elapsed(last, last, 0));
+    }
+
+    #[test]
+    fn test_should_fall_back() {
+        assert!(should_fall_back(&HeadsupError::Claude("overloaded".to_string()), false));
+        assert!(should_fall_back(&HeadsupError::ClaudeParseError("no JSON".to_string()), false));
+        assert!(should_fall_back(&HeadsupError::OpenAi("500".to_string()), false));
+        assert!(!should_fall_back(&HeadsupError::Config("invalid".to_string()), true));
+        assert!(!should_fall_back(&HeadsupError::MissingSourceUrl, true));
+    }
+
+    #[test]
+    fn test_should_fall_back_on_timeout() {
+        for error in [
+            HeadsupError::ClaudeTimeout(60),
+            HeadsupError::PerplexityTimeout(60),
+            HeadsupError::OpenAiTimeout(60),
+            HeadsupError::OllamaTimeout(60),
+        ] {
+            assert!(should_fall_back(&error, true));
+            assert!(!should_fall_back(&error, false));
+        }
+    }
+
+    #[test]
+    fn test_retry_counts_against_run_timeout() {
+        assert!(CallLimits::default().allows_retry(Duration::from_secs(3600)));
+
+        let limits = CallLimits {
+            deadline: Some(Instant::now() + Duration::from_secs(10)),
+            ..Default::default()
+        };
+        assert!(limits.allows_retry(Duration::from_secs(1)));
+        assert!(!limits.allows_retry(Duration::from_secs(60)));
+    }
+}
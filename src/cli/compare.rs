@@ -0,0 +1,125 @@
+use crate::claude::{self, ClaudeResponse};
+use crate::config::{Config, Subject};
+use crate::error::{ExitStatus, HeadsupError, Result};
+use crate::perplexity;
+use crate::state::State;
+use crate::ui;
+use serde_json::{Map, Value};
+
+/// Check each subject with both backends and print where their answers agree (nothing is saved)
+pub async fn run_comparison(config: &Config, subjects: &[&Subject], state: &State) -> Result<ExitStatus> {
+    if config.perplexity.api_key_command.is_empty() {
+        return Err(HeadsupError::Config(
+            "--compare-backends requires perplexity.api_key_command to be configured".to_string(),
+        ));
+    }
+
+    println!("{:<20} {:<24} {:<30} {:<30} MATCH", "SUBJECT", "FIELD", "CLAUDE", "PERPLEXITY");
+    println!("{}", "-".repeat(112));
+
+    let mut failed = 0;
+    for subject in subjects {
+        ui::print_info(&format!("  Checking '{}' with both backends...", subject.name));
+        let subject_state = state.subjects.get(&subject.id);
+        let (claude_result, perplexity_result) = futures::join!(
+            claude::check_subject(&config.claude, subject, subject_state),
+            perplexity::check_subject(&config.perplexity, subject, subject_state),
+        );
+
+        let (claude_response, perplexity_response) = match (claude_result, perplexity_result) {
+            (Ok((c, _)), Ok((p, _))) => (c, p),
+            (c, p) => {
+                let describe = |r: &Result<(ClaudeResponse, String)>| match r {
+                    Ok(_) => "ok".to_string(),
+                    Err(e) => format!("error: {}", e),
+                };
+                println!(
+                    "{:<20} {:<24} {:<30} {:<30} -",
+                    truncate(&subject.name, 20),
+                    "(check failed)",
+                    truncate(&describe(&c), 30),
+                    truncate(&describe(&p), 30)
+                );
+                failed += 1;
+                continue;
+            }
+        };
+
+        let claude_fields = response_fields(&claude_response);
+        let perplexity_fields = response_fields(&perplexity_response);
+        let mut keys: Vec<&String> = claude_fields.keys().chain(perplexity_fields.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut name = subject.name.as_str();
+        let (mut shown, mut matching) = (0, 0);
+        for key in keys {
+            let c = claude_fields.get(key).unwrap_or(&Value::Null);
+            let p = perplexity_fields.get(key).unwrap_or(&Value::Null);
+            // Fields neither backend filled in say nothing about agreement
+            if c.is_null() && p.is_null() {
+                continue;
+            }
+            let same = values_match(c, p);
+            shown += 1;
+            if same {
+                matching += 1;
+            }
+            println!(
+                "{:<20} {:<24} {:<30} {:<30} {}",
+                truncate(name, 20),
+                key,
+                truncate(&display_value(c), 30),
+                truncate(&display_value(p), 30),
+                if same { "✓" } else { "✗" }
+            );
+            name = "";
+        }
+        ui::print_info(&format!("  {}: {} of {} fields match", subject.name, matching, shown));
+    }
+
+    if failed == 0 {
+        Ok(ExitStatus::Success)
+    } else if failed == subjects.len() {
+        Ok(ExitStatus::AllSubjectsFailed)
+    } else {
+        Ok(ExitStatus::PartialFailure)
+    }
+}
+
+/// Fields of the typed response as a JSON object
+fn response_fields(response: &ClaudeResponse) -> Map<String, Value> {
+    let value = match response {
+        ClaudeResponse::Release(r) => serde_json::to_value(r),
+        ClaudeResponse::Question(r) => serde_json::to_value(r),
+        ClaudeResponse::Recurring(r) => serde_json::to_value(r),
+    };
+    match value {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    }
+}
+
+/// Compare values, ignoring case and surrounding whitespace in strings
+fn values_match(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.trim().eq_ignore_ascii_case(b.trim()),
+        _ => a == b,
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
+        s.to_string()
+    } else {
+        format!("{}...", s.chars().take(max_len - 3).collect::<String>())
+    }
+}
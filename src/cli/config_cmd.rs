@@ -1,32 +1,86 @@
-use crate::cli::ConfigCommands;
-use crate::config::{self, Config};
+use crate::cli::{ConfigCommands, ConfigSection, MergeStrategy};
+use crate::claude;
+use crate::config::{self, Config, Subject, SubjectType};
 use crate::email;
 use crate::error::{HeadsupError, Result};
 use crate::ui;
+use crate::util::dedup;
 use std::path::PathBuf;
 use std::process::Command;
 
 /// Run config subcommands
 pub fn run_config(command: ConfigCommands) -> Result<()> {
     match command {
-        ConfigCommands::Show => show_config(),
+        ConfigCommands::Show { section } => show_config(section),
         ConfigCommands::Edit => edit_config(),
         ConfigCommands::Validate => validate_config(),
+        ConfigCommands::Audit => audit_config(),
         ConfigCommands::Path => print_path(),
+        ConfigCommands::EnvironmentInfo => print_environment_info(),
+        ConfigCommands::SetSmtpPassword => set_smtp_password(),
+        ConfigCommands::KeychainSet { service, account } => keychain_set(&service, &account),
+        ConfigCommands::SecretStore { service, account } => secret_store(&service, &account),
+        ConfigCommands::PassSet { path } => pass_set(&path),
+        ConfigCommands::Schema => print_schema(),
         ConfigCommands::Export => export_config(),
-        ConfigCommands::Import { file } => import_config(file),
+        ConfigCommands::Import { file, merge_strategy } => import_config(file, merge_strategy),
     }
 }
 
-fn show_config() -> Result<()> {
+fn show_config(section: Option<ConfigSection>) -> Result<()> {
     let config = config::load_config()?;
     let redacted = config::redact_config(&config);
-    let content = toml::to_string_pretty(&redacted)
-        .map_err(|e| HeadsupError::Config(format!("Failed to serialize config: {}", e)))?;
+
+    let content = match section {
+        None => toml::to_string_pretty(&redacted),
+        Some(ConfigSection::Email) => toml::to_string_pretty(&redacted.email),
+        Some(ConfigSection::Claude) => toml::to_string_pretty(&redacted.claude),
+        Some(ConfigSection::Perplexity) => toml::to_string_pretty(&redacted.perplexity),
+        Some(ConfigSection::OpenAi) => toml::to_string_pretty(&redacted.openai),
+        Some(ConfigSection::Ollama) => toml::to_string_pretty(&redacted.ollama),
+        Some(ConfigSection::Settings) => toml::to_string_pretty(&redacted.settings),
+        Some(ConfigSection::Subjects) => {
+            print_subjects_table(&redacted);
+            return Ok(());
+        }
+    }
+    .map_err(|e| HeadsupError::Config(format!("Failed to serialize config: {}", e)))?;
+
+    if let Some(section) = section {
+        println!("[{}]", section);
+    }
     println!("{}", content);
     Ok(())
 }
 
+fn print_subjects_table(config: &Config) {
+    if config.subjects.is_empty() {
+        ui::print_info("No subjects configured");
+        return;
+    }
+
+    println!("{:<20} {:<30} {:<10} {:<8} DETAILS", "KEY", "NAME", "TYPE", "ENABLED");
+    println!("{}", "-".repeat(80));
+
+    for subject in &config.subjects {
+        let details = match subject.subject_type {
+            SubjectType::Release => subject.category.map(|c| c.to_string()),
+            SubjectType::Question => subject.question.clone(),
+            SubjectType::Recurring => subject.event_name.clone(),
+        }
+        .unwrap_or_default();
+
+        println!(
+            "{:<20} {:<30} {:<10} {:<8} {}",
+            subject.key,
+            subject.name,
+            subject.subject_type.to_string(),
+            if subject.enabled { "yes" } else { "no" },
+            details
+        );
+    }
+}
+
 fn edit_config() -> Result<()> {
     let path = config::config_path()?;
 
@@ -93,12 +147,198 @@ fn validate_config() -> Result<()> {
     Ok(())
 }
 
+fn audit_config() -> Result<()> {
+    let config = config::load_config()?;
+    let findings = config.audit();
+
+    if findings.is_empty() {
+        ui::print_success("No security issues found");
+        return Ok(());
+    }
+
+    for finding in &findings {
+        ui::print_warning(&finding.warning);
+        ui::print_info(&format!("  Fix: {}", finding.remediation));
+    }
+    ui::print_blank();
+    ui::print_warning(&format!("{} security issue(s) found", findings.len()));
+
+    Ok(())
+}
+
 fn print_path() -> Result<()> {
     let path = config::config_path()?;
     println!("{}", path.display());
     Ok(())
 }
 
+fn print_schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&config::config_schema())?);
+    Ok(())
+}
+
+fn print_environment_info() -> Result<()> {
+    let config_path = config::config_path()?;
+    let state_path = config::state_path()?;
+    // Fall back to defaults so a broken config doesn't hide the rest of the report
+    let config = config::load_config().ok();
+    let claude_command = config.as_ref().map(|c| c.claude.command.as_str()).unwrap_or("claude");
+    let log_level = config
+        .as_ref()
+        .map(|c| format!("{:?}", c.settings.log_level).to_lowercase())
+        .unwrap_or_else(|| "unknown (config not loaded)".to_string());
+
+    println!("### Environment");
+    println!();
+    println!("- headsup: {}", env!("CARGO_PKG_VERSION"));
+    println!("- OS: {}", os_description());
+    println!("- Rust toolchain: {}", command_output("rustc", &["--version"]).unwrap_or_else(|| "not found".to_string()));
+    println!(
+        "- Claude CLI ({}): {}",
+        claude_command,
+        claude::claude_version(claude_command).unwrap_or_else(|| "not found".to_string())
+    );
+    println!("- Config dir: {}", config::config_dir()?.display());
+    println!("- Data dir: {}", config::data_dir()?.display());
+    println!("- Config exists: {}", yes_no(config_path.exists()));
+    println!("- State exists: {}", yes_no(state_path.exists()));
+    println!("- Log level: {}", log_level);
+    if let Ok(filter) = std::env::var("RUST_LOG") {
+        println!("- RUST_LOG: {}", filter);
+    }
+    Ok(())
+}
+
+/// OS family and architecture, plus the release name where it can be found
+fn os_description() -> String {
+    let base = format!("{} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let release = match std::env::consts::OS {
+        "linux" => std::fs::read_to_string("/etc/os-release").ok().and_then(|content| {
+            content
+                .lines()
+                .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+                .map(|name| name.trim_matches('"').to_string())
+        }),
+        "macos" => command_output("sw_vers", &["-productVersion"]).map(|v| format!("macOS {}", v)),
+        _ => None,
+    };
+    match release {
+        Some(release) => format!("{} ({})", base, release),
+        None => base,
+    }
+}
+
+/// First line of a command's stdout, if it runs successfully
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).lines().next().map(|line| line.trim().to_string())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+/// Password managers offered by the set-smtp-password wizard
+const PASSWORD_SOURCES: [&str; 5] = [
+    "1Password CLI (op)",
+    "pass",
+    "macOS Keychain",
+    "GNOME Keyring / libsecret (secret-tool)",
+    "Plain text (testing only)",
+];
+
+fn keychain_set(service: &str, account: &str) -> Result<()> {
+    config::keychain_set(service, account)?;
+    ui::print_success(&format!("Stored the password in the Keychain (service '{}', account '{}')", service, account));
+    ui::print_info(&format!(
+        "Use it with: smtp_password_command = \"{}{}/{}\"",
+        config::KEYCHAIN_PREFIX,
+        service,
+        account
+    ));
+    Ok(())
+}
+
+fn secret_store(service: &str, account: &str) -> Result<()> {
+    config::secret_service_store(service, account)?;
+    ui::print_success(&format!("Stored the password in the keyring (service '{}', account '{}')", service, account));
+    ui::print_info(&format!(
+        "Use it with: smtp_password_command = \"{}{}/{}\"",
+        config::SECRET_SERVICE_PREFIX,
+        service,
+        account
+    ));
+    Ok(())
+}
+
+fn pass_set(entry: &str) -> Result<()> {
+    config::pass_insert(entry)?;
+    ui::print_success(&format!("Stored the password in pass entry '{}'", entry));
+    ui::print_info(&format!("Use it with: smtp_password_command = \"{}{}\"", config::PASS_PREFIX, entry));
+    Ok(())
+}
+
+fn set_smtp_password() -> Result<()> {
+    if !ui::is_interactive() {
+        return Err(HeadsupError::Config(
+            "set-smtp-password requires an interactive terminal (or edit smtp_password_command directly)".to_string(),
+        ));
+    }
+
+    let mut config = config::load_config()?;
+
+    let source = ui::prompt_select("Where is the SMTP password stored?", PASSWORD_SOURCES.to_vec())?;
+    let command = match source {
+        "1Password CLI (op)" => {
+            let reference = ui::prompt_text_with_default("Secret reference:", "op://Private/SMTP/password")?;
+            format!("op read {}", shell_quote(&reference))
+        }
+        "pass" => {
+            let entry = ui::prompt_text_with_default("Password entry:", "email/smtp")?;
+            format!("{}{}", config::PASS_PREFIX, entry)
+        }
+        "macOS Keychain" => {
+            let service = ui::prompt_text_with_default("Keychain item name (service):", "smtp")?;
+            format!("security find-generic-password -s {} -w", shell_quote(&service))
+        }
+        "GNOME Keyring / libsecret (secret-tool)" => {
+            let service = ui::prompt_text_with_default("Value of the 'service' attribute:", "smtp")?;
+            format!("secret-tool lookup service {}", shell_quote(&service))
+        }
+        _ => {
+            ui::print_warning("The password will be stored in plain text in the config file");
+            let password = ui::prompt_text("SMTP password:")?;
+            format!("echo {}", shell_quote(&password))
+        }
+    };
+
+    ui::print_info(&format!("Testing: {}", command));
+    match config::get_smtp_password(&command) {
+        Ok(password) => ui::print_success(&format!("Command printed a password ({} characters)", password.len())),
+        Err(e) => {
+            ui::print_error(&e.to_string());
+            if !ui::prompt_confirm("Save this command anyway?", false)? {
+                return Err(HeadsupError::UserCancelled);
+            }
+        }
+    }
+
+    config.email.smtp_password_command = command;
+    config::save_config(&config)?;
+    ui::print_success("Saved smtp_password_command");
+    ui::print_info("Run 'headsup test-email' to check the full SMTP setup");
+
+    Ok(())
+}
+
+/// Quote a value for use as a single POSIX shell word
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
 fn export_config() -> Result<()> {
     let config = config::load_config()?;
     let redacted = config::redact_config(&config);
@@ -108,29 +348,52 @@ fn export_config() -> Result<()> {
     Ok(())
 }
 
-fn import_config(file: PathBuf) -> Result<()> {
+fn import_config(file: PathBuf, strategy: MergeStrategy) -> Result<()> {
+    if strategy == MergeStrategy::Ask && !ui::is_interactive() {
+        return Err(HeadsupError::Config(
+            "--merge-strategy ask requires an interactive terminal".to_string(),
+        ));
+    }
+
     // Load existing config
     let mut config = config::load_config().unwrap_or_else(|_| Config::default_with_email("user@example.com"));
 
     // Load import file
     let import_config = config::load_config_from(&file)?;
 
-    // Merge subjects (add new ones, skip duplicates by key)
-    let existing_keys: std::collections::HashSet<String> = config
-        .subjects
-        .iter()
-        .map(|s| s.key.to_lowercase())
-        .collect();
-
     let mut added = 0;
+    let mut updated = 0;
     let mut skipped = 0;
 
-    for subject in import_config.subjects {
-        if existing_keys.contains(&subject.key.to_lowercase()) {
-            skipped += 1;
-        } else {
+    for mut subject in import_config.subjects {
+        // Private notes are never carried over from another config
+        subject.notes_private = None;
+
+        let Some(existing) = config
+            .subjects
+            .iter_mut()
+            .find(|s| s.key.eq_ignore_ascii_case(&subject.key))
+        else {
             config.subjects.push(subject);
             added += 1;
+            continue;
+        };
+
+        let strategy = match strategy {
+            MergeStrategy::Ask => ask_merge_strategy(&subject.key)?,
+            other => other,
+        };
+
+        match strategy {
+            MergeStrategy::Overwrite => {
+                overwrite_subject(existing, subject);
+                updated += 1;
+            }
+            MergeStrategy::Merge => {
+                merge_imported_subject(existing, &subject);
+                updated += 1;
+            }
+            _ => skipped += 1,
         }
     }
 
@@ -138,9 +401,73 @@ fn import_config(file: PathBuf) -> Result<()> {
     config::save_config(&config)?;
 
     ui::print_success(&format!(
-        "Imported {} subjects ({} skipped as duplicates)",
-        added, skipped
+        "Imported {} subjects ({} updated, {} skipped as duplicates)",
+        added, updated, skipped
     ));
 
     Ok(())
 }
+
+fn ask_merge_strategy(key: &str) -> Result<MergeStrategy> {
+    let options = vec!["Skip", "Overwrite", "Merge"];
+    let choice = ui::prompt_select(&format!("Subject '{}' already exists:", key), options)?;
+    Ok(match choice {
+        "Overwrite" => MergeStrategy::Overwrite,
+        "Merge" => MergeStrategy::Merge,
+        _ => MergeStrategy::SkipDuplicates,
+    })
+}
+
+/// Replace a subject with an imported one, keeping its ID so existing state still applies
+fn overwrite_subject(existing: &mut Subject, imported: Subject) {
+    let id = existing.id;
+    let notes_private = existing.notes_private.take();
+    *existing = Subject {
+        id,
+        notes_private,
+        ..imported
+    };
+}
+
+/// Union search terms and keep the longer notes; whether the subject is enabled or archived stays local
+fn merge_imported_subject(existing: &mut Subject, imported: &Subject) {
+    let (enabled, archived) = (existing.enabled, existing.archived);
+    dedup::merge_subject(existing, imported);
+    existing.enabled = enabled;
+    existing.archived = archived;
+    if let Some(ref notes) = imported.notes {
+        if existing.notes.as_ref().is_none_or(|n| notes.len() > n.len()) {
+            existing.notes = Some(notes.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_local_flags() {
+        let mut existing = Subject {
+            key: "gta6".to_string(),
+            enabled: false,
+            archived: true,
+            search_terms: vec!["GTA 6".to_string()],
+            ..Default::default()
+        };
+        let imported = Subject {
+            key: "gta6".to_string(),
+            enabled: true,
+            archived: false,
+            search_terms: vec!["Grand Theft Auto VI".to_string()],
+            notes: Some("Rockstar".to_string()),
+            ..Default::default()
+        };
+
+        merge_imported_subject(&mut existing, &imported);
+        assert!(!existing.enabled);
+        assert!(existing.archived);
+        assert_eq!(existing.search_terms.len(), 2);
+        assert_eq!(existing.notes.as_deref(), Some("Rockstar"));
+    }
+}
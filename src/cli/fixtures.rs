@@ -0,0 +1,114 @@
+use crate::claude::{self, ClaudeResponse};
+use crate::config::Subject;
+use crate::error::{HeadsupError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "fixtures_manifest.json";
+
+/// A captured backend response together with the prompt that produced it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Fixture {
+    pub subject_key: String,
+    pub timestamp: DateTime<Utc>,
+    pub prompt: String,
+    pub response: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub file: String,
+    pub subject_key: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Writes fixtures to a directory and keeps its manifest up to date
+pub struct FixtureWriter {
+    dir: PathBuf,
+    entries: Vec<ManifestEntry>,
+}
+
+impl FixtureWriter {
+    /// Create the fixture directory, keeping entries from an existing manifest
+    pub fn new(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let entries = if manifest_path.exists() {
+            serde_json::from_str(&fs::read_to_string(&manifest_path)?)?
+        } else {
+            Vec::new()
+        };
+        Ok(FixtureWriter { dir: dir.to_path_buf(), entries })
+    }
+
+    /// Save one raw response as `<subject-key>-<timestamp>.json`
+    pub fn save(&mut self, subject: &Subject, prompt: String, response: &str) -> Result<()> {
+        let timestamp = Utc::now();
+        let file = format!("{}-{}.json", subject.key, timestamp.format("%Y%m%dT%H%M%SZ"));
+        let fixture = Fixture {
+            subject_key: subject.key.clone(),
+            timestamp,
+            prompt,
+            response: response.to_string(),
+        };
+        fs::write(self.dir.join(&file), serde_json::to_string_pretty(&fixture)?)?;
+
+        self.entries.push(ManifestEntry {
+            file,
+            subject_key: subject.key.clone(),
+            timestamp,
+        });
+        Ok(())
+    }
+
+    /// Write the manifest and return the number of fixtures it lists
+    pub fn finish(self) -> Result<usize> {
+        let manifest = serde_json::to_string_pretty(&self.entries)?;
+        fs::write(self.dir.join(MANIFEST_FILE), manifest)?;
+        Ok(self.entries.len())
+    }
+}
+
+/// Load a saved response for a subject and parse it as if the backend had returned it
+///
+/// Looks for `<dir>/<subject-key>.json` first, then for the newest fixture of the
+/// subject in the manifest written by `--save-fixtures`. The file may hold a fixture
+/// or the raw backend output.
+pub fn load_response(dir: &Path, subject: &Subject) -> Result<(ClaudeResponse, String)> {
+    let path = find_response_file(dir, &subject.key)?.ok_or_else(|| {
+        HeadsupError::Config(format!(
+            "No saved response for '{}' in {}",
+            subject.key,
+            dir.display()
+        ))
+    })?;
+
+    let content = fs::read_to_string(&path)?;
+    let raw = match serde_json::from_str::<Fixture>(&content) {
+        Ok(fixture) => fixture.response,
+        Err(_) => content,
+    };
+
+    let response = claude::parse_response(subject.subject_type, &raw)?;
+    Ok((response, raw))
+}
+
+fn find_response_file(dir: &Path, subject_key: &str) -> Result<Option<PathBuf>> {
+    let direct = dir.join(format!("{}.json", subject_key));
+    if direct.exists() {
+        return Ok(Some(direct));
+    }
+
+    let manifest_path = dir.join(MANIFEST_FILE);
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let entries: Vec<ManifestEntry> = serde_json::from_str(&fs::read_to_string(&manifest_path)?)?;
+    Ok(entries
+        .into_iter()
+        .filter(|e| e.subject_key == subject_key)
+        .max_by_key(|e| e.timestamp)
+        .map(|e| dir.join(e.file)))
+}
@@ -1,15 +1,167 @@
-use crate::config;
+use crate::cli::{HistoryCommands, HistoryFormat};
+use crate::config::{self, Config, Subject};
 use crate::error::{HeadsupError, Result};
-use crate::state::{self, HistoryEntry, SubjectState};
+use crate::state::{self, HistoryEntry, State};
 use crate::ui;
+use crate::util::duration::{format_duration, parse_duration};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
 
 /// Run the history command
-pub fn run_history(subject_key: Option<String>, limit: usize, json_output: bool) -> Result<()> {
+pub fn run_history(
+    command: Option<HistoryCommands>,
+    subject_key: Option<String>,
+    limit: Option<usize>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    format: HistoryFormat,
+    json_output: bool,
+) -> Result<()> {
     let config = config::load_config()?;
     let state = state::load_state_readonly()?;
 
-    // Collect history entries
-    let mut entries: Vec<(String, &HistoryEntry)> = Vec::new();
+    let (entries, format) = match command {
+        Some(HistoryCommands::Export { subject, format }) => {
+            let entries = collect_entries(&config, &state, subject, None, None, None)?;
+            print_entries(&state, &entries, format);
+            return Ok(());
+        }
+        Some(HistoryCommands::Stats { subject, json }) => {
+            return print_stats(&config, &state, subject, json);
+        }
+        Some(HistoryCommands::Search { tag, json }) => {
+            let mut entries = collect_entries(&config, &state, None, None, None, None)?;
+            entries.retain(|(_, entry)| entry.tags.contains(&tag));
+            (entries, if json { HistoryFormat::Json } else { HistoryFormat::Text })
+        }
+        None => {
+            // A time window shows every entry in it unless --limit is also given
+            let limit = limit.or(if since.is_none() { Some(DEFAULT_LIMIT) } else { None });
+            let format = if json_output { HistoryFormat::Json } else { format };
+            (collect_entries(&config, &state, subject_key, limit, since, until)?, format)
+        }
+    };
+
+    if entries.is_empty() {
+        if since.is_some() || until.is_some() {
+            ui::print_info("No history entries in the specified time range");
+        } else {
+            ui::print_info("No history entries found");
+        }
+        return Ok(());
+    }
+
+    print_entries(&state, &entries, format);
+    Ok(())
+}
+
+/// Print history entries to stdout in the given format
+fn print_entries(state: &State, entries: &[(&Subject, &HistoryEntry)], format: HistoryFormat) {
+    match format {
+        HistoryFormat::Text => print_table(state, entries),
+        HistoryFormat::Json => print_json(state, entries),
+        HistoryFormat::Csv => print!("{}", format_csv(entries)),
+        HistoryFormat::Jsonl => {
+            for (subject, entry) in entries {
+                let record = HistoryRecord::new(subject, entry);
+                println!("{}", serde_json::to_string(&record).unwrap_or_default());
+            }
+        }
+    }
+}
+
+fn print_table(state: &State, entries: &[(&Subject, &HistoryEntry)]) {
+    println!("{:<20} {:<20} {:<15} DETAILS", "TIMESTAMP", "SUBJECT", "EVENT");
+    println!("{}", "-".repeat(80));
+
+    for (subject, entry) in entries {
+        let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M");
+        let mut details = format_details(&entry.details);
+        if let Some(channels) = state.channels_for(entry) {
+            details.push_str(&format!(" via {}", channels.join("+")));
+        }
+        println!(
+            "{:<20} {:<20} {:<15} {}",
+            timestamp,
+            truncate(&subject.name, 18),
+            entry.event,
+            truncate(&details, 30)
+        );
+    }
+}
+
+/// A history entry with the commonly used detail fields pulled out, as written to CSV and JSON Lines
+#[derive(Debug, Serialize)]
+struct HistoryRecord {
+    timestamp: DateTime<Utc>,
+    subject_key: String,
+    subject_name: String,
+    event: String,
+    /// Release or next occurrence date
+    found_date: Option<String>,
+    found_answer: Option<String>,
+    confidence: Option<String>,
+    source_url: Option<String>,
+    latency_ms: Option<u64>,
+    notified: Option<bool>,
+}
+
+impl HistoryRecord {
+    fn new(subject: &Subject, entry: &HistoryEntry) -> Self {
+        let detail = |key: &str| entry.details.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        HistoryRecord {
+            timestamp: entry.timestamp,
+            subject_key: subject.key.clone(),
+            subject_name: subject.name.clone(),
+            event: entry.event.clone(),
+            found_date: detail("found_release_date").or_else(|| detail("next_occurrence_date")),
+            found_answer: detail("found_answer"),
+            confidence: detail("confidence"),
+            source_url: entry.source_url.clone(),
+            latency_ms: entry.latency_ms,
+            notified: entry.details.get("should_notify").and_then(|v| v.as_bool()),
+        }
+    }
+}
+
+/// Entries shown when neither --limit nor --since is given
+const DEFAULT_LIMIT: usize = 20;
+
+/// Parse a `--since`/`--until` value: an RFC 3339 datetime, a `YYYY-MM-DD` date
+/// (midnight UTC), or an age like `7d` counted back from now
+pub fn parse_time_bound(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    parse_duration(value)
+        .map(|age| Utc::now() - age)
+        .map_err(|_| {
+            HeadsupError::Config(format!(
+                "Invalid time '{}' (expected an ISO 8601 datetime, a date like 2026-01-31, or an age like 7d)",
+                value
+            ))
+        })
+}
+
+/// Collect history entries, newest first
+fn collect_entries<'a>(
+    config: &'a Config,
+    state: &'a State,
+    subject_key: Option<String>,
+    limit: Option<usize>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<(&'a Subject, &'a HistoryEntry)>> {
+    let mut entries: Vec<(&Subject, &HistoryEntry)> = Vec::new();
+    let limit = limit.unwrap_or(usize::MAX);
+    let in_window = |e: &&HistoryEntry| {
+        since.is_none_or(|t| e.timestamp >= t) && until.is_none_or(|t| e.timestamp < t)
+    };
 
     match subject_key {
         Some(key) => {
@@ -18,9 +170,10 @@ pub fn run_history(subject_key: Option<String>, limit: usize, json_output: bool)
                 .ok_or_else(|| HeadsupError::SubjectNotFound(key.clone()))?;
 
             if let Some(subject_state) = state.subjects.get(&subject.id) {
-                let history = get_history_from_state(subject_state);
-                for entry in history.iter().rev().take(limit) {
-                    entries.push((subject.name.clone(), entry));
+                let history = subject_state.history();
+                let recent = history.iter().rev().filter(in_window);
+                for entry in recent.take(limit) {
+                    entries.push((subject, entry));
                 }
             }
         }
@@ -28,63 +181,194 @@ pub fn run_history(subject_key: Option<String>, limit: usize, json_output: bool)
             // Get history for all subjects
             for subject in &config.subjects {
                 if let Some(subject_state) = state.subjects.get(&subject.id) {
-                    let history = get_history_from_state(subject_state);
-                    for entry in history {
-                        entries.push((subject.name.clone(), entry));
+                    let history = subject_state.history();
+                    for entry in history.iter().filter(in_window) {
+                        entries.push((subject, entry));
                     }
                 }
             }
             // Sort by timestamp descending
-            entries.sort_by(|a, b| b.1.timestamp.cmp(&a.1.timestamp));
+            entries.sort_by_key(|e| std::cmp::Reverse(e.1.timestamp));
             entries.truncate(limit);
         }
     }
 
-    if entries.is_empty() {
+    Ok(entries)
+}
+
+/// Aggregated history of one subject, or of all of them for the totals row
+#[derive(Debug, Default, Serialize)]
+struct HistoryStats {
+    subject: String,
+    checks: usize,
+    notifications: usize,
+    failures: usize,
+    /// Percentage of successful checks that notified
+    notification_rate: f64,
+    /// Percentage of check attempts that failed
+    failure_rate: f64,
+    avg_seconds_between_checks: Option<i64>,
+    last_notification: Option<DateTime<Utc>>,
+    most_common_event: Option<String>,
+}
+
+impl HistoryStats {
+    fn from_entries(subject: &str, entries: &[&HistoryEntry]) -> Self {
+        let mut check_times: Vec<DateTime<Utc>> = entries.iter()
+            .filter(|e| e.event == "check")
+            .map(|e| e.timestamp)
+            .collect();
+        check_times.sort();
+        let notified: Vec<DateTime<Utc>> = entries.iter()
+            .filter(|e| e.event == "check" && e.details.get("should_notify").and_then(|v| v.as_bool()) == Some(true))
+            .map(|e| e.timestamp)
+            .collect();
+        let failures = entries.iter().filter(|e| e.event == state::FAILED_EVENT).count();
+
+        let mut event_counts: HashMap<&str, usize> = HashMap::new();
+        for entry in entries {
+            *event_counts.entry(entry.event.as_str()).or_insert(0) += 1;
+        }
+        // Ties go to the alphabetically first event, so the output is stable
+        let most_common_event = event_counts.into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+            .map(|(event, _)| event.to_string());
+
+        let percent = |part: usize, whole: usize| {
+            if whole == 0 { 0.0 } else { (part as f64 * 1000.0 / whole as f64).round() / 10.0 }
+        };
+        let avg_seconds_between_checks = (check_times.len() > 1).then(|| {
+            let span = *check_times.last().unwrap() - check_times[0];
+            span.num_seconds() / (check_times.len() as i64 - 1)
+        });
+
+        HistoryStats {
+            subject: subject.to_string(),
+            checks: check_times.len(),
+            notifications: notified.len(),
+            failures,
+            notification_rate: percent(notified.len(), check_times.len()),
+            failure_rate: percent(failures, check_times.len() + failures),
+            avg_seconds_between_checks,
+            last_notification: notified.into_iter().max(),
+            most_common_event,
+        }
+    }
+}
+
+fn print_stats(config: &Config, state: &State, subject_key: Option<String>, json_output: bool) -> Result<()> {
+    let single = subject_key.is_some();
+    let subjects: Vec<&Subject> = match subject_key {
+        Some(key) => vec![config.find_subject(&key).ok_or_else(|| HeadsupError::SubjectNotFound(key.clone()))?],
+        None => config.subjects.iter().collect(),
+    };
+
+    let histories: Vec<(&Subject, Vec<&HistoryEntry>)> = subjects.into_iter()
+        .filter_map(|subject| {
+            let history = state.subjects.get(&subject.id)?.history();
+            (!history.is_empty()).then(|| (subject, history.iter().collect()))
+        })
+        .collect();
+
+    if histories.is_empty() {
         ui::print_info("No history entries found");
         return Ok(());
     }
 
+    let rows: Vec<HistoryStats> = histories.iter()
+        .map(|(subject, entries)| HistoryStats::from_entries(&subject.name, entries))
+        .collect();
+    let totals = (!single).then(|| {
+        // Gaps between checks are averaged per subject, then across subjects
+        let all: Vec<&HistoryEntry> = histories.iter().flat_map(|(_, e)| e.iter().copied()).collect();
+        let mut totals = HistoryStats::from_entries("TOTAL", &all);
+        let gaps: Vec<i64> = rows.iter().filter_map(|r| r.avg_seconds_between_checks).collect();
+        totals.avg_seconds_between_checks = (!gaps.is_empty()).then(|| gaps.iter().sum::<i64>() / gaps.len() as i64);
+        totals
+    });
+
     if json_output {
-        // Output as JSON
-        let json_entries: Vec<serde_json::Value> = entries.iter()
-            .map(|(name, entry)| {
-                serde_json::json!({
-                    "subject": name,
-                    "timestamp": entry.timestamp,
-                    "event": entry.event,
-                    "details": entry.details,
-                    "source_url": entry.source_url,
-                })
+        let output = serde_json::json!({ "subjects": rows, "totals": totals });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<20} {:>6} {:>8} {:>8} {:>7} {:>8} {:<17} TOP EVENT",
+        "SUBJECT", "CHECKS", "NOTIFIED", "NOTIFY %", "FAIL %", "AVG GAP", "LAST NOTIFIED"
+    );
+    println!("{}", "-".repeat(95));
+    for row in rows.iter().chain(totals.as_ref()) {
+        println!(
+            "{:<20} {:>6} {:>8} {:>8.1} {:>7.1} {:>8} {:<17} {}",
+            truncate(&row.subject, 18),
+            row.checks,
+            row.notifications,
+            row.notification_rate,
+            row.failure_rate,
+            row.avg_seconds_between_checks
+                .map(|s| format_duration(Duration::seconds(s)))
+                .unwrap_or_else(|| "-".to_string()),
+            row.last_notification
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            row.most_common_event.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+fn print_json(state: &State, entries: &[(&Subject, &HistoryEntry)]) {
+    let json_entries: Vec<serde_json::Value> = entries.iter()
+        .map(|(subject, entry)| {
+            serde_json::json!({
+                "subject": subject.name,
+                "timestamp": entry.timestamp,
+                "event": entry.event,
+                "details": entry.details,
+                "source_url": entry.source_url,
+                "tags": entry.tags,
+                "channels": state.channels_for(entry).unwrap_or_default(),
             })
-            .collect();
-        println!("{}", serde_json::to_string_pretty(&json_entries).unwrap());
-    } else {
-        // Output as text
-        println!("{:<20} {:<20} {:<15} {}", "TIMESTAMP", "SUBJECT", "EVENT", "DETAILS");
-        println!("{}", "-".repeat(80));
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&json_entries).unwrap());
+}
 
-        for (name, entry) in entries {
-            let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M");
-            let details = format_details(&entry.details);
-            println!(
-                "{:<20} {:<20} {:<15} {}",
-                timestamp,
-                truncate(&name, 18),
-                entry.event,
-                truncate(&details, 30)
-            );
-        }
+/// Format history entries as CSV with a header row
+fn format_csv(entries: &[(&Subject, &HistoryEntry)]) -> String {
+    let mut out = String::from(
+        "timestamp,subject_key,subject_name,event,found_date,found_answer,confidence,source_url,latency_ms,notified\n",
+    );
+
+    for (subject, entry) in entries {
+        let record = HistoryRecord::new(subject, entry);
+        let fields = [
+            record.timestamp.to_rfc3339(),
+            record.subject_key,
+            record.subject_name,
+            record.event,
+            record.found_date.unwrap_or_default(),
+            record.found_answer.unwrap_or_default(),
+            record.confidence.unwrap_or_default(),
+            record.source_url.unwrap_or_default(),
+            record.latency_ms.map(|ms| ms.to_string()).unwrap_or_default(),
+            record.notified.map(|b| b.to_string()).unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
     }
 
-    Ok(())
+    out
 }
 
-fn get_history_from_state(state: &SubjectState) -> &[HistoryEntry] {
-    match state {
-        SubjectState::Release(s) => &s.history,
-        SubjectState::Question(s) => &s.history,
-        SubjectState::Recurring(s) => &s.history,
+/// Quote a CSV field if it contains a delimiter, quote, or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
     }
 }
 
@@ -125,3 +409,83 @@ fn truncate(s: &str, max_len: usize) -> String {
         format!("{}...", &s[..max_len - 3])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SubjectType;
+
+    fn entry(timestamp: DateTime<Utc>, event: &str, should_notify: bool) -> HistoryEntry {
+        HistoryEntry {
+            timestamp,
+            event: event.to_string(),
+            details: serde_json::json!({ "should_notify": should_notify }),
+            source_url: None,
+            raw_response: None,
+            raw_ai_output: None,
+            latency_ms: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        let midnight = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        assert_eq!(parse_time_bound("2026-01-31").unwrap(), midnight);
+        assert_eq!(parse_time_bound("2026-01-31T02:00:00+02:00").unwrap(), midnight);
+
+        let week_ago = parse_time_bound("7d").unwrap();
+        assert!((Utc::now() - Duration::days(7) - week_ago).num_seconds().abs() < 5);
+        assert!(parse_time_bound("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_until_date_is_exclusive() {
+        let mut config = Config::default_with_email("user@example.com");
+        let subject = Subject {
+            key: "show".to_string(),
+            subject_type: SubjectType::Question,
+            question: Some("Is it renewed?".to_string()),
+            ..Default::default()
+        };
+        let mut state = State::default();
+        state.get_or_create_question(&subject).unwrap();
+        config.subjects.push(subject.clone());
+
+        let until = parse_time_bound("2026-02-01").unwrap();
+        state.add_history(subject.id, entry(until - Duration::seconds(1), "check", false), &config);
+        state.add_history(subject.id, entry(until, "check", false), &config);
+
+        let entries = collect_entries(&config, &state, Some("show".to_string()), None, None, Some(until)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].1.timestamp < until);
+    }
+
+    #[test]
+    fn test_stats_from_entries() {
+        let start = Utc::now() - Duration::hours(10);
+        let entries = [
+            entry(start, "check", true),
+            entry(start + Duration::hours(2), "check", false),
+            entry(start + Duration::hours(3), state::FAILED_EVENT, false),
+            entry(start + Duration::hours(4), "check", false),
+            entry(start + Duration::hours(4), "check", false),
+        ];
+        let refs: Vec<&HistoryEntry> = entries.iter().collect();
+        let stats = HistoryStats::from_entries("show", &refs);
+
+        assert_eq!(stats.checks, 4);
+        assert_eq!(stats.notifications, 1);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.notification_rate, 25.0);
+        assert_eq!(stats.failure_rate, 20.0);
+        assert_eq!(stats.avg_seconds_between_checks, Some(4800));
+        assert_eq!(stats.last_notification, Some(start));
+        assert_eq!(stats.most_common_event.as_deref(), Some("check"));
+
+        let empty = HistoryStats::from_entries("none", &[]);
+        assert_eq!(empty.checks, 0);
+        assert_eq!(empty.failure_rate, 0.0);
+        assert!(empty.avg_seconds_between_checks.is_none());
+    }
+}
@@ -1,9 +1,14 @@
 use crate::config::{self, Config};
-use crate::error::Result;
+use crate::error::{HeadsupError, Result};
 use crate::ui;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Placeholder `config export` writes in place of secrets
+const REDACTED: &str = "[REDACTED]";
 
 /// Run the init command
-pub fn run_init(force: bool, email: Option<String>) -> Result<()> {
+pub fn run_init(force: bool, email: Option<String>, from_export: Option<PathBuf>) -> Result<()> {
     let config_path = config::config_path()?;
 
     // Check if config already exists
@@ -16,20 +21,24 @@ pub fn run_init(force: bool, email: Option<String>) -> Result<()> {
         return Ok(());
     }
 
+    if let Some(path) = from_export {
+        return init_from_export(&path, &config_path);
+    }
+
     // Get email address
     let email_addr = if let Some(e) = email {
         e
     } else if ui::is_interactive() {
         ui::prompt_text("Email address for notifications:")?
     } else {
-        return Err(crate::error::HeadsupError::Config(
+        return Err(HeadsupError::Config(
             "Email address required (use --email flag)".to_string(),
         ));
     };
 
     // Validate email (basic check)
     if !email_addr.contains('@') {
-        return Err(crate::error::HeadsupError::Config(
+        return Err(HeadsupError::Config(
             "Invalid email address format".to_string(),
         ));
     }
@@ -45,6 +54,104 @@ pub fn run_init(force: bool, email: Option<String>) -> Result<()> {
         config_path.display()
     ));
     ui::print_info("Edit the config file to configure your SMTP settings");
+    ui::print_info(
+        "smtp_password_command is run through the shell; on macOS, \"keychain:<service>/<account>\" reads the password from the Keychain instead (store it with 'headsup config keychain-set'), on Linux \"secret-service:<service>/<account>\" reads it from the keyring ('headsup config secret-store'), and \"pass:<entry>\" reads it from pass ('headsup config pass-set')",
+    );
+    if uses_oauth_provider(&email_addr) {
+        ui::print_info(
+            "This provider may require OAuth2: set smtp_auth_mechanism = \"xoauth2\" and make smtp_password_command print an access token",
+        );
+    }
 
     Ok(())
 }
+
+/// Whether the address belongs to a provider that commonly rejects app passwords
+fn uses_oauth_provider(email: &str) -> bool {
+    const OAUTH_DOMAINS: &[&str] = &["gmail.com", "googlemail.com", "outlook.com", "hotmail.com", "live.com", "office365.com"];
+    email
+        .rsplit_once('@')
+        .is_some_and(|(_, domain)| OAUTH_DOMAINS.contains(&domain.to_lowercase().as_str()))
+}
+
+/// Restore a config from an export, asking for each redacted value
+fn init_from_export(export_path: &Path, config_path: &Path) -> Result<()> {
+    let content = fs::read_to_string(export_path)?;
+    let mut value: toml::Value = toml::from_str(&content)?;
+
+    let mut redacted = Vec::new();
+    find_redacted(&value, "", &mut redacted);
+    if !redacted.is_empty() && !ui::is_interactive() {
+        return Err(HeadsupError::Config(format!(
+            "Export has redacted fields that need an interactive terminal: {}",
+            redacted.join(", ")
+        )));
+    }
+    fill_redacted(&mut value, "")?;
+
+    let config: Config = value.try_into()?;
+    if let Err(errors) = config.validate() {
+        for error in errors {
+            ui::print_error(&error);
+        }
+        return Err(HeadsupError::ConfigInvalid("Restored config is invalid".to_string()));
+    }
+
+    config::save_config(&config)?;
+
+    ui::print_success(&format!(
+        "Restored {} subjects into {}",
+        config.subjects.len(),
+        config_path.display()
+    ));
+    ui::print_info("Run 'headsup test-email' to verify the SMTP settings");
+
+    Ok(())
+}
+
+/// Collect the dotted paths of all redacted string values
+fn find_redacted(value: &toml::Value, path: &str, found: &mut Vec<String>) {
+    match value {
+        toml::Value::String(s) if s.contains(REDACTED) => found.push(path.to_string()),
+        toml::Value::Table(table) => {
+            for (key, child) in table {
+                find_redacted(child, &join_path(path, key), found);
+            }
+        }
+        toml::Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                find_redacted(child, &format!("{}[{}]", path, i), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Prompt for a replacement for every redacted string value
+fn fill_redacted(value: &mut toml::Value, path: &str) -> Result<()> {
+    match value {
+        toml::Value::String(s) if s.contains(REDACTED) => {
+            *s = ui::prompt_text(&format!("{} (redacted in export):", path))?;
+        }
+        toml::Value::Table(table) => {
+            for (key, child) in table.iter_mut() {
+                fill_redacted(child, &join_path(path, key))?;
+            }
+        }
+        toml::Value::Array(items) => {
+            for (i, child) in items.iter_mut().enumerate() {
+                fill_redacted(child, &format!("{}[{}]", path, i))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
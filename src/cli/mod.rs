@@ -1,20 +1,29 @@
+mod benchmark;
+mod cache_cmd;
 mod check;
+mod compare;
 mod config_cmd;
+mod fixtures;
 mod history;
 mod init;
 mod notify;
 mod state_cmd;
+mod stats;
 mod subjects;
 
-pub use check::run_check;
+pub use cache_cmd::run_cache;
+pub use check::{run_check, CheckOptions, CheckResult};
 pub use config_cmd::run_config;
 pub use history::run_history;
 pub use init::run_init;
-pub use notify::run_notify;
+pub use notify::{run_notify, NotifyOptions};
 pub use state_cmd::run_state;
+pub use stats::run_stats;
 pub use subjects::run_subjects;
 
-use clap::{Parser, Subcommand};
+use crate::state::{Confidence, ReleaseStatus};
+use crate::templates::QuestionTemplate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -46,13 +55,23 @@ pub struct Cli {
     pub command: Option<Commands>,
 }
 
+// Parsed once per run, so the size of the check variant doesn't matter
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// Run a check for all subjects, or a specific one
     Check {
         /// Check only this subject (by key or UUID)
         subject: Option<String>,
 
+        /// Only check subjects with this tag
+        #[arg(long, value_name = "TAG", conflicts_with = "subject")]
+        tag: Option<String>,
+
+        /// Only check subjects in this group
+        #[arg(long, value_name = "GROUP", conflicts_with = "subject")]
+        group: Option<String>,
+
         /// Don't send emails or update state
         #[arg(long)]
         dry_run: bool,
@@ -64,6 +83,114 @@ pub enum Commands {
         /// Only check and update state, don't send emails
         #[arg(long)]
         no_notify: bool,
+
+        /// Send notifications only to the [webhook] endpoint, not by email
+        #[arg(long, conflicts_with = "no_notify")]
+        webhook_only: bool,
+
+        /// Benchmark prompt construction and response parsing (no AI calls)
+        #[arg(long)]
+        benchmark: bool,
+
+        /// Override the per-subject AI timeout for this run
+        #[arg(long, value_name = "SECONDS")]
+        ai_timeout: Option<u64>,
+
+        /// Treat responses that cite no source URL as failed checks
+        #[arg(long)]
+        fail_on_no_sources: bool,
+
+        /// Store the unparsed AI output in each history entry
+        #[arg(long)]
+        annotate_response: bool,
+
+        /// Skip subjects whose last check took longer than this (use --force to override)
+        #[arg(long, value_name = "MS")]
+        max_latency: Option<u64>,
+
+        /// Write an HTML report of all subjects to this file after checking
+        #[arg(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+
+        /// Save each raw AI response and its prompt to this directory
+        #[arg(long, value_name = "DIR")]
+        save_fixtures: Option<PathBuf>,
+
+        /// Append a JSON summary of this run as one line to this file
+        #[arg(long, value_name = "PATH")]
+        result_file: Option<PathBuf>,
+
+        /// Tag the history entry of every successful check in this run
+        #[arg(long, value_name = "TAG", value_parser = check::parse_tag)]
+        tag_all: Option<String>,
+
+        /// POST a JSON alert to this URL for each subject whose check fails
+        #[arg(long, value_name = "URL")]
+        webhook_on_failure: Option<String>,
+
+        /// Sync state with the S3 bucket in settings.cloud_sync before and after the run
+        #[arg(long)]
+        cloud_sync: bool,
+
+        /// Start at most this many AI calls per minute (overrides claude.max_calls_per_minute)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        rate_limit_backend: Option<u32>,
+
+        /// Run at most N subject checks at the same time (overrides claude.parallel)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        parallel: Option<u32>,
+
+        /// Write run metrics in this format to --metrics-file
+        #[arg(long, value_name = "FORMAT", requires = "metrics_file")]
+        emit_metrics: Option<MetricsFormat>,
+
+        /// File to write metrics to (e.g. for the node exporter textfile collector)
+        #[arg(long, value_name = "PATH", requires = "emit_metrics")]
+        metrics_file: Option<PathBuf>,
+
+        /// Overwrite this file with a one-line status summary after the run
+        #[arg(long, value_name = "PATH")]
+        status_file: Option<PathBuf>,
+
+        /// Template for --status-file ({{subjects}}, {{pending}}, {{last_run}})
+        #[arg(long, value_name = "TEMPLATE", requires = "status_file")]
+        status_format: Option<String>,
+
+        /// Wait until the network is reachable before checking
+        #[arg(long)]
+        wait_for_internet: bool,
+
+        /// How long --wait-for-internet waits before giving up, in seconds
+        #[arg(long, value_name = "SECONDS", default_value_t = 120, requires = "wait_for_internet")]
+        timeout: u64,
+
+        /// Call the AI once for subjects with identical prompts and share the response
+        #[arg(long)]
+        cache_prompts: bool,
+
+        /// Replay AI responses saved in this directory (<subject-key>.json) instead of calling the AI
+        #[arg(long, value_name = "DIR")]
+        save_responses: Option<PathBuf>,
+
+        /// Push run metrics to a metrics gateway
+        #[arg(long, value_name = "FORMAT")]
+        output: Option<CheckOutput>,
+
+        /// Pushgateway URL for --output prometheus-push (defaults to settings.prometheus_pushgateway_url)
+        #[arg(long, value_name = "URL", requires = "output")]
+        pushgateway_url: Option<String>,
+
+        /// Print the results as a JSON array instead of progress lines
+        #[arg(long)]
+        json: bool,
+
+        /// Check with both Claude and Perplexity and compare their answers (nothing is saved)
+        #[arg(long, conflicts_with_all = ["json", "save_responses", "cache_prompts"])]
+        compare_backends: bool,
+
+        /// Fail subjects whose backend call fails instead of trying settings.ai_backend_fallback
+        #[arg(long)]
+        no_fallback: bool,
     },
 
     /// Send pending notifications
@@ -75,6 +202,43 @@ pub enum Commands {
         /// Force digest mode for this run
         #[arg(long)]
         digest: bool,
+
+        /// Only send notifications at or above this confidence
+        /// (official, reliable, rumor, speculation); others stay queued
+        #[arg(long, value_name = "LEVEL")]
+        filter_confidence: Option<Confidence>,
+
+        /// Send a separate digest for each day notifications were queued
+        #[arg(long)]
+        group_by_date: bool,
+
+        /// Merge pending notifications per subject, keeping the highest-confidence one
+        #[arg(long)]
+        consolidate: bool,
+
+        /// Where and how to deliver notifications
+        #[arg(long, default_value = "email")]
+        format: NotifyFormat,
+
+        /// Webhook URL (required for --format discord-webhook)
+        #[arg(long, value_name = "URL", required_if_eq("format", "discord-webhook"))]
+        url: Option<String>,
+
+        /// Send notifications only to the [webhook] endpoint, not by email
+        #[arg(long, conflicts_with_all = ["url", "body_only"])]
+        webhook_only: bool,
+
+        /// Print only the email body for --subject's pending notification, without sending
+        #[arg(long, requires = "subject")]
+        body_only: bool,
+
+        /// Subject (key or UUID) whose pending notification --body-only prints
+        #[arg(long, value_name = "KEY", requires = "body_only")]
+        subject: Option<String>,
+
+        /// Print the email subject line before the body
+        #[arg(long, requires = "body_only")]
+        subject_line: bool,
     },
 
     /// Manage monitored subjects
@@ -95,20 +259,53 @@ pub enum Commands {
         command: StateCommands,
     },
 
+    /// Manage the backend response cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+
     /// View notification history
+    #[command(args_conflicts_with_subcommands = true)]
     History {
+        #[command(subcommand)]
+        command: Option<HistoryCommands>,
+
         /// Show history for specific subject only
         subject: Option<String>,
 
-        /// Show only last N entries
-        #[arg(long, default_value = "20")]
-        limit: usize,
-
-        /// Output as JSON
+        /// Show only last N entries (default 20, or all entries with --since)
         #[arg(long)]
+        limit: Option<usize>,
+
+        /// Show only entries from this time on: an ISO 8601 datetime or date, or an age like 30m, 24h, 7d
+        #[arg(long, value_name = "WHEN", value_parser = history::parse_time_bound)]
+        since: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Show only entries before this time, in the same formats as --since
+        #[arg(long, value_name = "WHEN", value_parser = history::parse_time_bound)]
+        until: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: HistoryFormat,
+
+        /// Output as JSON (same as --format json)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
     },
 
+    /// Show check activity recorded over the last days
+    Stats {
+        /// Show one row per day with a bar chart
+        #[arg(long)]
+        by_day: bool,
+
+        /// Number of days to include, counting today
+        #[arg(long, value_name = "N", default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+        last_n_days: u32,
+    },
+
     /// Initialize config and state files
     Init {
         /// Overwrite existing files
@@ -118,19 +315,77 @@ pub enum Commands {
         /// Set email address during init
         #[arg(long)]
         email: Option<String>,
+
+        /// Restore from a 'config export' file, prompting for redacted secrets
+        #[arg(long, value_name = "FILE", conflicts_with = "email")]
+        from_export: Option<PathBuf>,
     },
 
     /// Send a test email to verify SMTP configuration
     TestEmail,
+
+    /// Send a test message to verify the Telegram configuration
+    TestTelegram,
 }
 
 #[derive(Subcommand)]
 pub enum SubjectsCommands {
     /// List all subjects with status
-    List,
+    List(ListArgs),
 
     /// Add a new subject (interactive, AI-assisted)
-    Add,
+    Add {
+        /// Add a question subject from a predefined question pattern
+        #[arg(long, value_name = "TEMPLATE", requires = "name")]
+        question_template: Option<QuestionTemplate>,
+
+        /// Subject name to fill into the question template
+        #[arg(long, requires = "question_template")]
+        name: Option<String>,
+
+        /// Add a recurring subject that watches an RSS/Atom feed for new entries
+        #[arg(long, value_name = "URL", conflicts_with = "question_template")]
+        watch_rss: Option<String>,
+
+        /// Identify several subjects with a single AI call and confirm each one
+        #[arg(
+            long,
+            value_name = "QUERY",
+            num_args = 1..,
+            conflicts_with_all = ["question_template", "watch_rss"]
+        )]
+        bulk_ai: Vec<String>,
+
+        /// Read a JSON subject, or an array of subjects, from stdin
+        #[arg(long, conflicts_with_all = ["question_template", "watch_rss", "bulk_ai"])]
+        from_stdin: bool,
+
+        /// Let the AI pick the category of release subjects instead of asking
+        #[arg(long, conflicts_with_all = ["question_template", "watch_rss"])]
+        category_auto: bool,
+
+        /// Test the suggested search terms with a quick AI query and edit them until they fit
+        #[arg(long, conflicts_with_all = ["question_template", "watch_rss", "bulk_ai", "from_stdin"])]
+        refine_search_terms: bool,
+    },
+
+    /// Show details for a single subject
+    Show {
+        /// Subject key or UUID
+        key: String,
+    },
+
+    /// Copy a subject under a new key, starting with fresh state
+    Clone {
+        /// Key or UUID of the subject to copy
+        source_key: String,
+
+        /// Key of the new subject ("" generates one from the name)
+        new_key: String,
+
+        /// Name of the new subject (default "Copy of <name>")
+        new_name: Option<String>,
+    },
 
     /// Remove a subject
     Remove {
@@ -138,6 +393,24 @@ pub enum SubjectsCommands {
         key: String,
     },
 
+    /// Find and merge subjects with the same search terms
+    Deduplicate {
+        /// Only report duplicates, don't merge
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Minimum Jaccard similarity of search terms (1.0 = identical sets)
+        #[arg(long, value_name = "0.0-1.0", default_value_t = 1.0)]
+        similarity_threshold: f64,
+    },
+
+    /// Print a Graphviz DOT graph of subjects and their related_subjects links
+    Graph {
+        /// Write the graph to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
     /// Edit a subject (interactive)
     Edit {
         /// Subject key or UUID
@@ -155,12 +428,138 @@ pub enum SubjectsCommands {
         /// Subject key or UUID
         key: String,
     },
+
+    /// Add or remove tags on a subject
+    Tag {
+        /// Subject key or UUID
+        key: String,
+
+        /// Tags to add
+        #[arg(long, value_name = "TAG", num_args = 1..)]
+        add: Vec<String>,
+
+        /// Tags to remove
+        #[arg(long, value_name = "TAG", num_args = 1..)]
+        remove: Vec<String>,
+    },
+
+    /// Stop checking a resolved subject but keep it and its history
+    Archive {
+        /// Subject key or UUID
+        key: String,
+    },
+
+    /// Resume checking an archived subject
+    Unarchive {
+        /// Subject key or UUID
+        key: String,
+    },
+
+    /// Manage subject groups
+    Groups {
+        #[command(subcommand)]
+        command: GroupsCommands,
+    },
+
+    /// Skip a subject in checks until a date, without disabling it
+    Pause {
+        /// Subject key or UUID
+        key: String,
+
+        /// Resume checking at this date (YYYY-MM-DD, local midnight) or RFC 3339 time
+        #[arg(long, value_name = "DATE")]
+        until: String,
+    },
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ListArgs {
+    /// Only print the number of matching subjects
+    #[arg(long)]
+    pub count_only: bool,
+
+    /// Only include enabled subjects
+    #[arg(long, conflicts_with = "filter_disabled")]
+    pub filter_enabled: bool,
+
+    /// Only include disabled subjects
+    #[arg(long)]
+    pub filter_disabled: bool,
+
+    /// Only include subjects with this tag
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Only include archived subjects (hidden by default)
+    #[arg(long, conflicts_with = "all")]
+    pub archived: bool,
+
+    /// Include archived subjects along with active ones
+    #[arg(long)]
+    pub all: bool,
+
+    /// Only include release subjects with this status
+    /// (announced, delayed, released, cancelled, unknown)
+    #[arg(long, value_name = "STATUS")]
+    pub filter_status: Option<ReleaseStatus>,
+
+    /// Print only the data rows, without the header and separator
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Add a PENDING column with the number of queued notifications
+    #[arg(long)]
+    pub show_pending: bool,
+
+    /// Show subject UUIDs (as a column on wide terminals, on a second line otherwise)
+    #[arg(long)]
+    pub show_id: bool,
+
+    /// Output format: a table, or [[subjects]] TOML blocks to paste into another config
+    #[arg(long, default_value = "table")]
+    pub format: ListFormat,
+
+    /// Table layout: every column, or compact tab-separated KEY and NAME
+    #[arg(long, value_name = "LAYOUT", default_value = "full", conflicts_with_all = ["show_pending", "show_id", "upcoming_only"])]
+    pub table_format: TableFormat,
+
+    /// Print subjects with their state as a JSON array
+    #[arg(long, conflicts_with = "format")]
+    pub json: bool,
+
+    /// Only show dated subjects due within --within-days and open questions, soonest first
+    #[arg(long, conflicts_with = "show_pending")]
+    pub upcoming_only: bool,
+
+    /// Window for --upcoming-only, in days
+    #[arg(long, value_name = "N", default_value_t = 30, requires = "upcoming_only")]
+    pub within_days: u32,
+}
+
+/// Output formats for `subjects list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ListFormat {
+    #[default]
+    Table,
+    Toml,
+}
+
+/// Table layouts for `subjects list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum TableFormat {
+    #[default]
+    Full,
+    Compact,
 }
 
 #[derive(Subcommand)]
 pub enum ConfigCommands {
     /// Show config (secrets redacted)
-    Show,
+    Show {
+        /// Only show this section of the config
+        #[arg(long)]
+        section: Option<ConfigSection>,
+    },
 
     /// Open config in $EDITOR
     Edit,
@@ -168,9 +567,50 @@ pub enum ConfigCommands {
     /// Validate config file
     Validate,
 
+    /// Check config for security issues
+    Audit,
+
     /// Print config file path
     Path,
 
+    /// Print version, OS and path details to paste into a bug report
+    EnvironmentInfo,
+
+    /// Choose, test, and save the command that prints the SMTP password
+    SetSmtpPassword,
+
+    /// Store the SMTP password in the macOS Keychain (prompts for it)
+    KeychainSet {
+        /// Keychain item name
+        #[arg(long, default_value = "headsup")]
+        service: String,
+
+        /// Keychain account
+        #[arg(long, default_value = "smtp")]
+        account: String,
+    },
+
+    /// Store the SMTP password in the Secret Service keyring on Linux (prompts for it)
+    SecretStore {
+        /// Value of the 'service' attribute
+        #[arg(long, default_value = "headsup")]
+        service: String,
+
+        /// Value of the 'account' attribute
+        #[arg(long, default_value = "smtp")]
+        account: String,
+    },
+
+    /// Store the SMTP password in pass (prompts for it)
+    PassSet {
+        /// pass entry to write
+        #[arg(long, default_value = "email/smtp")]
+        path: String,
+    },
+
+    /// Print a JSON Schema of the config file for editor completion and validation
+    Schema,
+
     /// Export config to stdout (secrets redacted)
     Export,
 
@@ -178,17 +618,182 @@ pub enum ConfigCommands {
     Import {
         /// File to import
         file: PathBuf,
+
+        /// How to handle subjects whose key already exists
+        #[arg(long, default_value = "skip-duplicates")]
+        merge_strategy: MergeStrategy,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommands {
+    /// Export all history entries to stdout
+    Export {
+        /// Export history for specific subject only
+        subject: Option<String>,
+
+        /// Output format
+        #[arg(long, default_value = "json")]
+        format: HistoryFormat,
+    },
+
+    /// Find history entries by tag
+    Search {
+        /// Only show entries with this tag
+        #[arg(long)]
+        tag: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Summarize checks, notifications and failures per subject
+    Stats {
+        /// Show stats for specific subject only
+        subject: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Delivery formats for pending notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum NotifyFormat {
+    #[default]
+    Email,
+    DiscordWebhook,
+}
+
+/// Formats for `check --emit-metrics`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MetricsFormat {
+    Prometheus,
+}
+
+/// Destinations for `check --output`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CheckOutput {
+    /// Push run metrics to a Prometheus Pushgateway
+    PrometheusPush,
+}
+
+/// Output formats for history export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HistoryFormat {
+    /// Table for reading in the terminal
+    Text,
+    Json,
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+/// How `config import` handles subjects that already exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum MergeStrategy {
+    /// Keep the existing subject
+    #[default]
+    SkipDuplicates,
+    /// Replace the existing subject with the imported one
+    Overwrite,
+    /// Union search terms and keep the longer notes
+    Merge,
+    /// Ask for each conflicting subject
+    Ask,
+}
+
+/// Top-level sections of the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConfigSection {
+    Email,
+    Claude,
+    Perplexity,
+    #[value(name = "openai")]
+    OpenAi,
+    Ollama,
+    Settings,
+    Subjects,
+}
+
+impl std::fmt::Display for ConfigSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSection::Email => write!(f, "email"),
+            ConfigSection::Claude => write!(f, "claude"),
+            ConfigSection::Perplexity => write!(f, "perplexity"),
+            ConfigSection::OpenAi => write!(f, "openai"),
+            ConfigSection::Ollama => write!(f, "ollama"),
+            ConfigSection::Settings => write!(f, "settings"),
+            ConfigSection::Subjects => write!(f, "subjects"),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum GroupsCommands {
+    /// List groups with their settings and members
+    List,
+
+    /// Create an empty group
+    Create {
+        name: String,
+
+        /// Minimum hours between two checks of each member (default: settings.default_check_interval_hours)
+        #[arg(long, value_name = "HOURS")]
+        check_interval_hours: Option<u32>,
+    },
+
+    /// Delete a group (its subjects are kept)
+    Delete {
+        name: String,
+    },
+
+    /// Add a subject to a group
+    AddSubject {
+        group: String,
+
+        /// Subject key or UUID
+        subject: String,
     },
 }
 
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Delete all cached responses
+    Clear,
+}
+
 #[derive(Subcommand)]
 pub enum StateCommands {
     /// Show current state
     Show,
 
+    /// Show state file metadata (writer version, write time, subject count)
+    Info,
+
     /// Remove orphaned entries (subjects not in config)
     Prune,
 
+    /// Rewrite the state file in canonical form (sorted, pretty-printed)
+    Compact,
+
+    /// Upgrade an old state file to the current format (the original is backed up)
+    Migrate {
+        /// Print the migrated state without writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check that the state file is readable and each subject's state matches its configured type
+    Verify {
+        /// Reset state entries whose type does not match the config
+        #[arg(long)]
+        fix: bool,
+    },
+
     /// Reset state for a subject (or all if no key)
     Reset {
         /// Subject key or UUID
@@ -197,4 +802,31 @@ pub enum StateCommands {
 
     /// Print state file path
     Path,
+
+    /// Discard pending notifications that expired or exceeded settings.pending_notification_max_age_hours
+    PruneNotifications,
+
+    /// Copy the state file to the backups directory, or to a given file
+    Backup {
+        /// Write the backup here instead (not counted against settings.max_state_backups)
+        output: Option<PathBuf>,
+    },
+
+    /// Replace the state with a backup
+    Restore {
+        /// Backup file to restore
+        backup: PathBuf,
+    },
+
+    /// Manage state backups
+    Backups {
+        #[command(subcommand)]
+        command: BackupsCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BackupsCommands {
+    /// List backups, oldest first
+    List,
 }
@@ -1,12 +1,64 @@
+use crate::cli::NotifyFormat;
 use crate::config::{self, Config};
 use crate::email::{self, build_digest_email, EmailContent};
-use crate::error::{ExitStatus, Result};
-use crate::state::{self, PendingNotification};
+use crate::error::{ExitStatus, HeadsupError, Result};
+use crate::state::{self, Confidence, PendingNotification};
 use crate::ui;
+use crate::telegram;
+use crate::webhook::{self, discord};
+use chrono::{Local, NaiveDate, Utc};
+use std::collections::BTreeMap;
+
+/// Options for a notify run
+#[derive(Debug, Default)]
+pub struct NotifyOptions {
+    pub dry_run: bool,
+    /// Force digest mode for this run
+    pub digest: bool,
+    /// Only send notifications at or above this confidence
+    pub filter_confidence: Option<Confidence>,
+    /// Send one digest per day notifications were queued
+    pub group_by_date: bool,
+    /// Keep only the highest-confidence notification per subject
+    pub consolidate: bool,
+    pub format: NotifyFormat,
+    /// Webhook URL for webhook formats
+    pub webhook_url: Option<String>,
+    /// Send only to the configured webhook, skipping email
+    pub webhook_only: bool,
+    /// Print the email body for this subject's newest pending notification and exit
+    pub body_only: Option<String>,
+    /// Print the email subject line before the body
+    pub subject_line: bool,
+}
 
 /// Run the notify command
-pub fn run_notify(dry_run: bool, digest: bool) -> Result<ExitStatus> {
+pub async fn run_notify(options: NotifyOptions) -> Result<ExitStatus> {
+    let NotifyOptions {
+        dry_run,
+        digest,
+        filter_confidence,
+        group_by_date,
+        consolidate,
+        format,
+        webhook_url,
+        webhook_only,
+        body_only,
+        subject_line,
+    } = options;
+
     let config = config::load_config()?;
+
+    if let Some(key) = body_only {
+        print_email_body(&config, &key, subject_line)?;
+        return Ok(ExitStatus::Success);
+    }
+    if webhook_only && config.webhook.is_none() {
+        return Err(HeadsupError::Config("--webhook-only requires a [webhook] section in the config".to_string()));
+    }
+    // The webhook gets every notification alongside email, or alone with --webhook-only
+    let also_webhook = !webhook_only && format == NotifyFormat::Email && config.webhook.is_some();
+    let also_telegram = !webhook_only && format == NotifyFormat::Email && config.telegram.is_some();
     let (mut state, lock) = state::load_state()?;
 
     if state.pending_notifications.is_empty() {
@@ -14,7 +66,47 @@ pub fn run_notify(dry_run: bool, digest: bool) -> Result<ExitStatus> {
         return Ok(ExitStatus::Success);
     }
 
-    let notifications = state.clear_pending_notifications();
+    // Drop notifications that expired or outlived their TTL; they are never sent
+    let expired = state.discard_expired_notifications(Utc::now(), config.settings.pending_notification_max_age_hours);
+    log_discarded(&config, &expired);
+    let mut notifications = state.clear_pending_notifications();
+    if notifications.is_empty() {
+        ui::print_info("No pending notifications (all expired)");
+        if !dry_run {
+            state::save_state(&mut state, &lock)?;
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    // Keep lower-confidence notifications queued for a later run
+    if let Some(min_confidence) = filter_confidence {
+        let (matching, remaining): (Vec<_>, Vec<_>) = notifications
+            .into_iter()
+            .partition(|n| notification_confidence(n).is_at_least(&min_confidence));
+        state.pending_notifications = remaining;
+        notifications = matching;
+
+        if notifications.is_empty() {
+            ui::print_info(&format!(
+                "No pending notifications with confidence '{}' or better",
+                min_confidence
+            ));
+            return Ok(ExitStatus::Success);
+        }
+    }
+
+    if consolidate {
+        let before = notifications.len();
+        notifications = consolidate_notifications(notifications);
+        if notifications.len() < before {
+            ui::print_info(&format!(
+                "Consolidated {} notifications into {}",
+                before,
+                notifications.len()
+            ));
+        }
+    }
+
     let count = notifications.len();
 
     ui::print_info(&format!("Sending {} pending notifications...", count));
@@ -23,7 +115,26 @@ pub fn run_notify(dry_run: bool, digest: bool) -> Result<ExitStatus> {
     let use_digest = digest || config.email.digest_mode;
 
     if dry_run {
-        if use_digest {
+        if webhook_only {
+            ui::print_info(&format!("Would post {} notifications to the webhook", count));
+        } else if format == NotifyFormat::DiscordWebhook {
+            ui::print_info(&format!(
+                "Would post {} notifications to Discord{}",
+                count,
+                if use_digest { " as a digest" } else { "" }
+            ));
+        } else if group_by_date {
+            for (date, group) in group_notifications_by_date(&notifications) {
+                ui::print_info(&format!("Would send digest for {} with:", date));
+                for notif in group {
+                    let subject_name = config.subjects.iter()
+                        .find(|s| s.id == notif.subject_id)
+                        .map(|s| s.name.as_str())
+                        .unwrap_or("Unknown");
+                    ui::print_info(&format!("  - {} ({})", subject_name, notif.event_type));
+                }
+            }
+        } else if use_digest {
             ui::print_info("Would send digest email with:");
             for notif in &notifications {
                 let subject_name = config.subjects.iter()
@@ -41,10 +152,22 @@ pub fn run_notify(dry_run: bool, digest: bool) -> Result<ExitStatus> {
                 ui::print_info(&format!("Would send: {} - {}", subject_name, notif.event_type));
             }
         }
+        if also_webhook {
+            ui::print_info(&format!("Would also post {} notifications to the webhook", count));
+        }
+        if also_telegram {
+            ui::print_info(&format!("Would also send {} notifications to Telegram", count));
+        }
         return Ok(ExitStatus::Success);
     }
 
-    let result = if use_digest {
+    let result = if webhook_only {
+        send_webhooks(&config, &notifications).await
+    } else if let (NotifyFormat::DiscordWebhook, Some(url)) = (format, webhook_url.as_deref()) {
+        discord::send_notifications(url, &notifications, &config.subjects, use_digest).await
+    } else if group_by_date {
+        send_digests_by_date(&config, &notifications)
+    } else if use_digest {
         send_digest(&config, &notifications)
     } else {
         send_individual(&config, &notifications)
@@ -52,8 +175,37 @@ pub fn run_notify(dry_run: bool, digest: bool) -> Result<ExitStatus> {
 
     match result {
         Ok(sent) => {
+            let channel = match format {
+                _ if webhook_only => "webhook",
+                NotifyFormat::Email => "email",
+                NotifyFormat::DiscordWebhook => "discord",
+            };
+            for notif in &notifications {
+                state.record_notification_channel(notif.notification_id, channel);
+            }
+
+            // Email already went out, so a webhook failure only warns instead of requeueing
+            if also_webhook {
+                match send_webhooks(&config, &notifications).await {
+                    Ok(_) => {
+                        for notif in &notifications {
+                            state.record_notification_channel(notif.notification_id, "webhook");
+                        }
+                    }
+                    Err(e) => ui::print_warning(&format!("Failed to post notifications to the webhook: {}", e)),
+                }
+            }
+            if let Some(telegram_config) = config.telegram.as_ref().filter(|_| also_telegram) {
+                for notif in &notifications {
+                    match telegram::send_notification(telegram_config, notif, &config.subjects).await {
+                        Ok(()) => state.record_notification_channel(notif.notification_id, "telegram"),
+                        Err(e) => ui::print_warning(&format!("Failed to send a notification to Telegram: {}", e)),
+                    }
+                }
+            }
+
             // Save state (notifications cleared)
-            state::save_state(&state, &lock)?;
+            state::save_state(&mut state, &lock)?;
             ui::print_success(&format!("Sent {} notifications", sent));
             Ok(ExitStatus::Success)
         }
@@ -62,43 +214,146 @@ pub fn run_notify(dry_run: bool, digest: bool) -> Result<ExitStatus> {
             for notif in notifications {
                 state.add_pending_notification(notif);
             }
-            state::save_state(&state, &lock)?;
+            state::save_state(&mut state, &lock)?;
             ui::print_error(&format!("Failed to send notifications: {}", e));
             Ok(ExitStatus::EmailDeliveryFailed)
         }
     }
 }
 
+/// Post each notification to the configured webhook
+async fn send_webhooks(config: &Config, notifications: &[PendingNotification]) -> Result<usize> {
+    let Some(ref webhook_config) = config.webhook else {
+        return Ok(0);
+    };
+    for notif in notifications {
+        webhook::send_notification(webhook_config, notif, &config.subjects).await?;
+    }
+    Ok(notifications.len())
+}
+
+/// Log notifications that were dropped unsent
+pub(super) fn log_discarded(config: &Config, discarded: &[PendingNotification]) {
+    for notif in discarded {
+        let subject_name = config.subjects.iter()
+            .find(|s| s.id == notif.subject_id)
+            .map(|s| s.name.as_str())
+            .unwrap_or("Unknown");
+        tracing::warn!(
+            "Discarding expired notification for {} ({}), queued {}",
+            subject_name,
+            notif.event_type,
+            notif.created_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        );
+    }
+}
+
+/// Print the email for a subject's newest pending notification, leaving the queue untouched
+fn print_email_body(config: &Config, key: &str, subject_line: bool) -> Result<()> {
+    let subject = config.find_subject(key)
+        .ok_or_else(|| HeadsupError::SubjectNotFound(key.to_string()))?;
+    let state = state::load_state_readonly()?;
+    let notif = state.pending_notifications.iter()
+        .filter(|n| n.subject_id == subject.id)
+        .max_by_key(|n| n.created_at)
+        .ok_or_else(|| HeadsupError::State(format!("No pending notification for '{}'", subject.key)))?;
+
+    let content = individual_email(config, notif);
+    if subject_line {
+        println!("{}", content.subject);
+        println!();
+    }
+    println!("{}", content.body);
+    Ok(())
+}
+
+/// Keep one notification per subject: the highest confidence, the newest on ties
+fn consolidate_notifications(notifications: Vec<PendingNotification>) -> Vec<PendingNotification> {
+    let mut best: Vec<PendingNotification> = Vec::new();
+
+    for notif in notifications {
+        match best.iter_mut().find(|b| b.subject_id == notif.subject_id) {
+            Some(current) => {
+                let (new, old) = (notification_confidence(&notif), notification_confidence(current));
+                if new.is_higher_than(&old) || (new == old && notif.created_at >= current.created_at) {
+                    *current = notif;
+                }
+            }
+            None => best.push(notif),
+        }
+    }
+
+    best
+}
+
+/// Confidence of the response behind a notification
+fn notification_confidence(notif: &PendingNotification) -> Confidence {
+    notif.payload.get("confidence")
+        .and_then(|c| c.as_str())
+        .and_then(|c| c.parse().ok())
+        .or(notif.confidence)
+        .unwrap_or(Confidence::Unknown)
+}
+
 fn send_digest(config: &Config, notifications: &[PendingNotification]) -> Result<usize> {
-    let content = build_digest_email(notifications, &config.subjects);
+    let content = build_digest_email(notifications, &config.subjects, email::ics_reminder(config).as_ref());
     email::send_email(&config.email, &content)?;
     Ok(1)
 }
 
-fn send_individual(config: &Config, notifications: &[PendingNotification]) -> Result<usize> {
+/// Send one digest per local calendar day, oldest day first
+fn send_digests_by_date(config: &Config, notifications: &[PendingNotification]) -> Result<usize> {
     let mut sent = 0;
 
-    for notif in notifications {
-        let subject = config.subjects.iter()
-            .find(|s| s.id == notif.subject_id);
+    for (date, group) in group_notifications_by_date(notifications) {
+        let group: Vec<PendingNotification> = group.into_iter().cloned().collect();
+        let mut content = build_digest_email(&group, &config.subjects, email::ics_reminder(config).as_ref());
+        content.subject = format!("[Headsup] {} Updates from {}", group.len(), date);
+        email::send_email(&config.email, &content)?;
+        sent += 1;
+    }
 
-        let subject_name = subject
-            .map(|s| s.name.as_str())
-            .unwrap_or("Unknown");
+    Ok(sent)
+}
+
+/// Partition notifications by the local date they were created on
+fn group_notifications_by_date(
+    notifications: &[PendingNotification],
+) -> BTreeMap<NaiveDate, Vec<&PendingNotification>> {
+    let mut groups: BTreeMap<NaiveDate, Vec<&PendingNotification>> = BTreeMap::new();
+    for notif in notifications {
+        let date = notif.created_at.with_timezone(&Local).date_naive();
+        groups.entry(date).or_default().push(notif);
+    }
+    groups
+}
 
-        let content = EmailContent {
-            subject: format!("[Headsup] {} - {}", subject_name, notif.event_type),
-            body: format!(
-                "{}\n\nSource: {}\n\nThis is an automated message from Headsup.",
-                notif.summary,
-                notif.source_url.as_deref().unwrap_or("N/A")
-            ),
-            attachments: vec![],
-        };
+fn send_individual(config: &Config, notifications: &[PendingNotification]) -> Result<usize> {
+    let mut sent = 0;
 
-        email::send_email(&config.email, &content)?;
+    for notif in notifications {
+        email::send_email(&config.email, &individual_email(config, notif))?;
         sent += 1;
     }
 
     Ok(sent)
 }
+
+/// Build the email sent for a single notification
+fn individual_email(config: &Config, notif: &PendingNotification) -> EmailContent {
+    let subject_name = config.subjects.iter()
+        .find(|s| s.id == notif.subject_id)
+        .map(|s| s.name.as_str())
+        .unwrap_or("Unknown");
+
+    EmailContent {
+        subject: format!("[Headsup] {} - {}", subject_name, notif.event_type),
+        body: format!(
+            "{}\n\nSource: {}\n\nThis is an automated message from Headsup.",
+            notif.summary,
+            notif.source_url.as_deref().unwrap_or("N/A")
+        ),
+        html_body: None,
+        attachments: vec![],
+    }
+}
@@ -1,16 +1,29 @@
-use crate::cli::StateCommands;
+use crate::cache;
+use crate::cli::{BackupsCommands, StateCommands};
 use crate::config;
 use crate::error::{HeadsupError, Result};
 use crate::state;
 use crate::ui;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
 
 /// Run state subcommands
 pub fn run_state(command: StateCommands) -> Result<()> {
     match command {
         StateCommands::Show => show_state(),
+        StateCommands::Info => show_info(),
         StateCommands::Prune => prune_state(),
+        StateCommands::Compact => compact_state(),
+        StateCommands::Migrate { dry_run } => migrate_state(dry_run),
+        StateCommands::Verify { fix } => verify_state(fix),
         StateCommands::Reset { key } => reset_state(key),
         StateCommands::Path => print_path(),
+        StateCommands::PruneNotifications => prune_notifications(),
+        StateCommands::Backup { output } => backup_state(output),
+        StateCommands::Restore { backup } => restore_state(&backup),
+        StateCommands::Backups { command } => match command {
+            BackupsCommands::List => list_backups(),
+        },
     }
 }
 
@@ -19,6 +32,45 @@ fn show_state() -> Result<()> {
     let content = serde_json::to_string_pretty(&state)
         .map_err(|e| HeadsupError::State(format!("Failed to serialize state: {}", e)))?;
     println!("{}", content);
+
+    // On stderr so stdout stays valid JSON
+    let diagnostics = &state.startup_diagnostics;
+    if !diagnostics.is_empty() {
+        eprintln!();
+        eprintln!("Recent diagnostics:");
+        for diagnostic in &diagnostics[diagnostics.len().saturating_sub(5)..] {
+            eprintln!(
+                "  {} [{}] {}",
+                diagnostic.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                diagnostic.level,
+                diagnostic.message
+            );
+        }
+    }
+    Ok(())
+}
+
+fn show_info() -> Result<()> {
+    let state = state::load_state_readonly()?;
+
+    let schema_version = if state.schema_version.is_empty() {
+        "unknown"
+    } else {
+        &state.schema_version
+    };
+    let format_time = |t: Option<chrono::DateTime<chrono::Utc>>| {
+        t.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| "never".to_string())
+    };
+
+    println!("{:<22} {}", "State format version:", state.version);
+    println!("{:<22} {}", "Written by headsup:", schema_version);
+    println!("{:<22} {}", "Current headsup:", state::SCHEMA_VERSION);
+    println!("{:<22} {}", "Last written:", format_time(state.last_written));
+    println!("{:<22} {}", "Last run:", format_time(state.last_run));
+    println!("{:<22} {}", "Subjects:", state.subjects.len());
+    println!("{:<22} {}", "Pending notifications:", state.pending_notifications.len());
+
     Ok(())
 }
 
@@ -35,7 +87,8 @@ fn prune_state() -> Result<()> {
     if orphans.is_empty() {
         ui::print_info("No orphaned state entries found");
     } else {
-        state::save_state(&state, &lock)?;
+        backup_before_change(&config)?;
+        state::save_state(&mut state, &lock)?;
         ui::print_success(&format!("Pruned {} orphaned state entries", orphans.len()));
         for id in orphans {
             ui::print_info(&format!("  Removed: {}", id));
@@ -45,6 +98,128 @@ fn prune_state() -> Result<()> {
     Ok(())
 }
 
+fn prune_notifications() -> Result<()> {
+    let config = config::load_config()?;
+    let (mut state, lock) = state::load_state()?;
+
+    let discarded = state.discard_expired_notifications(Utc::now(), config.settings.pending_notification_max_age_hours);
+    if discarded.is_empty() {
+        ui::print_info("No expired notifications found");
+        return Ok(());
+    }
+
+    super::notify::log_discarded(&config, &discarded);
+    state::save_state(&mut state, &lock)?;
+    ui::print_success(&format!(
+        "Discarded {} expired notifications, {} still pending",
+        discarded.len(),
+        state.pending_notifications.len()
+    ));
+    Ok(())
+}
+
+fn compact_state() -> Result<()> {
+    let path = config::state_path()?;
+    let (mut state, lock) = state::load_state()?;
+
+    if !path.exists() {
+        ui::print_info("No state file to compact");
+        return Ok(());
+    }
+
+    let before = std::fs::metadata(&path)?.len();
+    state::save_state(&mut state, &lock)?;
+    let after = std::fs::metadata(&path)?.len();
+
+    ui::print_success(&format!(
+        "Compacted state file: {} bytes -> {} bytes",
+        before, after
+    ));
+
+    Ok(())
+}
+
+fn migrate_state(dry_run: bool) -> Result<()> {
+    let path = config::state_path()?;
+    if !path.exists() {
+        ui::print_info("No state file to migrate");
+        return Ok(());
+    }
+
+    let raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    let from = state::version_of(&raw);
+    if from >= state::STATE_VERSION {
+        ui::print_info(&format!("State file is already at version {}", from));
+        return Ok(());
+    }
+
+    if dry_run {
+        let migrated = state::migrate(raw)?;
+        println!("{}", serde_json::to_string_pretty(&migrated)?);
+        ui::print_info(&format!(
+            "Would migrate the state file from version {} to {} (dry run)",
+            from,
+            state::STATE_VERSION
+        ));
+        return Ok(());
+    }
+
+    // load_state backs up the original and migrates it
+    let (mut state, lock) = state::load_state()?;
+    state::save_state(&mut state, &lock)?;
+    ui::print_success(&format!(
+        "Migrated the state file from version {} to {}",
+        from,
+        state::STATE_VERSION
+    ));
+    Ok(())
+}
+
+fn verify_state(fix: bool) -> Result<()> {
+    let path = config::state_path()?;
+    if path.exists() {
+        state::verify_integrity(&path)?;
+        ui::print_success("State file reads back cleanly");
+    }
+
+    let config = config::load_config()?;
+    // Checking never writes, so it neither waits for the lock nor leaves a migration backup
+    let state = state::load_state_readonly()?;
+
+    let mismatched: Vec<_> = config.subjects.iter()
+        .filter_map(|subject| {
+            let found = state.subjects.get(&subject.id)?.subject_type();
+            (found != subject.subject_type).then_some((subject, found))
+        })
+        .collect();
+
+    if mismatched.is_empty() {
+        ui::print_success("All subject state matches the config");
+        return Ok(());
+    }
+
+    for (subject, found) in &mismatched {
+        ui::print_warning(&format!(
+            "'{}' is configured as {} but its state is {}",
+            subject.key, subject.subject_type, found
+        ));
+    }
+
+    if fix {
+        // Re-check under the lock in case a check ran in between
+        let (mut state, lock) = state::load_state()?;
+        state.subjects.retain(|id, entry| {
+            !mismatched.iter().any(|(s, _)| s.id == *id && s.subject_type != entry.subject_type())
+        });
+        state::save_state(&mut state, &lock)?;
+        ui::print_success(&format!("Reset state for {} subjects", mismatched.len()));
+    } else {
+        ui::print_info("Run 'headsup state verify --fix' to reset these entries");
+    }
+
+    Ok(())
+}
+
 fn reset_state(key: Option<String>) -> Result<()> {
     let (mut state, lock) = state::load_state()?;
 
@@ -55,8 +230,13 @@ fn reset_state(key: Option<String>) -> Result<()> {
             let subject = config.find_subject(&key_or_id)
                 .ok_or_else(|| HeadsupError::SubjectNotFound(key_or_id.clone()))?;
 
-            if state.subjects.remove(&subject.id).is_some() {
-                state::save_state(&state, &lock)?;
+            if state.subjects.contains_key(&subject.id) {
+                backup_before_change(&config)?;
+                state.subjects.remove(&subject.id);
+                state::save_state(&mut state, &lock)?;
+                if let Some(ref cache_config) = config.cache {
+                    cache::invalidate(cache_config, subject.id)?;
+                }
                 ui::print_success(&format!("Reset state for '{}'", subject.name));
             } else {
                 ui::print_info(&format!("No state found for '{}'", subject.name));
@@ -72,10 +252,15 @@ fn reset_state(key: Option<String>) -> Result<()> {
                 }
             }
 
+            let config = config::load_config()?;
+            backup_before_change(&config)?;
             let count = state.subjects.len();
             state.subjects.clear();
             state.pending_notifications.clear();
-            state::save_state(&state, &lock)?;
+            state::save_state(&mut state, &lock)?;
+            if let Some(ref cache_config) = config.cache {
+                cache::clear(cache_config)?;
+            }
             ui::print_success(&format!("Reset state for {} subjects", count));
         }
     }
@@ -88,3 +273,82 @@ fn print_path() -> Result<()> {
     println!("{}", path.display());
     Ok(())
 }
+
+/// Back up the state file ahead of a destructive change
+fn backup_before_change(config: &config::Config) -> Result<()> {
+    if let Some(path) = state::create_backup(config.settings.max_state_backups)? {
+        ui::print_info(&format!("Backed up state to {}", path.display()));
+    }
+    Ok(())
+}
+
+fn backup_state(output: Option<PathBuf>) -> Result<()> {
+    let path = config::state_path()?;
+    if !path.exists() {
+        ui::print_info("No state file to back up");
+        return Ok(());
+    }
+
+    let backup_path = match output {
+        Some(output) => {
+            std::fs::copy(&path, &output)?;
+            output
+        }
+        None => {
+            let config = config::load_config()?;
+            match state::create_backup(config.settings.max_state_backups)? {
+                Some(backup_path) => backup_path,
+                None => return Ok(()),
+            }
+        }
+    };
+
+    ui::print_success(&format!("Backed up state to {}", backup_path.display()));
+    Ok(())
+}
+
+fn restore_state(backup: &Path) -> Result<()> {
+    if !backup.exists() {
+        return Err(HeadsupError::State(format!(
+            "Backup not found: {}",
+            backup.display()
+        )));
+    }
+    state::verify_integrity(backup)?;
+
+    if ui::is_interactive() {
+        let confirm = ui::prompt_confirm(
+            &format!("Replace the current state with {}?", backup.display()),
+            false,
+        )?;
+        if !confirm {
+            ui::print_info("Cancelled");
+            return Ok(());
+        }
+    }
+
+    // Keep the state being replaced, unless the config can't be loaded
+    let max_backups = config::load_config().ok().map(|c| c.settings.max_state_backups);
+    if let Some(previous) = state::restore_backup(backup, max_backups)? {
+        ui::print_info(&format!("Backed up state to {}", previous.display()));
+    }
+    ui::print_success(&format!("Restored state from {}", backup.display()));
+    Ok(())
+}
+
+fn list_backups() -> Result<()> {
+    let backups = state::list_backups()?;
+    if backups.is_empty() {
+        ui::print_info(&format!(
+            "No backups in {}",
+            state::backups_dir()?.display()
+        ));
+        return Ok(());
+    }
+
+    for backup in backups {
+        let size = std::fs::metadata(&backup)?.len();
+        println!("{}  ({} bytes)", backup.display(), size);
+    }
+    Ok(())
+}
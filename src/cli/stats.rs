@@ -0,0 +1,73 @@
+use crate::error::Result;
+use crate::state::{self, DailyCheckSummary};
+use crate::ui;
+use chrono::{Days, Local, NaiveDate};
+use std::collections::BTreeMap;
+
+/// Width of the longest bar in the daily chart
+const BAR_WIDTH: usize = 40;
+
+/// Run the stats command
+pub fn run_stats(by_day: bool, last_n_days: u32) -> Result<()> {
+    let state = state::load_state_readonly()?;
+
+    let today = Local::now().date_naive();
+    let first_day = today - Days::new(last_n_days.saturating_sub(1) as u64);
+    let days: Vec<(NaiveDate, &DailyCheckSummary)> = state
+        .check_history
+        .range(first_day..=today)
+        .map(|(date, summary)| (*date, summary))
+        .collect();
+
+    if days.is_empty() {
+        ui::print_info(&format!("No checks recorded in the last {} days", last_n_days));
+        return Ok(());
+    }
+
+    if by_day {
+        print_daily_chart(first_day, today, &days);
+    } else {
+        print_totals(last_n_days, &days);
+    }
+    Ok(())
+}
+
+fn print_totals(last_n_days: u32, days: &[(NaiveDate, &DailyCheckSummary)]) {
+    let mut backends: BTreeMap<&str, u32> = BTreeMap::new();
+    for (_, summary) in days {
+        for (backend, count) in &summary.backends {
+            *backends.entry(backend.as_str()).or_insert(0) += count;
+        }
+    }
+
+    println!("Last {} days ({} with checks)", last_n_days, days.len());
+    println!("  Checks:        {}", days.iter().map(|(_, s)| s.checks).sum::<u32>());
+    println!("  Failures:      {}", days.iter().map(|(_, s)| s.failures).sum::<u32>());
+    println!("  Notifications: {}", days.iter().map(|(_, s)| s.notifications).sum::<u32>());
+    for (backend, count) in backends {
+        println!("  {:<14} {}", format!("{}:", backend), count);
+    }
+}
+
+/// One row per day, with a bar scaled to the busiest day
+fn print_daily_chart(first_day: NaiveDate, today: NaiveDate, days: &[(NaiveDate, &DailyCheckSummary)]) {
+    let by_date: BTreeMap<NaiveDate, &DailyCheckSummary> = days.iter().copied().collect();
+    let max_checks = days.iter().map(|(_, s)| s.checks).max().unwrap_or(0).max(1);
+
+    println!("{:<12} {:>7} {:>7} {:>9}  ACTIVITY", "DATE", "CHECKS", "FAILED", "NOTIFIED");
+    println!("{}", "-".repeat(80));
+
+    for date in first_day.iter_days().take_while(|d| *d <= today) {
+        let empty = DailyCheckSummary::default();
+        let summary = by_date.get(&date).copied().unwrap_or(&empty);
+        let bar_len = (summary.checks as usize * BAR_WIDTH).div_ceil(max_checks as usize);
+        println!(
+            "{:<12} {:>7} {:>7} {:>9}  {}",
+            date,
+            summary.checks,
+            summary.failures,
+            summary.notifications,
+            "#".repeat(bar_len)
+        );
+    }
+}
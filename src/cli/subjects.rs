@@ -1,57 +1,412 @@
-use crate::cli::SubjectsCommands;
-use crate::claude;
-use crate::config::{self, Config, Subject, SubjectType};
+use crate::cli::{GroupsCommands, ListArgs, ListFormat, SubjectsCommands, TableFormat};
+use crate::claude::{self, SubjectIdentificationResponse, SubjectMatch};
+use crate::config::{self, Backend, Category, Config, Subject, SubjectGroup, SubjectType};
 use crate::error::{HeadsupError, Result};
-use crate::state;
+use crate::email::ics::parse_exact_date;
+use crate::feed;
+use crate::graph;
+use crate::ollama;
+use crate::openai;
+use crate::perplexity;
+use crate::state::{self, DatePrecision, State, SubjectState};
+use crate::templates::QuestionTemplate;
 use crate::ui;
+use crate::util::dedup;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 /// Run subjects subcommands
 pub async fn run_subjects(command: SubjectsCommands) -> Result<()> {
     match command {
-        SubjectsCommands::List => list_subjects(),
-        SubjectsCommands::Add => add_subject().await,
+        SubjectsCommands::List(args) => list_subjects(&args),
+        SubjectsCommands::Add {
+            question_template,
+            name,
+            watch_rss,
+            bulk_ai,
+            from_stdin,
+            category_auto,
+            refine_search_terms,
+        } => {
+            if from_stdin {
+                add_subjects_from_stdin(category_auto).await
+            } else if !bulk_ai.is_empty() {
+                add_subjects_bulk(&bulk_ai, category_auto).await
+            } else if let Some(url) = watch_rss {
+                add_subject_from_feed(&url).await
+            } else if let (Some(template), Some(name)) = (question_template, name) {
+                add_subject_from_template(template, &name)
+            } else {
+                add_subject(category_auto, refine_search_terms).await
+            }
+        }
+        SubjectsCommands::Show { key } => show_subject(&key),
+        SubjectsCommands::Clone { source_key, new_key, new_name } => {
+            clone_subject(&source_key, &new_key, new_name)
+        }
         SubjectsCommands::Remove { key } => remove_subject(&key),
+        SubjectsCommands::Deduplicate { dry_run, similarity_threshold } => {
+            deduplicate_subjects(dry_run, similarity_threshold)
+        }
+        SubjectsCommands::Graph { output } => graph_subjects(output),
         SubjectsCommands::Edit { key } => edit_subject(&key),
         SubjectsCommands::Enable { key } => enable_subject(&key),
         SubjectsCommands::Disable { key } => disable_subject(&key),
+        SubjectsCommands::Tag { key, add, remove } => tag_subject(&key, &add, &remove),
+        SubjectsCommands::Archive { key } => set_archived(&key, true),
+        SubjectsCommands::Unarchive { key } => set_archived(&key, false),
+        SubjectsCommands::Groups { command } => run_groups(command),
+        SubjectsCommands::Pause { key, until } => pause_subject(&key, &until),
     }
 }
 
-fn list_subjects() -> Result<()> {
+/// Terminal width needed to show the UUID column on the same line
+const WIDE_TABLE_WIDTH: usize = 126;
+
+fn list_subjects(args: &ListArgs) -> Result<()> {
     let config = config::load_config()?;
     let state = state::load_state_readonly().unwrap_or_default();
 
+    let subjects: Vec<&Subject> = config.subjects.iter()
+        .filter(|s| !args.filter_enabled || s.enabled)
+        .filter(|s| !args.filter_disabled || !s.enabled)
+        .filter(|s| args.tag.as_deref().is_none_or(|t| s.has_tag(t)))
+        .filter(|s| args.all || s.archived == args.archived)
+        .filter(|s| match args.filter_status {
+            Some(status) => matches!(
+                state.subjects.get(&s.id),
+                Some(SubjectState::Release(rs)) if rs.status == status
+            ),
+            None => true,
+        })
+        .collect();
+
+    let subjects = if args.upcoming_only {
+        let today = Local::now().date_naive();
+        let mut upcoming: Vec<(&Subject, Upcoming)> = subjects.into_iter()
+            .filter_map(|s| Some((s, upcoming(state.subjects.get(&s.id), today, args.within_days)?)))
+            .collect();
+        upcoming.sort_by_key(|(_, u)| *u);
+
+        if !args.count_only && !args.json && args.format == ListFormat::Table {
+            print_upcoming(&upcoming, args.no_header);
+            return Ok(());
+        }
+        upcoming.into_iter().map(|(s, _)| s).collect()
+    } else {
+        subjects
+    };
+
+    if args.count_only {
+        println!("{}", subjects.len());
+        return Ok(());
+    }
+
+    if args.format == ListFormat::Toml {
+        return print_subjects_toml(&subjects);
+    }
+
+    if args.json {
+        return print_subjects_json(&subjects, &state);
+    }
+
     if config.subjects.is_empty() {
-        ui::print_info("No subjects configured");
-        ui::print_info("Use 'headsup subjects add' to add a subject");
+        if !args.no_header {
+            ui::print_info("No subjects configured");
+            ui::print_info("Use 'headsup subjects add' to add a subject");
+        }
         return Ok(());
     }
 
-    println!("{:<12} {:<30} {:<10} {:<10} {}", "KEY", "NAME", "TYPE", "STATUS", "LAST CHECKED");
-    println!("{}", "-".repeat(80));
+    if args.table_format == TableFormat::Compact {
+        if !args.no_header {
+            println!("KEY\tNAME");
+        }
+        for subject in subjects {
+            println!("{}\t{}", subject.key, subject.name);
+        }
+        return Ok(());
+    }
+
+    // Piped output has no width, so scripts always get the UUID as a column
+    let id_column = args.show_id && ui::terminal_width().is_none_or(|w| w >= WIDE_TABLE_WIDTH);
+    let id_header = if id_column { format!("{:<36} ", "UUID") } else { String::new() };
+
+    if !args.no_header {
+        if args.show_pending {
+            println!("{:<12} {}{:<30} {:<10} {:<10} {:<8} {:<16} TAGS", "KEY", id_header, "NAME", "TYPE", "STATUS", "PENDING", "LAST CHECKED");
+            println!("{}", "-".repeat(99 + id_header.len()));
+        } else {
+            println!("{:<12} {}{:<30} {:<10} {:<10} {:<16} TAGS", "KEY", id_header, "NAME", "TYPE", "STATUS", "LAST CHECKED");
+            println!("{}", "-".repeat(90 + id_header.len()));
+        }
+    }
 
-    for subject in &config.subjects {
-        let status = if subject.enabled { "enabled" } else { "disabled" };
+    for subject in subjects {
+        // '!' marks subjects whose last check failed
+        let status = format!(
+            "{}{}",
+            if subject.archived {
+                "archived"
+            } else if !subject.enabled {
+                "disabled"
+            } else if subject.is_paused(Utc::now()) {
+                "paused"
+            } else {
+                "enabled"
+            },
+            if subject.last_error.is_some() { " !" } else { "" }
+        );
         let last_checked = state.subjects.get(&subject.id)
             .and_then(|s| s.last_checked())
             .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
             .unwrap_or_else(|| "never".to_string());
 
+        let id_cell = if id_column { format!("{:<36} ", subject.id) } else { String::new() };
+        let tags = subject.tags.join(",");
+
+        if args.show_pending {
+            let pending = state.pending_notifications.iter()
+                .filter(|n| n.subject_id == subject.id)
+                .count();
+            println!(
+                "{:<12} {}{:<30} {:<10} {:<10} {:<8} {:<16} {}",
+                subject.key,
+                id_cell,
+                truncate(&subject.name, 28),
+                subject.subject_type.to_string(),
+                status,
+                pending,
+                last_checked,
+                tags
+            );
+        } else {
+            println!(
+                "{:<12} {}{:<30} {:<10} {:<10} {:<16} {}",
+                subject.key,
+                id_cell,
+                truncate(&subject.name, 28),
+                subject.subject_type.to_string(),
+                status,
+                last_checked,
+                tags
+            );
+        }
+        if args.show_id && !id_column {
+            println!("{:<12} {}", "", subject.id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Why a subject shows up in the upcoming view. Dated entries sort before open questions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Upcoming {
+    Date(NaiveDate),
+    OpenQuestion,
+}
+
+/// Exact release or next occurrence dates within the window, and unresolved questions
+fn upcoming(state: Option<&SubjectState>, today: NaiveDate, within_days: u32) -> Option<Upcoming> {
+    let date = match state? {
+        SubjectState::Release(rs) if rs.release_date_precision == DatePrecision::Exact => {
+            rs.known_release_date.as_deref()
+        }
+        SubjectState::Recurring(rs) => rs.next_occurrence_date.as_deref(),
+        SubjectState::Question(qs) if !qs.is_definitive => return Some(Upcoming::OpenQuestion),
+        _ => None,
+    };
+
+    let date = parse_exact_date(date?)?;
+    let days = (date - today).num_days();
+    (0..=within_days as i64).contains(&days).then_some(Upcoming::Date(date))
+}
+
+fn print_upcoming(upcoming: &[(&Subject, Upcoming)], no_header: bool) {
+    if !no_header {
+        println!("{:<12} {:<30} {:<10} DATE", "KEY", "NAME", "TYPE");
+        println!("{}", "-".repeat(66));
+    }
+
+    for (subject, when) in upcoming {
+        let date = match when {
+            Upcoming::Date(date) => date.format("%Y-%m-%d").to_string(),
+            Upcoming::OpenQuestion => "open".to_string(),
+        };
         println!(
-            "{:<12} {:<30} {:<10} {:<10} {}",
+            "{:<12} {:<30} {:<10} {}",
             subject.key,
             truncate(&subject.name, 28),
-            subject.subject_type,
-            status,
-            last_checked
+            subject.subject_type.to_string(),
+            date
         );
     }
+}
+
+/// Print subjects as `[[subjects]]` blocks, without private notes or run details
+fn print_subjects_toml(subjects: &[&Subject]) -> Result<()> {
+    #[derive(Serialize)]
+    struct SubjectsOnly {
+        subjects: Vec<Subject>,
+    }
+
+    let subjects = subjects
+        .iter()
+        .map(|s| Subject {
+            notes_private: None,
+            last_error: None,
+            ..(*s).clone()
+        })
+        .collect();
+    let content = toml::to_string_pretty(&SubjectsOnly { subjects })
+        .map_err(|e| HeadsupError::Config(format!("Failed to serialize subjects: {}", e)))?;
+    print!("{}", content);
+    Ok(())
+}
+
+/// A subject and a snapshot of its state in `subjects list --json`
+#[derive(Serialize)]
+struct SubjectJson<'a> {
+    id: Uuid,
+    key: &'a str,
+    name: &'a str,
+    #[serde(rename = "type")]
+    subject_type: SubjectType,
+    category: Option<Category>,
+    enabled: bool,
+    paused_until: Option<DateTime<Utc>>,
+    last_checked: Option<DateTime<Utc>>,
+    consecutive_failures: u32,
+    /// Known release date, current answer, or next occurrence
+    current_value: Option<String>,
+}
+
+fn print_subjects_json(subjects: &[&Subject], state: &State) -> Result<()> {
+    let entries: Vec<SubjectJson> = subjects
+        .iter()
+        .map(|s| {
+            let subject_state = state.subjects.get(&s.id);
+            SubjectJson {
+                id: s.id,
+                key: &s.key,
+                name: &s.name,
+                subject_type: s.subject_type,
+                category: s.category,
+                enabled: s.enabled,
+                paused_until: s.paused_until,
+                last_checked: subject_state.and_then(|st| st.last_checked()),
+                consecutive_failures: state.consecutive_failures.get(&s.id).copied().unwrap_or(0),
+                current_value: subject_state.and_then(|st| match st {
+                    SubjectState::Release(rs) => rs.known_release_date.clone(),
+                    SubjectState::Question(qs) => qs.current_answer.clone(),
+                    SubjectState::Recurring(rs) => rs.next_occurrence_date.clone(),
+                }),
+            }
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    Ok(())
+}
+
+fn graph_subjects(output: Option<PathBuf>) -> Result<()> {
+    let config = config::load_config()?;
+    let state = state::load_state_readonly().unwrap_or_default();
+    let dot = graph::generate_dot_graph(&config, &state);
+
+    match output {
+        Some(path) => {
+            fs::write(&path, dot)?;
+            ui::print_success(&format!("Graph written to {}", path.display()));
+        }
+        None => print!("{}", dot),
+    }
+    Ok(())
+}
+
+fn show_subject(key: &str) -> Result<()> {
+    let config = config::load_config()?;
+    let state = state::load_state_readonly().unwrap_or_default();
+
+    let subject = config.find_subject(key)
+        .ok_or_else(|| HeadsupError::SubjectNotFound(key.to_string()))?;
+
+    println!("{}", subject.name);
+    println!("{}", "-".repeat(80));
+    println!("{:<16} {}", "Key:", subject.key);
+    println!("{:<16} {}", "ID:", subject.id);
+    println!("{:<16} {}", "Type:", subject.subject_type);
+    if let Some(category) = subject.category {
+        println!("{:<16} {}", "Category:", category);
+    }
+    if let Some(ref question) = subject.question {
+        println!("{:<16} {}", "Question:", question);
+    }
+    if let Some(ref event_name) = subject.event_name {
+        println!("{:<16} {}", "Event:", event_name);
+    }
+    if let Some(ref rss_feed) = subject.rss_feed {
+        println!("{:<16} {}", "Feed:", rss_feed);
+    }
+    if !subject.search_terms.is_empty() {
+        println!("{:<16} {}", "Search terms:", subject.search_terms.join(", "));
+    }
+    if !subject.search_terms_exclude.is_empty() {
+        println!("{:<16} {}", "Exclude terms:", subject.search_terms_exclude.join(", "));
+    }
+    if !subject.tags.is_empty() {
+        println!("{:<16} {}", "Tags:", subject.tags.join(", "));
+    }
+    if let Some(group) = config.group_of(subject) {
+        println!("{:<16} {}", "Group:", group.name);
+    }
+    if subject.archived {
+        let since = state.subjects.get(&subject.id)
+            .and_then(|s| s.archived_at())
+            .map(|t| format!(" (since {})", t.with_timezone(&Local).format("%Y-%m-%d")))
+            .unwrap_or_default();
+        println!("{:<16} yes{}", "Archived:", since);
+    }
+    if let Some(ref notes) = subject.notes {
+        println!("{:<16} {}", "Notes:", notes);
+    }
+    let expected_answer = match state.subjects.get(&subject.id) {
+        Some(SubjectState::Question(qs)) => qs.expected_answer.as_ref(),
+        _ => None,
+    }
+    .or(subject.initial_expected_answer.as_ref());
+    if let Some(expected) = expected_answer {
+        println!("{:<16} {}", "Expected answer:", expected);
+    }
+    println!("{:<16} {}", "Status:", if subject.enabled { "enabled" } else { "disabled" });
+    if let Some(until) = subject.paused_until.filter(|_| subject.is_paused(Utc::now())) {
+        println!("{:<16} {}", "Paused until:", until.with_timezone(&Local).format("%Y-%m-%d %H:%M"));
+    }
+
+    let last_checked = state.subjects.get(&subject.id)
+        .and_then(|s| s.last_checked())
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    println!("{:<16} {}", "Last checked:", last_checked);
+    if let Some(ref error) = subject.last_error {
+        println!("{:<16} {}", "Last error:", error);
+    }
+
+    println!();
+    println!(
+        "{:<16} {}",
+        "Best source:",
+        subject.last_found_source.as_deref().unwrap_or("none found yet")
+    );
 
     Ok(())
 }
 
-async fn add_subject() -> Result<()> {
+async fn add_subject(category_auto: bool, refine: bool) -> Result<()> {
     if !ui::is_interactive() {
         return Err(HeadsupError::Config(
             "Interactive mode required for adding subjects. Edit config file directly.".to_string(),
@@ -63,9 +418,33 @@ async fn add_subject() -> Result<()> {
     // Get user input
     let query = ui::prompt_text("What would you like to track?")?;
 
-    // Use Claude to identify the subject (without revealing current state)
+    // Direct links are scraped for their page title instead of asking the AI
+    if is_url(&query) {
+        let spinner = ui::Spinner::new("Fetching page...");
+        match scrape_subject_match(query.trim()).await {
+            Ok(matched) => {
+                spinner.finish_and_clear();
+                ui::print_info(&format!("Found: {}", matched.name));
+                let mut subject = subject_from_match(&config, &matched, category_auto)?;
+                if refine {
+                    subject.search_terms = refine_search_terms(&config, subject.search_terms).await?;
+                }
+                subject.validate().map_err(HeadsupError::Config)?;
+                config.subjects.push(subject.clone());
+                config::save_config(&config)?;
+                ui::print_success(&format!("Added '{}' to your headsup", subject.name));
+                return Ok(());
+            }
+            Err(e) => {
+                spinner.finish_with_error(&e.to_string());
+                ui::print_warning("Could not read the page. Trying AI identification instead.");
+            }
+        }
+    }
+
+    // Use the configured backend to identify the subject (without revealing current state)
     let spinner = ui::Spinner::new("Searching...");
-    let identification = match claude::identify_subjects(&config.claude, &query).await {
+    let identification = match identify_subjects(&config, &query).await {
         Ok(result) => {
             spinner.finish_and_clear();
             result
@@ -74,13 +453,13 @@ async fn add_subject() -> Result<()> {
             spinner.finish_with_error(&e.to_string());
             // Fall back to manual entry
             ui::print_warning("Could not identify subject automatically. Please enter details manually.");
-            return add_subject_manual(&mut config).await;
+            return add_subject_manual(&mut config, category_auto).await;
         }
     };
 
     if identification.matches.is_empty() {
         ui::print_info("No matches found. Please enter details manually.");
-        return add_subject_manual(&mut config).await;
+        return add_subject_manual(&mut config, category_auto).await;
     }
 
     // Build options for selection
@@ -92,13 +471,52 @@ async fn add_subject() -> Result<()> {
     let selected = ui::prompt_select("Did you mean:", options.clone())?;
 
     if selected == "Something else..." {
-        return add_subject_manual(&mut config).await;
+        return add_subject_manual(&mut config, category_auto).await;
     }
 
     // Find the selected match
     let selected_idx = options.iter().position(|o| o == &selected).unwrap();
     let matched = &identification.matches[selected_idx];
 
+    let mut subject = subject_from_match(&config, matched, category_auto)?;
+    if refine {
+        subject.search_terms = refine_search_terms(&config, subject.search_terms).await?;
+    }
+
+    // Validate
+    subject.validate().map_err(HeadsupError::Config)?;
+
+    // Add to config
+    config.subjects.push(subject.clone());
+    config::save_config(&config)?;
+
+    ui::print_success(&format!("Added '{}' to your headsup", subject.name));
+
+    Ok(())
+}
+
+fn is_url(query: &str) -> bool {
+    let query = query.trim();
+    query.starts_with("http://") || query.starts_with("https://")
+}
+
+/// Build a match from the title of a web page
+async fn scrape_subject_match(url: &str) -> Result<SubjectMatch> {
+    let title = feed::fetch_page_title(url).await?;
+    Ok(SubjectMatch {
+        name: title.clone(),
+        description: url.to_string(),
+        suggested_type: None,
+        category: None,
+        search_terms: vec![title],
+        notes: Some(format!("Source page: {}", url)),
+        question: None,
+        event_name: None,
+    })
+}
+
+/// Confirm type-specific details for an identified match and build the subject
+fn subject_from_match(config: &Config, matched: &SubjectMatch, category_auto: bool) -> Result<Subject> {
     // Confirm subject type
     let type_options = ui::subject_type_options();
     let suggested_idx = match matched.suggested_type.as_deref() {
@@ -121,13 +539,19 @@ async fn add_subject() -> Result<()> {
             reordered
         },
     )?;
-    let subject_type = ui::parse_subject_type_option(&type_selection);
-
-    // For release type, confirm category
-    let category = if subject_type == SubjectType::Release {
+    let subject_type = ui::parse_subject_type_option(type_selection);
+
+    // For release type, confirm category (--category-auto reuses the category from identification)
+    let auto_category = matched.category.as_deref()
+        .and_then(parse_ai_category)
+        .filter(|_| category_auto);
+    let category = if let (SubjectType::Release, Some(category)) = (subject_type, auto_category) {
+        ui::print_info(&format!("Category: {}", category));
+        Some(category)
+    } else if subject_type == SubjectType::Release {
         let cat_options = ui::category_options();
         let cat_selection = ui::prompt_select("What category is this?", cat_options)?;
-        Some(ui::parse_category_option(&cat_selection))
+        Some(ui::parse_category_option(cat_selection))
     } else {
         None
     };
@@ -159,9 +583,7 @@ async fn add_subject() -> Result<()> {
     // Generate key
     let key = config.generate_unique_key(&matched.name);
 
-    // Create subject
-    let subject = Subject {
-        id: Uuid::new_v4(),
+    Ok(Subject {
         key,
         name: matched.name.clone(),
         subject_type,
@@ -170,35 +592,140 @@ async fn add_subject() -> Result<()> {
         event_name,
         search_terms: matched.search_terms.clone(),
         notes: matched.notes.clone(),
-        enabled: true,
+        ..Default::default()
+    })
+}
+
+/// Let the user test suggested search terms against the AI and edit them until they fit
+async fn refine_search_terms(config: &Config, mut search_terms: Vec<String>) -> Result<Vec<String>> {
+    loop {
+        if search_terms.is_empty() {
+            ui::print_info("No search terms; the AI will derive queries from the name");
+            return Ok(search_terms);
+        }
+
+        ui::print_info(&format!("Search terms: {}", search_terms.join(", ")));
+        let spinner = ui::Spinner::new("Testing search terms...");
+        match claude::verify_search_terms(&config.claude, &search_terms).await {
+            Ok(summary) => {
+                spinner.finish_and_clear();
+                ui::print_info(&format!("Found: {}", summary));
+            }
+            Err(e) => spinner.finish_with_error(&e.to_string()),
+        }
+
+        let answer = ui::prompt_text_with_default("Use these terms? [Y/n/e to edit]", "Y")?;
+        match answer.trim().to_lowercase().as_str() {
+            "" | "y" | "yes" => return Ok(search_terms),
+            "n" | "no" => {
+                ui::print_info("Dropping the suggested terms; the AI will derive queries from the name");
+                return Ok(Vec::new());
+            }
+            "e" | "edit" => {
+                let input = ui::prompt_text_with_default("Search terms (comma-separated):", &search_terms.join(", "))?;
+                search_terms = parse_search_terms(&input);
+            }
+            _ => ui::print_warning("Please answer y, n or e"),
+        }
+    }
+}
+
+/// Split comma-separated search terms, dropping empty ones
+fn parse_search_terms(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Identify several subjects in one AI call and confirm each match in turn
+async fn add_subjects_bulk(queries: &[String], category_auto: bool) -> Result<()> {
+    if !ui::is_interactive() {
+        return Err(HeadsupError::Config(
+            "Interactive mode required for adding subjects. Edit config file directly.".to_string(),
+        ));
+    }
+
+    let mut config = config::load_config()?;
+
+    let spinner = ui::Spinner::new(&format!("Searching for {} subjects...", queries.len()));
+    let identification = match identify_subjects(&config, &claude::combine_identification_queries(queries)).await {
+        Ok(result) => {
+            spinner.finish_and_clear();
+            result
+        }
+        Err(e) => {
+            spinner.finish_with_error(&e.to_string());
+            return Err(e);
+        }
     };
 
-    // Validate
-    subject.validate().map_err(|e| HeadsupError::Config(e))?;
+    if identification.matches.is_empty() {
+        ui::print_info("No matches found. Use 'headsup subjects add' to add subjects one at a time.");
+        return Ok(());
+    }
 
-    // Add to config
-    config.subjects.push(subject.clone());
-    config::save_config(&config)?;
+    let mut added = 0;
+    let mut confirm_each = || -> Result<()> {
+        for matched in &identification.matches {
+            ui::print_blank();
+            ui::print_info(&format!("{}\n  {}", matched.name, matched.description));
+            if !ui::prompt_confirm(&format!("Add '{}'?", matched.name), true)? {
+                continue;
+            }
 
-    ui::print_success(&format!("Added '{}' to your headsup", subject.name));
+            let subject = subject_from_match(&config, matched, category_auto)?;
+            subject.validate().map_err(HeadsupError::Config)?;
+            ui::print_success(&format!("Added '{}' to your headsup", subject.name));
+            config.subjects.push(subject);
+            added += 1;
+        }
+        Ok(())
+    };
+    // Subjects confirmed before an error (or a cancelled prompt) are still saved
+    let result = confirm_each();
 
-    Ok(())
+    if added > 0 {
+        config::save_config(&config)?;
+    }
+    ui::print_info(&format!("Added {} of {} matches", added, identification.matches.len()));
+
+    result
 }
 
-async fn add_subject_manual(config: &mut Config) -> Result<()> {
+async fn add_subject_manual(config: &mut Config, category_auto: bool) -> Result<()> {
     // Get name
     let name = ui::prompt_text("Subject name:")?;
 
     // Get type
     let type_options = ui::subject_type_options();
     let type_selection = ui::prompt_select("What type of tracking?", type_options)?;
-    let subject_type = ui::parse_subject_type_option(&type_selection);
+    let subject_type = ui::parse_subject_type_option(type_selection);
 
     // Type-specific fields
-    let category = if subject_type == SubjectType::Release {
+    let auto_category = if category_auto && subject_type == SubjectType::Release {
+        let spinner = ui::Spinner::new("Finding category...");
+        match identify_category(config, &name).await {
+            Ok(category) => {
+                spinner.finish_and_clear();
+                ui::print_info(&format!("Category: {}", category));
+                Some(category)
+            }
+            Err(e) => {
+                spinner.finish_with_error(&e.to_string());
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let category = if auto_category.is_some() {
+        auto_category
+    } else if subject_type == SubjectType::Release {
         let cat_options = ui::category_options();
         let cat_selection = ui::prompt_select("Category:", cat_options)?;
-        Some(ui::parse_category_option(&cat_selection))
+        Some(ui::parse_category_option(cat_selection))
     } else {
         None
     };
@@ -217,15 +744,7 @@ async fn add_subject_manual(config: &mut Config) -> Result<()> {
 
     // Get search terms (optional - AI can determine queries from context)
     let search_terms_input = ui::prompt_text("Search terms (comma-separated, or press Enter to let AI decide):")?;
-    let search_terms: Vec<String> = if search_terms_input.trim().is_empty() {
-        Vec::new()
-    } else {
-        search_terms_input
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect()
-    };
+    let search_terms = parse_search_terms(&search_terms_input);
 
     // Optional notes
     let notes = ui::prompt_text_with_default("Notes (optional):", "")?;
@@ -236,7 +755,6 @@ async fn add_subject_manual(config: &mut Config) -> Result<()> {
 
     // Create subject
     let subject = Subject {
-        id: Uuid::new_v4(),
         key,
         name: name.clone(),
         subject_type,
@@ -245,11 +763,11 @@ async fn add_subject_manual(config: &mut Config) -> Result<()> {
         event_name,
         search_terms,
         notes,
-        enabled: true,
+        ..Default::default()
     };
 
     // Validate
-    subject.validate().map_err(|e| HeadsupError::Config(e))?;
+    subject.validate().map_err(HeadsupError::Config)?;
 
     // Add to config
     config.subjects.push(subject);
@@ -260,6 +778,195 @@ async fn add_subject_manual(config: &mut Config) -> Result<()> {
     Ok(())
 }
 
+/// Add subjects from JSON on stdin; each array element is validated on its own
+async fn add_subjects_from_stdin(category_auto: bool) -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let items = match serde_json::from_str(&input)? {
+        serde_json::Value::Array(items) => items,
+        item => vec![item],
+    };
+
+    let mut config = config::load_config()?;
+    let total = items.len();
+    let mut failed = 0;
+
+    for (index, item) in items.into_iter().enumerate() {
+        let label = item.get("name")
+            .and_then(|n| n.as_str())
+            .map(|n| format!("'{}'", n))
+            .unwrap_or_else(|| format!("#{}", index + 1));
+
+        match subject_from_json(&config, item, category_auto).await {
+            Ok(subject) => {
+                ui::print_success(&format!("Added {} as '{}'", label, subject.key));
+                config.subjects.push(subject);
+            }
+            Err(e) => {
+                ui::print_error(&format!("Skipped {}: {}", label, e));
+                failed += 1;
+            }
+        }
+    }
+
+    if failed < total {
+        config::save_config(&config)?;
+    }
+    if failed > 0 {
+        return Err(HeadsupError::Config(format!("{} of {} subjects could not be added", failed, total)));
+    }
+    Ok(())
+}
+
+async fn subject_from_json(config: &Config, item: serde_json::Value, category_auto: bool) -> Result<Subject> {
+    let mut subject: Subject = serde_json::from_value(item)?;
+
+    if subject.key.is_empty() {
+        subject.key = config.generate_unique_key(&subject.name);
+    } else if config.find_subject(&subject.key).is_some() {
+        return Err(HeadsupError::SubjectKeyExists(subject.key));
+    }
+    if config.subjects.iter().any(|s| s.id == subject.id) {
+        subject.id = Uuid::new_v4();
+    }
+    if category_auto && subject.subject_type == SubjectType::Release && subject.category.is_none() {
+        subject.category = Some(identify_category(config, &subject.name).await?);
+    }
+
+    subject.validate().map_err(HeadsupError::Config)?;
+    Ok(subject)
+}
+
+/// Identify subjects based on user input with the configured backend
+async fn identify_subjects(config: &Config, user_input: &str) -> Result<SubjectIdentificationResponse> {
+    let prompt = claude::build_subject_identification_prompt(user_input);
+    let raw = match config.backend {
+        Backend::Claude => claude::execute_claude(&config.claude, &prompt).await?,
+        Backend::Perplexity => perplexity::execute_perplexity(&config.perplexity, &prompt).await?,
+        Backend::OpenAi => openai::execute_openai(&config.openai, &prompt).await?,
+        Backend::Ollama => ollama::execute_ollama(&config.ollama, &prompt).await?,
+    };
+    claude::parse_subject_identification_response(&raw)
+}
+
+/// Ask the AI for a subject's category, taken from the first identification match
+async fn identify_category(config: &Config, name: &str) -> Result<Category> {
+    let identification = identify_subjects(config, name).await?;
+    identification.matches.first()
+        .and_then(|m| m.category.as_deref())
+        .and_then(parse_ai_category)
+        .ok_or_else(|| HeadsupError::Claude(format!("Could not determine a category for '{}'", name)))
+}
+
+/// Parse a category as named in AI responses (game, tv_show, ...)
+fn parse_ai_category(value: &str) -> Option<Category> {
+    serde_json::from_value(serde_json::Value::String(value.trim().to_lowercase())).ok()
+}
+
+fn add_subject_from_template(template: QuestionTemplate, name: &str) -> Result<()> {
+    let mut config = config::load_config()?;
+
+    let question = template.fill(name);
+    let key = config.generate_unique_key(name);
+
+    let subject = Subject {
+        key,
+        name: name.to_string(),
+        subject_type: SubjectType::Question,
+        question: Some(question.clone()),
+        ..Default::default()
+    };
+
+    // Validate
+    subject.validate().map_err(HeadsupError::Config)?;
+
+    // Add to config
+    config.subjects.push(subject);
+    config::save_config(&config)?;
+
+    ui::print_success(&format!("Added '{}' to your headsup", name));
+    ui::print_info(&format!("Tracking question: {}", question));
+
+    Ok(())
+}
+
+async fn add_subject_from_feed(url: &str) -> Result<()> {
+    let mut config = config::load_config()?;
+
+    let spinner = ui::Spinner::new("Fetching feed...");
+    let name = match feed::fetch_feed_title(url).await {
+        Ok(title) => {
+            spinner.finish_and_clear();
+            title
+        }
+        Err(e) => {
+            spinner.finish_with_error(&e.to_string());
+            return Err(e);
+        }
+    };
+
+    let key = config.generate_unique_key(&name);
+
+    let subject = Subject {
+        key,
+        name: name.clone(),
+        subject_type: SubjectType::Recurring,
+        event_name: Some("New release".to_string()),
+        rss_feed: Some(url.to_string()),
+        ..Default::default()
+    };
+
+    // Validate
+    subject.validate().map_err(HeadsupError::Config)?;
+
+    // Add to config
+    config.subjects.push(subject);
+    config::save_config(&config)?;
+
+    ui::print_success(&format!("Added '{}' to your headsup", name));
+    ui::print_info(&format!("Watching feed: {}", url));
+
+    Ok(())
+}
+
+fn clone_subject(source_key: &str, new_key: &str, new_name: Option<String>) -> Result<()> {
+    let mut config = config::load_config()?;
+    let subject = cloned_subject(&config, source_key, new_key, new_name)?;
+
+    let (name, key) = (subject.name.clone(), subject.key.clone());
+    config.subjects.push(subject);
+    config::save_config(&config)?;
+
+    ui::print_success(&format!("Created '{}' with key '{}'", name, key));
+    ui::print_info("Its state starts fresh; the next check treats it as a new subject");
+    Ok(())
+}
+
+/// A copy of a subject under a new key; a blank key is generated from the new name
+fn cloned_subject(config: &Config, source_key: &str, new_key: &str, new_name: Option<String>) -> Result<Subject> {
+    let source = config.find_subject(source_key)
+        .ok_or_else(|| HeadsupError::SubjectNotFound(source_key.to_string()))?;
+    let name = new_name.unwrap_or_else(|| format!("Copy of {}", source.name));
+    let key = match new_key.trim() {
+        "" => config.generate_unique_key(&name),
+        key if config.key_exists(key) => return Err(HeadsupError::SubjectKeyExists(key.to_string())),
+        key => key.to_string(),
+    };
+
+    let subject = Subject {
+        id: Uuid::new_v4(),
+        key,
+        name,
+        // Results of the source's checks do not carry over
+        last_found_source: None,
+        last_error: None,
+        archived: false,
+        ..source.clone()
+    };
+    subject.validate().map_err(HeadsupError::Config)?;
+    Ok(subject)
+}
+
 fn remove_subject(key: &str) -> Result<()> {
     let mut config = config::load_config()?;
 
@@ -275,6 +982,78 @@ fn remove_subject(key: &str) -> Result<()> {
     Ok(())
 }
 
+fn deduplicate_subjects(dry_run: bool, threshold: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(HeadsupError::Config(
+            "Similarity threshold must be between 0.0 and 1.0".to_string(),
+        ));
+    }
+
+    let mut config = config::load_config()?;
+    let pairs = dedup::find_duplicates(&config.subjects, threshold);
+
+    if pairs.is_empty() {
+        ui::print_info("No duplicate subjects found");
+        return Ok(());
+    }
+
+    let name_of = |config: &Config, id| {
+        config.subjects.iter()
+            .find(|s| s.id == id)
+            .map(|s| format!("{} ({})", s.name, s.key))
+            .unwrap_or_default()
+    };
+
+    let mut merged_pairs = Vec::new();
+    for pair in &pairs {
+        // Either side may already have been merged away by an earlier pair
+        if !config.subjects.iter().any(|s| s.id == pair.keep)
+            || !config.subjects.iter().any(|s| s.id == pair.duplicate)
+        {
+            continue;
+        }
+
+        ui::print_info(&format!(
+            "{} and {} ({:.0}% similar search terms)",
+            name_of(&config, pair.keep),
+            name_of(&config, pair.duplicate),
+            pair.similarity * 100.0
+        ));
+
+        if dry_run || !ui::is_interactive() {
+            continue;
+        }
+        let prompt = format!("Merge {} into {}?", name_of(&config, pair.duplicate), name_of(&config, pair.keep));
+        if !ui::prompt_confirm(&prompt, false)? {
+            continue;
+        }
+
+        let idx = config.subjects.iter().position(|s| s.id == pair.duplicate).unwrap();
+        let duplicate = config.subjects.remove(idx);
+        if let Some(keep) = config.subjects.iter_mut().find(|s| s.id == pair.keep) {
+            dedup::merge_subject(keep, &duplicate);
+            let keep_key = keep.key.clone();
+            config.replace_subject_references(&duplicate.key, &keep_key);
+        }
+        merged_pairs.push((pair.keep, pair.duplicate));
+    }
+
+    let merged = merged_pairs.len();
+    if merged > 0 {
+        config::save_config(&config)?;
+        let (mut state, lock) = state::load_state()?;
+        for &(keep, duplicate) in &merged_pairs {
+            state.merge_subject(keep, duplicate);
+        }
+        state::save_state(&mut state, &lock)?;
+        ui::print_success(&format!("Merged {} duplicate subjects", merged));
+    } else if dry_run {
+        ui::print_info("Dry run: no subjects merged");
+    }
+
+    Ok(())
+}
+
 fn edit_subject(key: &str) -> Result<()> {
     if !ui::is_interactive() {
         return Err(HeadsupError::Config(
@@ -329,7 +1108,7 @@ fn edit_subject(key: &str) -> Result<()> {
     subject.notes = if new_notes.is_empty() { None } else { Some(new_notes) };
 
     // Validate
-    subject.validate().map_err(|e| HeadsupError::Config(e))?;
+    subject.validate().map_err(HeadsupError::Config)?;
 
     config::save_config(&config)?;
     ui::print_success("Subject updated");
@@ -373,6 +1152,194 @@ fn disable_subject(key: &str) -> Result<()> {
     Ok(())
 }
 
+fn tag_subject(key: &str, add: &[String], remove: &[String]) -> Result<()> {
+    if add.is_empty() && remove.is_empty() {
+        return Err(HeadsupError::Config("Pass --add or --remove with at least one tag".to_string()));
+    }
+    if let Some(tag) = add.iter().find(|t| !Subject::is_valid_tag(t)) {
+        return Err(HeadsupError::ConfigInvalid(format!(
+            "Tag '{}' must be 1-32 lowercase letters, numbers, and hyphens",
+            tag
+        )));
+    }
+
+    let mut config = config::load_config()?;
+    let subject = config.find_subject_mut(key)
+        .ok_or_else(|| HeadsupError::SubjectNotFound(key.to_string()))?;
+
+    subject.tags.retain(|t| !remove.contains(t));
+    for tag in add {
+        if !subject.has_tag(tag) {
+            subject.tags.push(tag.clone());
+        }
+    }
+    let name = subject.name.clone();
+    let tags = subject.tags.join(", ");
+    config::save_config(&config)?;
+
+    if tags.is_empty() {
+        ui::print_success(&format!("'{}' has no tags", name));
+    } else {
+        ui::print_success(&format!("Tags of '{}': {}", name, tags));
+    }
+    Ok(())
+}
+
+fn set_archived(key: &str, archived: bool) -> Result<()> {
+    let mut config = config::load_config()?;
+    let subject = config.find_subject_mut(key)
+        .ok_or_else(|| HeadsupError::SubjectNotFound(key.to_string()))?;
+    let (id, name) = (subject.id, subject.name.clone());
+
+    if subject.archived == archived {
+        let current = if archived { "archived" } else { "not archived" };
+        ui::print_info(&format!("'{}' is already {}", name, current));
+        return Ok(());
+    }
+    subject.archived = archived;
+    config::save_config(&config)?;
+
+    let (mut state, lock) = state::load_state()?;
+    if let Some(subject_state) = state.subjects.get_mut(&id) {
+        subject_state.set_archived_at(archived.then(Utc::now));
+        state::save_state(&mut state, &lock)?;
+    }
+
+    if archived {
+        ui::print_success(&format!("Archived '{}'", name));
+    } else {
+        ui::print_success(&format!("Unarchived '{}'", name));
+    }
+    Ok(())
+}
+
+fn run_groups(command: GroupsCommands) -> Result<()> {
+    match command {
+        GroupsCommands::List => list_groups(),
+        GroupsCommands::Create { name, check_interval_hours } => create_group(&name, check_interval_hours),
+        GroupsCommands::Delete { name } => delete_group(&name),
+        GroupsCommands::AddSubject { group, subject } => add_subject_to_group(&group, &subject),
+    }
+}
+
+fn list_groups() -> Result<()> {
+    let config = config::load_config()?;
+    if config.groups.is_empty() {
+        ui::print_info("No groups configured");
+        ui::print_info("Use 'headsup subjects groups create' to add one");
+        return Ok(());
+    }
+
+    println!("{:<16} {:<10} {:<10} SUBJECTS", "NAME", "INTERVAL", "CRITERIA");
+    println!("{}", "-".repeat(60));
+    for group in &config.groups {
+        let members: Vec<&str> = config.subjects.iter()
+            .filter(|s| config.group_of(s).is_some_and(|g| g.name == group.name))
+            .map(|s| s.key.as_str())
+            .collect();
+        println!(
+            "{:<16} {:<10} {:<10} {}",
+            truncate(&group.name, 16),
+            group.check_interval_hours.map(|h| format!("{}h", h)).unwrap_or_else(|| "-".to_string()),
+            if group.notification_criteria.is_some() { "yes" } else { "-" },
+            members.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn create_group(name: &str, check_interval_hours: Option<u32>) -> Result<()> {
+    let mut config = config::load_config()?;
+    if name.is_empty() {
+        return Err(HeadsupError::ConfigInvalid("Group names must not be empty".to_string()));
+    }
+    if config.find_group(name).is_some() {
+        return Err(HeadsupError::Config(format!("Group '{}' already exists", name)));
+    }
+
+    config.groups.push(SubjectGroup {
+        name: name.to_string(),
+        check_interval_hours,
+        notification_criteria: None,
+        subjects: Vec::new(),
+    });
+    config::save_config(&config)?;
+    ui::print_success(&format!("Created group '{}'", name));
+    Ok(())
+}
+
+fn delete_group(name: &str) -> Result<()> {
+    let mut config = config::load_config()?;
+    let before = config.groups.len();
+    config.groups.retain(|g| g.name != name);
+    if config.groups.len() == before {
+        return Err(HeadsupError::Config(format!("No group named '{}'", name)));
+    }
+
+    for subject in &mut config.subjects {
+        if subject.group.as_deref() == Some(name) {
+            subject.group = None;
+        }
+    }
+    config::save_config(&config)?;
+    ui::print_success(&format!("Deleted group '{}'", name));
+    Ok(())
+}
+
+fn add_subject_to_group(group: &str, key: &str) -> Result<()> {
+    let mut config = config::load_config()?;
+    let subject = config.find_subject(key)
+        .ok_or_else(|| HeadsupError::SubjectNotFound(key.to_string()))?;
+    let (subject_key, subject_name) = (subject.key.clone(), subject.name.clone());
+    if let Some(other) = config.group_of(subject).filter(|g| g.name != group) {
+        return Err(HeadsupError::Config(format!(
+            "'{}' already belongs to group '{}'",
+            subject_name, other.name
+        )));
+    }
+
+    let target = config.groups.iter_mut().find(|g| g.name == group)
+        .ok_or_else(|| HeadsupError::Config(format!("No group named '{}'", group)))?;
+    if target.subjects.iter().any(|k| k.eq_ignore_ascii_case(&subject_key)) {
+        ui::print_info(&format!("'{}' is already in group '{}'", subject_name, group));
+        return Ok(());
+    }
+    target.subjects.push(subject_key);
+    config::save_config(&config)?;
+    ui::print_success(&format!("Added '{}' to group '{}'", subject_name, group));
+    Ok(())
+}
+
+fn pause_subject(key: &str, until: &str) -> Result<()> {
+    let until = parse_pause_until(until)?;
+    let mut config = config::load_config()?;
+
+    let subject = config.find_subject_mut(key)
+        .ok_or_else(|| HeadsupError::SubjectNotFound(key.to_string()))?;
+    subject.paused_until = Some(until);
+    let name = subject.name.clone();
+    config::save_config(&config)?;
+
+    ui::print_success(&format!(
+        "Paused '{}' until {}",
+        name,
+        until.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    ));
+    Ok(())
+}
+
+/// Parse a date (local midnight) or an RFC 3339 timestamp
+fn parse_pause_until(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| HeadsupError::Config(format!("Invalid date '{}' (expected YYYY-MM-DD)", value)))
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -380,3 +1347,32 @@ fn truncate(s: &str, max_len: usize) -> String {
         format!("{}...", &s[..max_len - 3])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloned_subject() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.subjects.push(Subject {
+            key: "gta6".to_string(),
+            name: "GTA 6".to_string(),
+            category: Some(Category::Game),
+            last_error: Some("timeout".to_string()),
+            ..Default::default()
+        });
+
+        let copy = cloned_subject(&config, "gta6", " gta6-pc ", Some("GTA 6 (PC)".to_string())).unwrap();
+        assert_eq!(copy.key, "gta6-pc");
+        assert!(copy.last_error.is_none());
+        assert_ne!(copy.id, config.subjects[0].id);
+
+        // A blank key falls back to one generated from the name
+        assert_eq!(cloned_subject(&config, "gta6", "", None).unwrap().key, "copy-of-gta-6");
+        assert_eq!(cloned_subject(&config, "gta6", "   ", Some("GTA 6 PC".to_string())).unwrap().key, "gta-6-pc");
+
+        assert!(matches!(cloned_subject(&config, "gta6", "GTA6", None), Err(HeadsupError::SubjectKeyExists(_))));
+        assert!(matches!(cloned_subject(&config, "missing", "x", None), Err(HeadsupError::SubjectNotFound(_))));
+    }
+}
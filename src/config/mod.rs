@@ -1,12 +1,26 @@
+mod password;
+mod schema;
 mod types;
 
+pub use password::{
+    get_smtp_password, keychain_set, on_path, pass_entry, pass_insert, secret_service_store, KEYCHAIN_PREFIX,
+    PASS_PREFIX, SECRET_SERVICE_PREFIX,
+};
+pub use schema::config_schema;
 pub use types::*;
 
 use crate::error::{HeadsupError, Result};
+use crate::state;
+use chrono::NaiveDate;
 use directories::ProjectDirs;
 use std::fs;
 use std::path::PathBuf;
-use std::process::Command;
+use std::time::Duration;
+use toml_edit::DocumentMut;
+use uuid::Uuid;
+
+/// How long to wait for another process writing the config file
+const CONFIG_LOCK_TIMEOUT_SECS: u64 = 5;
 
 /// Get the XDG-compliant config directory
 pub fn config_dir() -> Result<PathBuf> {
@@ -22,6 +36,13 @@ pub fn data_dir() -> Result<PathBuf> {
         .ok_or_else(|| HeadsupError::Config("Could not determine data directory".to_string()))
 }
 
+/// Get the XDG-compliant cache directory
+pub fn cache_dir() -> Result<PathBuf> {
+    ProjectDirs::from("", "", "headsup")
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .ok_or_else(|| HeadsupError::Config("Could not determine cache directory".to_string()))
+}
+
 /// Get the config file path
 pub fn config_path() -> Result<PathBuf> {
     Ok(config_dir()?.join("config.toml"))
@@ -45,7 +66,8 @@ pub fn load_config() -> Result<Config> {
     }
 
     let content = fs::read_to_string(&path)?;
-    let config: Config = toml::from_str(&content)?;
+    let mut config: Config = toml::from_str(&content)?;
+    config.migrate_archived_subjects();
     Ok(config)
 }
 
@@ -56,10 +78,21 @@ pub fn load_config_from(path: &PathBuf) -> Result<Config> {
     }
 
     let content = fs::read_to_string(path)?;
-    let config: Config = toml::from_str(&content)?;
+    let mut config: Config = toml::from_str(&content)?;
+    config.migrate_archived_subjects();
     Ok(config)
 }
 
+/// Archive subjects whose expiry date is before `today` in the config file
+pub fn archive_expired_subjects(today: NaiveDate) -> Result<Vec<Subject>> {
+    // Reload from disk so per-run overrides are not persisted
+    let mut config = load_config()?;
+    let expired = config.archive_expired(today);
+    let patches: Vec<(Uuid, SubjectPatch)> = expired.iter().map(|s| (s.id, SubjectPatch::Archived(true))).collect();
+    patch_subjects(&patches)?;
+    Ok(expired)
+}
+
 /// Save config to file
 pub fn save_config(config: &Config) -> Result<()> {
     let path = config_path()?;
@@ -70,52 +103,102 @@ pub fn save_config(config: &Config) -> Result<()> {
     }
 
     let content = toml::to_string_pretty(config)?;
-    fs::write(&path, content)?;
+    let _lock = lock_config(&path)?;
+    state::write_atomic(&path, &content)
+}
+
+/// Hold the config lock so concurrent writers cannot interleave
+fn lock_config(path: &std::path::Path) -> Result<state::FileLock> {
+    state::FileLock::acquire(path, Duration::from_secs(CONFIG_LOCK_TIMEOUT_SECS)).map_err(|e| match e {
+        HeadsupError::StateLocked => HeadsupError::ConfigLocked,
+        e => e,
+    })
+}
+
+/// A change to one field of a subject that unattended commands record in the config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubjectPatch {
+    LastFoundSource(String),
+    /// Set or clear the reason the last check failed
+    LastError(Option<String>),
+    Archived(bool),
+}
+
+/// Apply field changes to subjects in the config file, keeping its comments and formatting
+pub fn patch_subjects(patches: &[(Uuid, SubjectPatch)]) -> Result<()> {
+    if patches.is_empty() {
+        return Ok(());
+    }
+
+    let path = config_path()?;
+    let _lock = lock_config(&path)?;
+    let content = fs::read_to_string(&path)?;
+    let patched = patch_subjects_in(&content, patches)?;
+    if patched != content {
+        state::write_atomic(&path, &patched)?;
+    }
     Ok(())
 }
 
-/// Execute the password command and return the password
-pub fn get_smtp_password(command: &str) -> Result<String> {
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", command])
-            .output()
-    } else {
-        Command::new("sh")
-            .args(["-c", command])
-            .output()
+fn patch_subjects_in(content: &str, patches: &[(Uuid, SubjectPatch)]) -> Result<String> {
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e| HeadsupError::ConfigInvalid(format!("Could not parse the config file: {}", e)))?;
+    let Some(subjects) = doc.get_mut("subjects").and_then(|item| item.as_array_of_tables_mut()) else {
+        return Ok(content.to_string());
     };
 
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let password = String::from_utf8_lossy(&output.stdout)
-                    .trim()
-                    .to_string();
-                if password.is_empty() {
-                    Err(HeadsupError::PasswordCommand("Password command returned empty output".to_string()))
-                } else {
-                    Ok(password)
+    for table in subjects.iter_mut() {
+        let Some(id) = table.get("id").and_then(|v| v.as_str()).and_then(|v| Uuid::parse_str(v).ok()) else {
+            continue;
+        };
+        for (_, patch) in patches.iter().filter(|(patch_id, _)| *patch_id == id) {
+            match patch {
+                SubjectPatch::LastFoundSource(url) => {
+                    table.insert("last_found_source", toml_edit::value(url.as_str()));
+                }
+                SubjectPatch::LastError(Some(reason)) => {
+                    table.insert("last_error", toml_edit::value(reason.as_str()));
+                }
+                SubjectPatch::LastError(None) => {
+                    table.remove("last_error");
+                }
+                SubjectPatch::Archived(archived) => {
+                    table.insert("archived", toml_edit::value(*archived));
                 }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(HeadsupError::PasswordCommand(format!(
-                    "Password command failed: {}",
-                    stderr.trim()
-                )))
             }
         }
-        Err(e) => Err(HeadsupError::PasswordCommand(format!(
-            "Failed to execute password command: {}",
-            e
-        ))),
     }
+    Ok(doc.to_string())
 }
 
 /// Redact sensitive information from config for display
 pub fn redact_config(config: &Config) -> Config {
     let mut redacted = config.clone();
     redacted.email.smtp_password_command = "[REDACTED]".to_string();
+    if let Some(ref mut telegram) = redacted.telegram {
+        telegram.bot_token_command = "[REDACTED]".to_string();
+    }
+    // Webhook headers usually carry tokens
+    if let Some(ref mut webhook) = redacted.webhook {
+        for value in webhook.headers.values_mut() {
+            *value = "[REDACTED]".to_string();
+        }
+        if webhook.secret_command.is_some() {
+            webhook.secret_command = Some("[REDACTED]".to_string());
+        }
+    }
+    if redacted.settings.prometheus_auth_command.is_some() {
+        redacted.settings.prometheus_auth_command = Some("[REDACTED]".to_string());
+    }
+    if let Some(ref mut cloud_sync) = redacted.settings.cloud_sync {
+        if cloud_sync.aws_credentials_command.is_some() {
+            cloud_sync.aws_credentials_command = Some("[REDACTED]".to_string());
+        }
+    }
+    for subject in &mut redacted.subjects {
+        subject.notes_private = None;
+    }
     redacted
 }
 
@@ -130,6 +213,17 @@ mod tests {
         assert_eq!(Subject::generate_key("Rust 2024 Edition"), "rust-2024-edition");
     }
 
+    #[test]
+    fn test_is_valid_tag() {
+        assert!(Subject::is_valid_tag("games"));
+        assert!(Subject::is_valid_tag("sci-fi-2026"));
+        assert!(!Subject::is_valid_tag(""));
+        assert!(!Subject::is_valid_tag("Games"));
+        assert!(!Subject::is_valid_tag("sci fi"));
+        assert!(!Subject::is_valid_tag("-games"));
+        assert!(!Subject::is_valid_tag(&"a".repeat(33)));
+    }
+
     #[test]
     fn test_subject_validation() {
         let mut subject = Subject {
@@ -141,8 +235,27 @@ mod tests {
             question: None,
             event_name: None,
             search_terms: vec!["test".to_string()],
+            search_terms_exclude: Vec::new(),
             notes: None,
+            notes_private: None,
             enabled: true,
+            last_found_source: None,
+            check_on_weekdays_only: false,
+            check_on_weekends_only: false,
+            rss_feed: None,
+            initial_expected_answer: None,
+            last_error: None,
+            max_history_entries: None,
+            related_subjects: Vec::new(),
+            paused_until: None,
+            smart_check: false,
+            notification_ttl_hours: None,
+            check_interval_hours: None,
+            expiry_date: None,
+            notification_criteria: None,
+            tags: vec!["games".to_string()],
+            group: None,
+            archived: false,
         };
         assert!(subject.validate().is_ok());
 
@@ -157,5 +270,268 @@ mod tests {
 
         subject.question = Some("Who is the next Bond?".to_string());
         assert!(subject.validate().is_ok());
+
+        // Excluded term that is also searched for
+        subject.search_terms_exclude = vec!["TEST".to_string()];
+        assert!(subject.validate().is_err());
+        subject.search_terms_exclude = vec!["corrosion".to_string()];
+        assert!(subject.validate().is_ok());
+
+        // Conflicting day restrictions
+        subject.check_on_weekdays_only = true;
+        subject.check_on_weekends_only = true;
+        assert!(subject.validate().is_err());
+        subject.check_on_weekends_only = false;
+
+        // Tags use the key alphabet
+        subject.tags = vec!["Work Stuff".to_string()];
+        assert!(subject.validate().is_err());
+        subject.tags = vec!["work-stuff".to_string()];
+        assert!(subject.validate().is_ok());
+    }
+
+    #[test]
+    fn test_email_to_accepts_string_or_list() {
+        let email = |to: &str| {
+            let toml = format!(
+                "to = {}\nfrom = \"a@example.com\"\nsmtp_host = \"smtp\"\nsmtp_username = \"u\"\nsmtp_password_command = \"true\"\n",
+                to
+            );
+            toml::from_str::<EmailConfig>(&toml).unwrap()
+        };
+
+        assert_eq!(email("\"me@example.com\"").to, vec!["me@example.com"]);
+        let list = email("[\"me@example.com\", \"you@example.com\"]");
+        assert_eq!(list.to.len(), 2);
+        assert!(list.cc.is_empty() && list.bcc.is_empty());
+    }
+
+    #[test]
+    fn test_expired_subject_loads_and_archives() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.subjects.push(Subject {
+            key: "old-show".to_string(),
+            name: "Old Show".to_string(),
+            question: Some("Is it renewed?".to_string()),
+            subject_type: SubjectType::Question,
+            expiry_date: NaiveDate::from_ymd_opt(2020, 1, 1),
+            ..Default::default()
+        });
+        let mut loaded: Config = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+
+        let warnings = loaded.validate().unwrap();
+        assert!(warnings.iter().any(|w| w.contains("old-show")));
+
+        let today = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let expired = loaded.archive_expired(today);
+        assert_eq!(expired.len(), 1);
+        assert!(loaded.find_subject("old-show").unwrap().archived);
+        assert!(loaded.validate().is_ok());
+        // Already archived subjects are not archived again
+        assert!(loaded.archive_expired(today).is_empty());
+    }
+
+    #[test]
+    fn test_legacy_archived_subjects_migrate() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.subjects.push(Subject { key: "active".to_string(), ..Default::default() });
+        config.archived_subjects.push(Subject { key: "expired".to_string(), enabled: false, ..Default::default() });
+        // Written by older versions; no longer serialized
+        let legacy = toml::Value::try_from(&config.archived_subjects).unwrap();
+        let mut content = toml::to_string_pretty(&config).unwrap();
+        content.push_str(&toml::to_string_pretty(&toml::toml! { archived_subjects = legacy }).unwrap());
+        let mut loaded: Config = toml::from_str(&content).unwrap();
+
+        loaded.migrate_archived_subjects();
+        assert!(loaded.archived_subjects.is_empty());
+        assert!(!loaded.find_subject("active").unwrap().archived);
+        assert!(loaded.find_subject("expired").unwrap().archived);
+
+        let saved = toml::to_string_pretty(&loaded).unwrap();
+        assert!(!saved.contains("archived_subjects"));
+    }
+
+    #[test]
+    fn test_patch_subjects_keeps_formatting() {
+        let id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let content = format!(
+            "# my headsup config\n\n[[subjects]]\nid = \"{}\"  # gta\nkey = \"gta6\"\n\n[[subjects]]\nid = \"{}\"\nkey = \"other\"\n",
+            id, other
+        );
+
+        let patches = [
+            (id, SubjectPatch::LastFoundSource("https://example.com".to_string())),
+            (id, SubjectPatch::Archived(true)),
+            (other, SubjectPatch::LastError(Some("timeout".to_string()))),
+        ];
+        let patched = patch_subjects_in(&content, &patches).unwrap();
+        assert!(patched.starts_with("# my headsup config\n"));
+        assert!(patched.contains("# gta"));
+        let parsed: toml::Value = toml::from_str(&patched).unwrap();
+        let subjects = parsed["subjects"].as_array().unwrap();
+        assert_eq!(subjects[0]["last_found_source"].as_str(), Some("https://example.com"));
+        assert_eq!(subjects[0]["archived"].as_bool(), Some(true));
+        assert!(subjects[1].get("last_found_source").is_none());
+        assert_eq!(subjects[1]["last_error"].as_str(), Some("timeout"));
+
+        let cleared = patch_subjects_in(&patched, &[(other, SubjectPatch::LastError(None))]).unwrap();
+        assert!(!cleared.contains("timeout"));
+        assert!(cleared.contains("# gta"));
+    }
+
+    #[test]
+    fn test_redact_config() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.subjects.push(Subject {
+            notes_private: Some("personal reason".to_string()),
+            ..Default::default()
+        });
+
+        config.settings.prometheus_auth_command = Some("pass show prometheus".to_string());
+
+        let redacted = redact_config(&config);
+        assert_eq!(redacted.email.smtp_password_command, "[REDACTED]");
+        assert_eq!(redacted.settings.prometheus_auth_command.as_deref(), Some("[REDACTED]"));
+        assert!(redacted.subjects[0].notes_private.is_none());
+    }
+
+    #[test]
+    fn test_can_check_on() {
+        use chrono::Weekday;
+
+        let mut subject = Subject::default();
+        assert!(subject.can_check_on(Weekday::Mon));
+        assert!(subject.can_check_on(Weekday::Sat));
+
+        subject.check_on_weekdays_only = true;
+        assert!(subject.can_check_on(Weekday::Fri));
+        assert!(!subject.can_check_on(Weekday::Sun));
+
+        subject.check_on_weekdays_only = false;
+        subject.check_on_weekends_only = true;
+        assert!(!subject.can_check_on(Weekday::Wed));
+        assert!(subject.can_check_on(Weekday::Sat));
+    }
+
+    #[test]
+    fn test_search_terms_effective() {
+        let mut subject = Subject {
+            name: "GTA 6".to_string(),
+            category: Some(Category::Game),
+            ..Default::default()
+        };
+        assert_eq!(subject.search_terms_effective(), vec!["GTA 6 release date", "GTA 6 game release"]);
+
+        subject.search_terms = vec!["GTA VI".to_string()];
+        assert_eq!(subject.search_terms_effective(), vec!["GTA VI"]);
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.email.smtp_password_command = "pass show email/smtp".to_string();
+        assert!(config.audit().is_empty());
+
+        config.email.smtp_password_command = "echo hunter2".to_string();
+        config.subjects.push(Subject {
+            key: "feed".to_string(),
+            rss_feed: Some("http://example.com/feed.xml".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(config.audit().len(), 2);
+
+        config.webhook = Some(WebhookConfig {
+            url: "https://example.com/hook".to_string(),
+            method: Default::default(),
+            headers: [("authorization".to_string(), "Bearer abc123".to_string())].into(),
+            secret_command: Some("printf s3cret".to_string()),
+        });
+        assert_eq!(config.audit().len(), 4);
+    }
+
+    #[test]
+    fn test_notification_criteria() {
+        use crate::state::{Confidence, DatePrecision};
+
+        let criteria = NotificationCriteria {
+            min_confidence: Some(Confidence::Reliable),
+            min_date_precision: Some(DatePrecision::Month),
+            ..Default::default()
+        };
+        assert!(criteria.allows(Confidence::Official, Some(DatePrecision::Exact), None));
+        assert!(!criteria.allows(Confidence::Rumor, Some(DatePrecision::Exact), None));
+        assert!(!criteria.allows(Confidence::Official, Some(DatePrecision::Year), None));
+
+        let criteria = NotificationCriteria {
+            require_definitive: Some(true),
+            suppress_speculation: true,
+            ..Default::default()
+        };
+        assert!(criteria.allows(Confidence::Rumor, None, Some(true)));
+        assert!(!criteria.allows(Confidence::Rumor, None, Some(false)));
+        assert!(!criteria.allows(Confidence::Speculation, None, Some(true)));
+    }
+
+    #[test]
+    fn test_replace_subject_references() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.subjects.push(Subject {
+            key: "gta6".to_string(),
+            related_subjects: vec!["GTA-6".to_string()],
+            ..Default::default()
+        });
+        config.subjects.push(Subject {
+            key: "gta5".to_string(),
+            related_subjects: vec!["gta6".to_string(), "gta-6".to_string()],
+            ..Default::default()
+        });
+        config.groups.push(SubjectGroup {
+            name: "games".to_string(),
+            check_interval_hours: None,
+            notification_criteria: None,
+            subjects: vec!["gta-6".to_string(), "gta5".to_string()],
+        });
+
+        config.replace_subject_references("gta-6", "gta6");
+        assert!(config.subjects[0].related_subjects.is_empty());
+        assert_eq!(config.subjects[1].related_subjects, vec!["gta6"]);
+        assert_eq!(config.groups[0].subjects, vec!["gta6", "gta5"]);
+    }
+
+    #[test]
+    fn test_group_settings_apply_unless_overridden() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.settings.default_check_interval_hours = Some(6);
+        config.groups.push(SubjectGroup {
+            name: "games".to_string(),
+            check_interval_hours: Some(24),
+            notification_criteria: None,
+            subjects: vec!["gta6".to_string()],
+        });
+        config.groups.push(SubjectGroup {
+            name: "shows".to_string(),
+            check_interval_hours: None,
+            notification_criteria: None,
+            subjects: vec!["severance".to_string()],
+        });
+        let mut subject = Subject {
+            key: "gta6".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.check_interval_hours(&subject), Some(24));
+
+        subject.check_interval_hours = Some(1);
+        assert_eq!(config.check_interval_hours(&subject), Some(1));
+
+        subject.key = "other".to_string();
+        subject.check_interval_hours = None;
+        assert_eq!(config.check_interval_hours(&subject), Some(6));
+        subject.group = Some("games".to_string());
+        assert_eq!(config.group_of(&subject).map(|g| g.name.as_str()), Some("games"));
+
+        // A group without an interval does not override the default
+        subject.group = Some("shows".to_string());
+        assert_eq!(config.check_interval_hours(&subject), Some(6));
     }
 }
@@ -0,0 +1,260 @@
+use crate::error::{HeadsupError, Result};
+use crate::util::secret::run_secret_command;
+use std::io::ErrorKind;
+use std::process::{Command, Output};
+
+/// `smtp_password_command` prefix reading the password from the macOS Keychain
+pub const KEYCHAIN_PREFIX: &str = "keychain:";
+
+/// `smtp_password_command` prefix reading the password from the Secret Service (GNOME Keyring, KWallet)
+pub const SECRET_SERVICE_PREFIX: &str = "secret-service:";
+
+/// `smtp_password_command` prefix reading the password from a `pass` entry
+pub const PASS_PREFIX: &str = "pass:";
+
+/// Where the SMTP password is read from, as given by `smtp_password_command`
+#[derive(Debug, PartialEq, Eq)]
+enum PasswordSource<'a> {
+    /// `keychain:service/account`, read with the macOS `security` tool
+    Keychain { service: &'a str, account: &'a str },
+    /// `secret-service:service/account`, looked up by those attributes with libsecret's `secret-tool`
+    SecretService { service: &'a str, account: &'a str },
+    /// `pass:path/to/entry`, the first line of `pass show`
+    Pass { entry: &'a str },
+    /// A prefix this platform cannot read, with the reason
+    Unsupported(&'static str),
+    /// Any other value, run through the shell
+    Command(&'a str),
+}
+
+impl<'a> PasswordSource<'a> {
+    fn parse(command: &'a str) -> Result<Self> {
+        if let Some(entry) = command.strip_prefix(PASS_PREFIX) {
+            return Ok(PasswordSource::Pass { entry: entry.trim() });
+        }
+        if let Some(rest) = command.strip_prefix(KEYCHAIN_PREFIX) {
+            let (service, account) = service_and_account(KEYCHAIN_PREFIX, rest)?;
+            if cfg!(target_os = "macos") {
+                return Ok(PasswordSource::Keychain { service, account });
+            }
+            return Ok(PasswordSource::Unsupported("The keychain: prefix needs the macOS Keychain; use a password command on this platform"));
+        }
+        if let Some(rest) = command.strip_prefix(SECRET_SERVICE_PREFIX) {
+            let (service, account) = service_and_account(SECRET_SERVICE_PREFIX, rest)?;
+            if cfg!(target_os = "linux") {
+                return Ok(PasswordSource::SecretService { service, account });
+            }
+            return Ok(PasswordSource::Unsupported("The secret-service: prefix needs Linux with secret-tool; use a password command on this platform"));
+        }
+        Ok(PasswordSource::Command(command))
+    }
+}
+
+/// Split the `<service>/<account>` part after a keychain prefix
+fn service_and_account<'a>(prefix: &str, rest: &'a str) -> Result<(&'a str, &'a str)> {
+    rest.split_once('/')
+        .filter(|(service, account)| !service.is_empty() && !account.is_empty())
+        .ok_or_else(|| HeadsupError::Config(format!(
+            "smtp_password_command '{}{}' must have the form {}<service>/<account>",
+            prefix, rest, prefix
+        )))
+}
+
+/// Execute the password command (or read the password source it names) and return the password
+pub fn get_smtp_password(command: &str) -> Result<String> {
+    match PasswordSource::parse(command)? {
+        PasswordSource::Keychain { service, account } => {
+            let output = Command::new("security")
+                .args(["find-generic-password", "-s", service, "-a", account, "-w"])
+                .output();
+            // security exits with 44 when there is no such item
+            if output.as_ref().is_ok_and(|o| o.status.code() == Some(44)) {
+                return Err(HeadsupError::PasswordCommand(format!(
+                    "Keychain item not found (service '{}', account '{}'); add it with 'headsup config keychain-set'",
+                    service, account
+                )));
+            }
+            password_from(output)
+        }
+        PasswordSource::SecretService { service, account } => {
+            let output = Command::new("secret-tool")
+                .args(["lookup", "service", service, "account", account])
+                .output();
+            match output {
+                Err(e) if e.kind() == ErrorKind::NotFound => Err(HeadsupError::PasswordCommand(
+                    "The secret-service: prefix needs secret-tool; install libsecret-tools (Debian/Ubuntu) or libsecret (Fedora, Arch)"
+                        .to_string(),
+                )),
+                // secret-tool exits with 1 and prints nothing when no item matches
+                Ok(ref o) if !o.status.success() && o.stderr.is_empty() => Err(HeadsupError::SecretService(format!(
+                    "No secret with service '{}' and account '{}'; add it with 'headsup config secret-store'",
+                    service, account
+                ))),
+                Ok(ref o) if !o.status.success() => Err(HeadsupError::SecretService(
+                    String::from_utf8_lossy(&o.stderr).trim().to_string(),
+                )),
+                output => password_from(output),
+            }
+        }
+        PasswordSource::Pass { entry: "" } => Err(HeadsupError::PasswordCommand(
+            "smtp_password_command 'pass:' names no entry (e.g. pass:email/smtp)".to_string(),
+        )),
+        PasswordSource::Pass { entry } => {
+            let output = Command::new("pass").args(["show", entry]).output();
+            match output {
+                Err(e) if e.kind() == ErrorKind::NotFound => Err(HeadsupError::PasswordCommand(
+                    "pass is not installed or not on PATH".to_string(),
+                )),
+                Ok(ref o) if String::from_utf8_lossy(&o.stderr).contains("is not in the password store") => {
+                    Err(HeadsupError::PasswordCommand(format!(
+                        "pass entry '{}' not found; add it with 'headsup config pass-set --path {}'",
+                        entry, entry
+                    )))
+                }
+                // Entries may hold more lines (user names, URLs); the password is the first
+                Ok(mut o) if o.status.success() => {
+                    let first_line = o.stdout.split(|b| *b == b'\n').next().unwrap_or_default().to_vec();
+                    o.stdout = first_line;
+                    password_from(Ok(o))
+                }
+                output => password_from(output),
+            }
+        }
+        PasswordSource::Unsupported(reason) => Err(HeadsupError::PasswordCommand(reason.to_string())),
+        PasswordSource::Command(command) => get_smtp_password_from_shell(command),
+    }
+}
+
+/// The `pass` entry a `smtp_password_command` names with the `pass:` prefix
+pub fn pass_entry(command: &str) -> Option<&str> {
+    match PasswordSource::parse(command) {
+        Ok(PasswordSource::Pass { entry }) => Some(entry),
+        _ => None,
+    }
+}
+
+/// Whether a program can be found in one of the PATH directories
+pub fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// Add or overwrite a `pass` entry; `pass insert` prompts for the password
+pub fn pass_insert(entry: &str) -> Result<()> {
+    if entry.trim().is_empty() {
+        return Err(HeadsupError::PasswordCommand("The pass entry must not be empty".to_string()));
+    }
+    let status = Command::new("pass")
+        .args(["insert", "--force", entry])
+        .status()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => HeadsupError::PasswordCommand("pass is not installed or not on PATH".to_string()),
+            _ => HeadsupError::PasswordCommand(format!("Failed to run pass: {}", e)),
+        })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(HeadsupError::PasswordCommand(format!("pass insert failed ({})", status)))
+    }
+}
+
+/// Run the password command through the platform shell
+fn get_smtp_password_from_shell(command: &str) -> Result<String> {
+    run_secret_command(command, "password command").map_err(HeadsupError::PasswordCommand)
+}
+
+/// Store a password in the Secret Service; `secret-tool` prompts for it
+pub fn secret_service_store(service: &str, account: &str) -> Result<()> {
+    if !cfg!(target_os = "linux") {
+        return Err(HeadsupError::SecretService("The Secret Service is only supported on Linux".to_string()));
+    }
+
+    let label = format!("headsup {}/{}", service, account);
+    let status = Command::new("secret-tool")
+        .args(["store", "--label", &label, "service", service, "account", account])
+        .status()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => HeadsupError::SecretService(
+                "secret-tool not found; install libsecret-tools (Debian/Ubuntu) or libsecret (Fedora, Arch)".to_string(),
+            ),
+            _ => HeadsupError::SecretService(format!("Failed to run secret-tool: {}", e)),
+        })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(HeadsupError::SecretService(format!("secret-tool store failed ({}); is a keyring daemon running?", status)))
+    }
+}
+
+/// Store a password in the macOS Keychain; `security` prompts for it so it never appears in argv
+pub fn keychain_set(service: &str, account: &str) -> Result<()> {
+    if !cfg!(target_os = "macos") {
+        return Err(HeadsupError::PasswordCommand("The Keychain is only available on macOS".to_string()));
+    }
+
+    // -U updates an existing item, and -w given last makes security ask for the password
+    let status = Command::new("security")
+        .args(["add-generic-password", "-U", "-s", service, "-a", account, "-w"])
+        .status()
+        .map_err(|e| HeadsupError::PasswordCommand(format!("Failed to run security: {}", e)))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(HeadsupError::PasswordCommand(format!("security add-generic-password failed ({})", status)))
+    }
+}
+
+fn password_from(output: std::io::Result<Output>) -> Result<String> {
+    match output {
+        Ok(output) => {
+            if output.status.success() {
+                let password = String::from_utf8_lossy(&output.stdout)
+                    .trim()
+                    .to_string();
+                if password.is_empty() {
+                    Err(HeadsupError::PasswordCommand("Password command returned empty output".to_string()))
+                } else {
+                    Ok(password)
+                }
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                Err(HeadsupError::PasswordCommand(format!(
+                    "Password command failed: {}",
+                    stderr.trim()
+                )))
+            }
+        }
+        Err(e) => Err(HeadsupError::PasswordCommand(format!(
+            "Failed to execute password command: {}",
+            e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_source() {
+        assert_eq!(PasswordSource::parse("pass show smtp").unwrap(), PasswordSource::Command("pass show smtp"));
+        assert!(matches!(PasswordSource::parse("keychain:nothing"), Err(HeadsupError::Config(_))));
+        assert!(matches!(PasswordSource::parse("secret-service:smtp/"), Err(HeadsupError::Config(_))));
+        assert_eq!(PasswordSource::parse("pass: email/smtp").unwrap(), PasswordSource::Pass { entry: "email/smtp" });
+        assert_eq!(PasswordSource::parse("pass: ").unwrap(), PasswordSource::Pass { entry: "" });
+        assert!(matches!(get_smtp_password("pass:"), Err(HeadsupError::PasswordCommand(_))));
+        let keychain = PasswordSource::parse("keychain:headsup/smtp").unwrap();
+        if cfg!(target_os = "macos") {
+            assert_eq!(keychain, PasswordSource::Keychain { service: "headsup", account: "smtp" });
+        } else {
+            assert!(matches!(keychain, PasswordSource::Unsupported(_)));
+            assert!(matches!(get_smtp_password("keychain:headsup/smtp"), Err(HeadsupError::PasswordCommand(_))));
+        }
+        let secret_service = PasswordSource::parse("secret-service:headsup/smtp").unwrap();
+        if cfg!(target_os = "linux") {
+            assert_eq!(secret_service, PasswordSource::SecretService { service: "headsup", account: "smtp" });
+        } else {
+            assert!(matches!(secret_service, PasswordSource::Unsupported(_)));
+        }
+    }
+}
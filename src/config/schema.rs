@@ -0,0 +1,363 @@
+use serde_json::{json, Value};
+
+/// JSON Schema (draft 2020-12) describing the config file, for editor completion and validation
+///
+/// Written by hand; keep it in step with the types in `types.rs`.
+pub fn config_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "headsup config",
+        "type": "object",
+        "required": ["email", "settings"],
+        "properties": {
+            "email": email_schema(),
+            "backend": { "enum": ["claude", "perplexity", "openai", "ollama"], "default": "claude" },
+            "claude": claude_schema(),
+            "perplexity": perplexity_schema(),
+            "openai": openai_schema(),
+            "ollama": {
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "default": "http://localhost:11434" },
+                    "model": { "type": "string", "default": "llama3.1" },
+                    "timeout_seconds": { "type": "integer", "minimum": 0, "default": 180 },
+                    "max_searches_per_run": { "type": "integer", "minimum": 0, "default": 20 },
+                    "total_run_timeout_seconds": { "type": "integer", "minimum": 0, "default": 0 },
+                },
+            },
+            "settings": settings_schema(),
+            "webhook": webhook_schema(),
+            "telegram": {
+                "type": "object",
+                "required": ["bot_token_command", "chat_id"],
+                "properties": {
+                    "bot_token_command": {
+                        "type": "string",
+                        "description": "Command to execute to retrieve the bot token",
+                    },
+                    "chat_id": { "type": "string" },
+                },
+            },
+            "cache": {
+                "type": "object",
+                "properties": {
+                    "enabled": { "type": "boolean", "default": true },
+                    "max_age_hours": { "type": "integer", "minimum": 0, "default": 12 },
+                    "cache_dir": { "type": "string", "description": "Responses are stored in its headsup-responses subdirectory; defaults to the XDG cache directory" },
+                },
+            },
+            "groups": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "check_interval_hours": { "type": "integer", "minimum": 0 },
+                        "notification_criteria": notification_criteria_schema(),
+                        "subjects": { "type": "array", "items": { "type": "string" } },
+                    },
+                },
+            },
+            "subjects": { "type": "array", "items": subject_schema() },
+            "archived_subjects": { "type": "array", "items": subject_schema() },
+        },
+    })
+}
+
+fn webhook_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["url"],
+        "properties": {
+            "url": { "type": "string", "format": "uri" },
+            "method": { "enum": ["GET", "POST"], "default": "POST" },
+            "headers": { "type": "object", "additionalProperties": { "type": "string" } },
+            "secret_command": {
+                "type": "string",
+                "description": "Command printing the secret used to sign requests with HMAC-SHA256",
+            },
+        },
+    })
+}
+
+fn email_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["to", "from"],
+        "properties": {
+            "to": {
+                "oneOf": [
+                    { "type": "string" },
+                    { "type": "array", "items": { "type": "string" } },
+                ],
+            },
+            "cc": { "type": "array", "items": { "type": "string" } },
+            "bcc": { "type": "array", "items": { "type": "string" } },
+            "from": { "type": "string" },
+            "smtp_host": { "type": "string" },
+            "smtp_port": { "type": "integer", "minimum": 0, "maximum": 65535, "default": 587 },
+            "smtp_username": { "type": "string" },
+            "smtp_password_command": {
+                "type": "string",
+                "description": "Command to execute to retrieve the SMTP password",
+            },
+            "smtp_timeout_seconds": { "type": "integer", "minimum": 0, "default": 30 },
+            "digest_mode": { "type": "boolean", "default": false },
+            "smtp_auth_mechanism": { "enum": ["plain", "login", "xoauth2"], "default": "plain" },
+            "smtp_tls_mode": { "enum": ["starttls", "tls"], "default": "starttls" },
+            "html": { "type": "boolean", "default": false },
+            "sendmail_path": {
+                "type": "string",
+                "description": "Local sendmail binary used instead of SMTP",
+            },
+        },
+    })
+}
+
+fn claude_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "command": { "type": "string", "default": "claude" },
+            "model": { "type": "string", "default": "sonnet" },
+            "max_searches_per_run": { "type": "integer", "minimum": 0, "default": 20 },
+            "timeout_seconds": { "type": "integer", "minimum": 0, "default": 60 },
+            "total_run_timeout_seconds": { "type": "integer", "minimum": 0, "default": 0 },
+            "context_window_strategy": { "enum": ["stateless", "summary", "rolling"], "default": "stateless" },
+            "context_exchanges": { "type": "integer", "minimum": 0, "default": 3 },
+            "max_calls_per_minute": { "type": "integer", "minimum": 1 },
+            "parallel": { "type": "integer", "minimum": 1 },
+            "retry_attempts": { "type": "integer", "minimum": 0, "default": 2 },
+            "retry_initial_delay_ms": { "type": "integer", "minimum": 0, "default": 1000 },
+            "retry_jitter": { "type": "boolean", "default": false },
+        },
+    })
+}
+
+fn perplexity_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "api_key_command": {
+                "type": "string",
+                "description": "Command to execute to retrieve the Perplexity API key",
+            },
+            "model": { "type": "string", "default": "sonar" },
+            "timeout_seconds": { "type": "integer", "minimum": 0, "default": 60 },
+            "max_searches_per_run": { "type": "integer", "minimum": 0, "default": 20 },
+            "total_run_timeout_seconds": { "type": "integer", "minimum": 0, "default": 0 },
+            "retry_attempts": { "type": "integer", "minimum": 0, "default": 2 },
+            "retry_initial_delay_ms": { "type": "integer", "minimum": 0, "default": 1000 },
+            "retry_jitter": { "type": "boolean", "default": false },
+        },
+    })
+}
+
+fn openai_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "api_key_command": {
+                "type": "string",
+                "description": "Command to execute to retrieve the OpenAI API key",
+            },
+            "model": { "type": "string", "default": "gpt-4o" },
+            "timeout_seconds": { "type": "integer", "minimum": 0, "default": 60 },
+            "max_searches_per_run": { "type": "integer", "minimum": 0, "default": 20 },
+            "total_run_timeout_seconds": { "type": "integer", "minimum": 0, "default": 0 },
+            "enable_web_search": { "type": "boolean", "default": false },
+        },
+    })
+}
+
+fn settings_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "log_level": { "enum": ["quiet", "normal", "verbose"], "default": "quiet" },
+            "log_format": { "enum": ["text", "json"], "default": "text" },
+            "imminent_threshold_days": { "type": "integer", "minimum": 0, "default": 7 },
+            "max_history_entries": { "type": "integer", "minimum": 0, "default": 50 },
+            "require_source_url": { "type": "boolean", "default": false },
+            "store_raw_responses": { "type": "boolean", "default": false },
+            "dedup_window_hours": { "type": "integer", "minimum": 0, "default": 1 },
+            "cloud_sync": {
+                "type": "object",
+                "required": ["s3_bucket"],
+                "properties": {
+                    "s3_bucket": { "type": "string" },
+                    "s3_key": { "type": "string", "default": "headsup/state.json" },
+                    "aws_credentials_command": { "type": "string" },
+                    "endpoint_url": { "type": "string" },
+                },
+            },
+            "internet_check_url": { "type": "string", "default": "https://1.1.1.1" },
+            "prometheus_pushgateway_url": { "type": "string" },
+            "prometheus_job_name": { "type": "string", "default": "headsup" },
+            "prometheus_auth_command": { "type": "string" },
+            "default_notification_ttl_hours": { "type": "integer", "minimum": 0 },
+            "pending_notification_max_age_hours": { "type": "integer", "minimum": 0, "default": 24 },
+            "default_check_interval_hours": { "type": "integer", "minimum": 0 },
+            "auto_archive_released": { "type": "boolean", "default": false },
+            "max_state_backups": { "type": "integer", "minimum": 1, "default": 10 },
+            "ics_reminder_minutes": { "type": "integer", "minimum": 0 },
+            "ics_reminder_action": { "enum": ["display", "email"], "default": "display" },
+            "ai_backend_fallback": {
+                "type": "array",
+                "items": { "enum": ["claude", "perplexity", "openai", "ollama"] },
+            },
+            "fallback_on_timeout": { "type": "boolean", "default": false },
+        },
+    })
+}
+
+fn subject_schema() -> Value {
+    let string_list = json!({ "type": "array", "items": { "type": "string" } });
+    json!({
+        "type": "object",
+        "required": ["name", "search_terms"],
+        "properties": {
+            "id": { "type": "string", "format": "uuid" },
+            "key": { "type": "string", "maxLength": 32, "pattern": "^[a-z][a-z0-9-]*$" },
+            "name": { "type": "string" },
+            "type": { "enum": ["release", "question", "recurring"], "default": "release" },
+            "category": { "enum": ["game", "tv_show", "tv_season", "movie", "music", "software", "other"] },
+            "question": { "type": "string" },
+            "event_name": { "type": "string" },
+            "search_terms": string_list,
+            "search_terms_exclude": string_list,
+            "notes": { "type": "string" },
+            "notes_private": { "type": "string" },
+            "enabled": { "type": "boolean", "default": true },
+            "last_found_source": { "type": "string" },
+            "check_on_weekdays_only": { "type": "boolean", "default": false },
+            "check_on_weekends_only": { "type": "boolean", "default": false },
+            "rss_feed": { "type": "string" },
+            "initial_expected_answer": { "type": "string" },
+            "last_error": { "type": "string" },
+            "max_history_entries": { "type": "integer", "minimum": 0 },
+            "related_subjects": string_list,
+            "paused_until": { "type": "string", "format": "date-time" },
+            "smart_check": { "type": "boolean", "default": false },
+            "notification_ttl_hours": { "type": "integer", "minimum": 0 },
+            "check_interval_hours": { "type": "integer", "minimum": 0 },
+            "expiry_date": { "type": "string", "format": "date" },
+            "tags": string_list,
+            "group": { "type": "string", "description": "Name of an entry in groups" },
+            "archived": { "type": "boolean", "default": false },
+            "notification_criteria": notification_criteria_schema(),
+        },
+    })
+}
+
+fn notification_criteria_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "min_confidence": { "enum": ["official", "reliable", "rumor", "speculation", "unknown"] },
+            "require_definitive": { "type": "boolean" },
+            "min_date_precision": { "enum": ["exact", "month", "season", "year", "unknown"] },
+            "suppress_speculation": { "type": "boolean", "default": false },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Backend, CacheConfig, CloudSyncConfig, Config, NotificationCriteria, SubjectGroup, Subject, TelegramConfig, WebhookConfig, WebhookMethod};
+
+    /// Every key the config serializes to must be described by the schema
+    fn assert_covered(value: &Value, schema: &Value, path: &str) {
+        let (Value::Object(fields), Some(Value::Object(properties))) = (value, schema.get("properties")) else {
+            return;
+        };
+        for (key, field) in fields {
+            let field_schema = properties.get(key)
+                .unwrap_or_else(|| panic!("schema is missing {}.{}", path, key));
+            let field_schema = field_schema.get("items").unwrap_or(field_schema);
+            match field {
+                Value::Array(items) => items.iter().for_each(|item| assert_covered(item, field_schema, key)),
+                _ => assert_covered(field, field_schema, key),
+            }
+        }
+    }
+
+    #[test]
+    fn test_schema_covers_config() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.email.cc = vec!["cc@example.com".to_string()];
+        config.email.bcc = vec!["bcc@example.com".to_string()];
+        config.email.sendmail_path = Some("/usr/sbin/sendmail".into());
+        config.webhook = Some(WebhookConfig {
+            url: "https://example.com/hook".to_string(),
+            method: WebhookMethod::Get,
+            headers: [("Authorization".to_string(), "Bearer x".to_string())].into(),
+            secret_command: Some("echo secret".to_string()),
+        });
+        config.telegram = Some(TelegramConfig {
+            bot_token_command: "pass show telegram".to_string(),
+            chat_id: "-100123".to_string(),
+        });
+        config.cache = Some(CacheConfig {
+            enabled: true,
+            max_age_hours: 6,
+            cache_dir: Some("/tmp/headsup-cache".into()),
+        });
+        config.settings.cloud_sync = Some(CloudSyncConfig {
+            s3_bucket: "bucket".to_string(),
+            s3_key: "key".to_string(),
+            aws_credentials_command: Some("creds".to_string()),
+            endpoint_url: Some("http://localhost:9000".to_string()),
+        });
+        config.settings.internet_check_url = Some("https://1.1.1.1".to_string());
+        config.settings.prometheus_pushgateway_url = Some("http://localhost:9091".to_string());
+        config.settings.prometheus_job_name = Some("headsup".to_string());
+        config.settings.prometheus_auth_command = Some("echo token".to_string());
+        config.settings.default_notification_ttl_hours = Some(48);
+        config.settings.ics_reminder_minutes = Some(60);
+        config.settings.default_check_interval_hours = Some(24);
+        config.settings.ai_backend_fallback = vec![Backend::Perplexity];
+        config.settings.fallback_on_timeout = true;
+        config.settings.auto_archive_released = true;
+        config.claude.max_calls_per_minute = Some(10);
+        config.claude.parallel = Some(4);
+        config.claude.retry_jitter = true;
+        config.perplexity.retry_jitter = true;
+        config.subjects.push(Subject {
+            key: "gta6".to_string(),
+            name: "GTA 6".to_string(),
+            search_terms_exclude: vec!["mod".to_string()],
+            last_error: Some("timeout".to_string()),
+            max_history_entries: Some(5),
+            related_subjects: vec!["gta5".to_string()],
+            paused_until: Some(chrono::Utc::now()),
+            check_on_weekdays_only: true,
+            smart_check: true,
+            notification_ttl_hours: Some(24),
+            check_interval_hours: Some(6),
+            expiry_date: Some(chrono::NaiveDate::from_ymd_opt(2030, 1, 1).unwrap()),
+            tags: vec!["games".to_string()],
+            group: Some("games".to_string()),
+            archived: true,
+            notification_criteria: Some(NotificationCriteria {
+                min_confidence: Some(crate::state::Confidence::Official),
+                require_definitive: Some(true),
+                min_date_precision: Some(crate::state::DatePrecision::Month),
+                suppress_speculation: true,
+            }),
+            ..Default::default()
+        });
+        config.archived_subjects = config.subjects.clone();
+        config.groups.push(SubjectGroup {
+            name: "games".to_string(),
+            check_interval_hours: Some(24),
+            notification_criteria: Some(NotificationCriteria::default()),
+            subjects: vec!["gta6".to_string()],
+        });
+
+        let value = serde_json::to_value(&config).unwrap();
+        assert_covered(&value, &config_schema(), "config");
+    }
+}
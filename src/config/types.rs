@@ -1,4 +1,9 @@
+use crate::state::{Confidence, DatePrecision};
+use crate::util::backoff::Backoff;
+use chrono::{DateTime, NaiveDate, Utc, Weekday};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,9 +15,28 @@ pub struct Config {
     pub claude: ClaudeConfig,
     #[serde(default)]
     pub perplexity: PerplexityConfig,
+    #[serde(default)]
+    pub openai: OpenAiConfig,
+    #[serde(default)]
+    pub ollama: OllamaConfig,
     pub settings: Settings,
+    /// HTTP endpoint notified alongside (or with --webhook-only, instead of) email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookConfig>,
+    /// Telegram chat notified alongside email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<TelegramConfig>,
+    /// Reuse recent backend responses for unchanged prompts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheConfig>,
+    /// Named sets of subjects sharing a check interval and notification criteria
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<SubjectGroup>,
     #[serde(default)]
     pub subjects: Vec<Subject>,
+    /// Legacy list of expired subjects; moved into `subjects` with `archived = true` on load
+    #[serde(default, skip_serializing)]
+    pub archived_subjects: Vec<Subject>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -21,22 +45,140 @@ pub enum Backend {
     #[default]
     Claude,
     Perplexity,
+    OpenAi,
+    /// A local model served by Ollama (no web search)
+    Ollama,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmailConfig {
-    pub to: String,
+    /// Recipients (a single address string is also accepted)
+    #[serde(deserialize_with = "string_or_list")]
+    pub to: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cc: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bcc: Vec<String>,
     pub from: String,
+    #[serde(default)]
     pub smtp_host: String,
     #[serde(default = "default_smtp_port")]
     pub smtp_port: u16,
+    #[serde(default)]
     pub smtp_username: String,
     /// Command to execute to retrieve the SMTP password
+    #[serde(default)]
     pub smtp_password_command: String,
     #[serde(default = "default_smtp_timeout")]
     pub smtp_timeout_seconds: u64,
     #[serde(default)]
     pub digest_mode: bool,
+    /// SMTP authentication mechanism (for xoauth2 the password command returns an access token)
+    #[serde(default)]
+    pub smtp_auth_mechanism: SmtpAuthMechanism,
+    #[serde(default)]
+    pub smtp_tls_mode: SmtpTlsMode,
+    /// Also send an HTML version of each email (multipart/alternative)
+    #[serde(default)]
+    pub html: bool,
+    /// Hand emails to this local sendmail binary instead of connecting to an SMTP server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sendmail_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpAuthMechanism {
+    #[default]
+    Plain,
+    Login,
+    XOAuth2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTlsMode {
+    /// Upgrade a plain connection with STARTTLS (usually port 587)
+    #[default]
+    Starttls,
+    /// Implicit TLS from the start of the connection (usually port 465)
+    Tls,
+}
+
+/// Accept either `to = "a@b.c"` or `to = ["a@b.c", ...]`
+fn string_or_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match StringOrList::deserialize(deserializer)? {
+        StringOrList::One(address) if address.is_empty() => Vec::new(),
+        StringOrList::One(address) => vec![address],
+        StringOrList::Many(addresses) => addresses,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub method: WebhookMethod,
+    /// Extra request headers, e.g. Authorization
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Command printing the secret used to sign requests (X-Headsup-Signature: sha256=<hex HMAC>)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_command: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    /// Command to execute to retrieve the bot token
+    pub bot_token_command: String,
+    /// User or group chat the bot writes to
+    pub chat_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Responses older than this are fetched again
+    #[serde(default = "default_cache_max_age_hours")]
+    pub max_age_hours: u32,
+    /// Responses go in its headsup-responses subdirectory; defaults to the XDG cache directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            enabled: true,
+            max_age_hours: default_cache_max_age_hours(),
+            cache_dir: None,
+        }
+    }
+}
+
+fn default_cache_max_age_hours() -> u32 {
+    12
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WebhookMethod {
+    /// Send the notification as query parameters
+    Get,
+    /// Send the notification as a JSON body
+    #[default]
+    Post,
 }
 
 fn default_smtp_port() -> u16 {
@@ -59,6 +201,71 @@ pub struct ClaudeConfig {
     pub timeout_seconds: u64,
     #[serde(default)]
     pub total_run_timeout_seconds: u64,
+    /// How findings from earlier checks are carried into the next prompt
+    #[serde(default)]
+    pub context_window_strategy: ContextStrategy,
+    /// Number of previous checks included with the rolling strategy
+    #[serde(default = "default_context_exchanges")]
+    pub context_exchanges: u32,
+    /// Start at most this many AI calls per minute (unlimited when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_calls_per_minute: Option<u32>,
+    /// Run at most this many subject checks at the same time (all at once when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<u32>,
+    /// Retries after a failed or timed-out call (not after an unparseable response)
+    #[serde(default = "default_retry_attempts")]
+    pub retry_attempts: u32,
+    /// Delay before the first retry, doubling for each further one
+    #[serde(default = "default_retry_initial_delay_ms")]
+    pub retry_initial_delay_ms: u64,
+    /// Add a random amount of up to half the delay to each retry
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub retry_jitter: bool,
+}
+
+impl ClaudeConfig {
+    pub fn backoff(&self) -> Backoff {
+        Backoff {
+            attempts: self.retry_attempts,
+            initial_delay_ms: self.retry_initial_delay_ms,
+            jitter: self.retry_jitter,
+        }
+    }
+}
+
+fn default_retry_attempts() -> u32 {
+    2
+}
+
+fn default_retry_initial_delay_ms() -> u64 {
+    1000
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IcsReminderAction {
+    /// Alert on the device
+    #[default]
+    Display,
+    /// Email the reminder to the email.to recipients
+    Email,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextStrategy {
+    /// Each check starts from the current state only
+    #[default]
+    Stateless,
+    /// Prepend the summary of the previous check
+    Summary,
+    /// Prepend the summaries of the last `context_exchanges` checks
+    Rolling,
+}
+
+fn default_context_exchanges() -> u32 {
+    3
 }
 
 fn default_claude_command() -> String {
@@ -90,6 +297,99 @@ pub struct PerplexityConfig {
     pub max_searches_per_run: u32,
     #[serde(default)]
     pub total_run_timeout_seconds: u64,
+    /// Retries after a failed or timed-out request (not after an unparseable response)
+    #[serde(default = "default_retry_attempts")]
+    pub retry_attempts: u32,
+    /// Delay before the first retry, doubling for each further one
+    #[serde(default = "default_retry_initial_delay_ms")]
+    pub retry_initial_delay_ms: u64,
+    /// Add a random amount of up to half the delay to each retry
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub retry_jitter: bool,
+}
+
+impl PerplexityConfig {
+    pub fn backoff(&self) -> Backoff {
+        Backoff {
+            attempts: self.retry_attempts,
+            initial_delay_ms: self.retry_initial_delay_ms,
+            jitter: self.retry_jitter,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiConfig {
+    /// Command to execute to retrieve the OpenAI API key
+    #[serde(default)]
+    pub api_key_command: String,
+    #[serde(default = "default_openai_model")]
+    pub model: String,
+    #[serde(default = "default_timeout")]
+    pub timeout_seconds: u64,
+    #[serde(default = "default_max_searches")]
+    pub max_searches_per_run: u32,
+    #[serde(default)]
+    pub total_run_timeout_seconds: u64,
+    /// Let the model search the web (needs a search model such as gpt-4o-search-preview)
+    #[serde(default)]
+    pub enable_web_search: bool,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        OpenAiConfig {
+            api_key_command: String::new(),
+            model: default_openai_model(),
+            timeout_seconds: default_timeout(),
+            max_searches_per_run: default_max_searches(),
+            total_run_timeout_seconds: 0,
+            enable_web_search: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    #[serde(default = "default_ollama_url")]
+    pub url: String,
+    #[serde(default = "default_ollama_model")]
+    pub model: String,
+    #[serde(default = "default_ollama_timeout")]
+    pub timeout_seconds: u64,
+    #[serde(default = "default_max_searches")]
+    pub max_searches_per_run: u32,
+    #[serde(default)]
+    pub total_run_timeout_seconds: u64,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        OllamaConfig {
+            url: default_ollama_url(),
+            model: default_ollama_model(),
+            timeout_seconds: default_ollama_timeout(),
+            max_searches_per_run: default_max_searches(),
+            total_run_timeout_seconds: 0,
+        }
+    }
+}
+
+fn default_ollama_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+fn default_ollama_model() -> String {
+    "llama3.1".to_string()
+}
+
+/// Local models are slower than hosted APIs
+fn default_ollama_timeout() -> u64 {
+    180
+}
+
+fn default_openai_model() -> String {
+    "gpt-4o".to_string()
 }
 
 fn default_perplexity_model() -> String {
@@ -106,6 +406,74 @@ pub struct Settings {
     pub imminent_threshold_days: u32,
     #[serde(default = "default_max_history")]
     pub max_history_entries: u32,
+    /// Treat responses without a source URL as failed checks
+    #[serde(default)]
+    pub require_source_url: bool,
+    /// Store the unparsed AI output in history entries
+    #[serde(default)]
+    pub store_raw_responses: bool,
+    /// Merge queued notifications for the same subject and event within this many hours (0 disables)
+    #[serde(default = "default_dedup_window")]
+    pub dedup_window_hours: u32,
+    /// Where `check --cloud-sync` keeps a shared copy of the state file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cloud_sync: Option<CloudSyncConfig>,
+    /// URL probed by `check --wait-for-internet` (default https://1.1.1.1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internet_check_url: Option<String>,
+    /// Pushgateway used by `check --output prometheus-push` when no URL is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prometheus_pushgateway_url: Option<String>,
+    /// Job label for pushed metrics (default "headsup")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prometheus_job_name: Option<String>,
+    /// Command printing the pushgateway Authorization header value, e.g. "Bearer <token>"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prometheus_auth_command: Option<String>,
+    /// Drop queued notifications older than this many hours unless the subject sets its own TTL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_notification_ttl_hours: Option<u32>,
+    /// Drop queued notifications without a TTL once they are this many hours old (0 keeps them)
+    #[serde(default = "default_pending_notification_max_age_hours")]
+    pub pending_notification_max_age_hours: u32,
+    /// Remind this many minutes before events in ICS attachments (no reminder when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ics_reminder_minutes: Option<u32>,
+    /// How calendar clients deliver the ICS reminder
+    #[serde(default)]
+    pub ics_reminder_action: IcsReminderAction,
+    /// Automatic state backups kept in the backups directory
+    #[serde(default = "default_max_state_backups")]
+    pub max_state_backups: u32,
+    /// Archive release subjects once a check reports them as released
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub auto_archive_released: bool,
+    /// Skip subjects checked less than this many hours ago unless they set their own interval
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_check_interval_hours: Option<u32>,
+    /// Backends tried in order when the configured backend fails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ai_backend_fallback: Vec<Backend>,
+    /// Also fall back when the backend times out
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub fallback_on_timeout: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudSyncConfig {
+    pub s3_bucket: String,
+    #[serde(default = "default_s3_key")]
+    pub s3_key: String,
+    /// Command printing credentials in the AWS credential_process JSON format
+    #[serde(default)]
+    pub aws_credentials_command: Option<String>,
+    /// Endpoint for S3-compatible services (MinIO, R2, ...)
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+}
+
+fn default_s3_key() -> String {
+    "headsup/state.json".to_string()
 }
 
 fn default_log_level() -> LogLevel {
@@ -124,6 +492,10 @@ fn default_max_history() -> u32 {
     50
 }
 
+fn default_dedup_window() -> u32 {
+    1
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
@@ -157,16 +529,174 @@ pub struct Subject {
     #[serde(default)]
     pub event_name: Option<String>,
     pub search_terms: Vec<String>,
+    /// Terms whose pages the AI should ignore (e.g. "corrosion" for Rust the language)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_terms_exclude: Vec<String>,
     #[serde(default)]
     pub notes: Option<String>,
+    /// Private context passed to the AI but never shown or exported
+    #[serde(default)]
+    pub notes_private: Option<String>,
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    /// Most recent credible source URL found for this subject
+    #[serde(default)]
+    pub last_found_source: Option<String>,
+    /// Only check this subject Monday through Friday
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub check_on_weekdays_only: bool,
+    /// Only check this subject on Saturday and Sunday
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub check_on_weekends_only: bool,
+    /// RSS/Atom feed URL to watch for new entries
+    #[serde(default)]
+    pub rss_feed: Option<String>,
+    /// Answer you expect a question to resolve to, seeded into state
+    #[serde(default)]
+    pub initial_expected_answer: Option<String>,
+    /// Reason the most recent check failed, cleared by the next successful check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// History entries to keep for this subject, overriding settings.max_history_entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_history_entries: Option<u32>,
+    /// Keys of subjects this one relates to, drawn as edges by `subjects graph`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_subjects: Vec<String>,
+    /// Skip this subject in checks until this time, without disabling it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused_until: Option<DateTime<Utc>>,
+    /// Check more often when past updates suggest one is likely, and back off otherwise
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub smart_check: bool,
+    /// Drop this subject's queued notifications older than this many hours
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification_ttl_hours: Option<u32>,
+    /// Minimum hours between two checks of this subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_interval_hours: Option<u32>,
+    /// Archive the subject once this date has passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_date: Option<NaiveDate>,
+    /// Thresholds a response must meet before the AI's decision to notify is honoured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification_criteria: Option<NotificationCriteria>,
+    /// Labels for grouping subjects, e.g. "games" (filter with --tag)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Group whose settings apply where the subject sets none of its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Resolved: no longer checked, but kept with its history
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubjectGroup {
+    pub name: String,
+    /// Minimum hours between two checks of each member, unless a subject sets its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_interval_hours: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification_criteria: Option<NotificationCriteria>,
+    /// Member subject keys (subjects can also join with their `group` field)
+    #[serde(default)]
+    pub subjects: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationCriteria {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<Confidence>,
+    /// Questions only: require a definitive answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_definitive: Option<bool>,
+    /// Releases and recurring events only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_date_precision: Option<DatePrecision>,
+    /// Never notify for speculation or unknown confidence
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub suppress_speculation: bool,
+}
+
+impl NotificationCriteria {
+    /// Whether a response with these properties may trigger a notification
+    pub fn allows(
+        &self,
+        confidence: Confidence,
+        precision: Option<DatePrecision>,
+        is_definitive: Option<bool>,
+    ) -> bool {
+        if self.suppress_speculation && !confidence.is_at_least(&Confidence::Rumor) {
+            return false;
+        }
+        if let Some(min) = self.min_confidence {
+            if !confidence.is_at_least(&min) {
+                return false;
+            }
+        }
+        if let (Some(min), Some(precision)) = (self.min_date_precision, precision) {
+            if !precision.is_at_least(&min) {
+                return false;
+            }
+        }
+        !(self.require_definitive == Some(true) && is_definitive == Some(false))
+    }
+}
+
+fn default_pending_notification_max_age_hours() -> u32 {
+    24
+}
+
+fn default_max_state_backups() -> u32 {
+    10
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl Default for Subject {
+    fn default() -> Self {
+        Subject {
+            id: Uuid::new_v4(),
+            key: String::new(),
+            name: String::new(),
+            subject_type: SubjectType::Release,
+            category: None,
+            question: None,
+            event_name: None,
+            search_terms: Vec::new(),
+            search_terms_exclude: Vec::new(),
+            notes: None,
+            notes_private: None,
+            enabled: true,
+            last_found_source: None,
+            check_on_weekdays_only: false,
+            check_on_weekends_only: false,
+            rss_feed: None,
+            initial_expected_answer: None,
+            last_error: None,
+            max_history_entries: None,
+            related_subjects: Vec::new(),
+            paused_until: None,
+            smart_check: false,
+            notification_ttl_hours: None,
+            check_interval_hours: None,
+            expiry_date: None,
+            notification_criteria: None,
+            tags: Vec::new(),
+            group: None,
+            archived: false,
+        }
+    }
+}
+
 impl Subject {
     /// Generate a key from the subject name
     pub fn generate_key(name: &str) -> String {
@@ -190,7 +720,7 @@ impl Subject {
             if self.key.len() > 32 {
                 return Err("Key must be 32 characters or less".to_string());
             }
-            if self.key.starts_with('-') || self.key.chars().next().map_or(false, |c| c.is_ascii_digit()) {
+            if self.key.starts_with('-') || self.key.chars().next().is_some_and(|c| c.is_ascii_digit()) {
                 return Err("Key cannot start with a number or hyphen".to_string());
             }
             if !self.key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
@@ -206,21 +736,103 @@ impl Subject {
                 }
             }
             SubjectType::Question => {
-                if self.question.is_none() || self.question.as_ref().map_or(true, |q| q.is_empty()) {
+                if self.question.is_none() || self.question.as_ref().is_none_or(|q| q.is_empty()) {
                     return Err("Question type subjects require a question field".to_string());
                 }
             }
             SubjectType::Recurring => {
-                if self.event_name.is_none() || self.event_name.as_ref().map_or(true, |e| e.is_empty()) {
+                if self.event_name.is_none() || self.event_name.as_ref().is_none_or(|e| e.is_empty()) {
                     return Err("Recurring type subjects require an event_name field".to_string());
                 }
             }
         }
 
-        // search_terms is now optional - Claude/Perplexity can determine queries from context
+        // search_terms is optional - fallback terms are derived from the name
+        if self.search_terms.is_empty() && self.name.trim().is_empty() {
+            return Err("Subjects without search_terms require a name".to_string());
+        }
+
+        if let Some(term) = self.search_terms_exclude.iter()
+            .find(|e| self.search_terms.iter().any(|t| t.trim().eq_ignore_ascii_case(e.trim())))
+        {
+            return Err(format!("'{}' is in both search_terms and search_terms_exclude", term));
+        }
+
+        if self.check_on_weekdays_only && self.check_on_weekends_only {
+            return Err("check_on_weekdays_only and check_on_weekends_only cannot both be set".to_string());
+        }
+
+        if let Some(tag) = self.tags.iter().find(|t| !Subject::is_valid_tag(t)) {
+            return Err(format!(
+                "Tag '{}' must be 1-32 lowercase letters, numbers, and hyphens",
+                tag
+            ));
+        }
 
         Ok(())
     }
+
+    /// Tags follow the shape of generated keys: lowercase alphanumerics joined by single hyphens
+    pub fn is_valid_tag(tag: &str) -> bool {
+        !tag.is_empty()
+            && tag.len() <= 32
+            && tag.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && Subject::generate_key(tag) == tag
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Configured search terms, or simple terms built from the name when none are set
+    pub fn search_terms_effective(&self) -> Vec<String> {
+        if !self.search_terms.is_empty() {
+            return self.search_terms.clone();
+        }
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Vec::new();
+        }
+
+        match self.subject_type {
+            SubjectType::Release => {
+                let mut terms = vec![format!("{} release date", name)];
+                if let Some(category) = self.category.filter(|c| *c != Category::Other) {
+                    terms.push(format!("{} {} release", name, category.to_string().to_lowercase()));
+                }
+                terms
+            }
+            SubjectType::Question => vec![name.to_string()],
+            SubjectType::Recurring => match self.event_name.as_deref().map(str::trim) {
+                Some(event) if !event.is_empty() => {
+                    vec![format!("{} {}", name, event), format!("{} {} date", name, event)]
+                }
+                _ => vec![format!("{} next date", name)],
+            },
+        }
+    }
+
+    /// Whether the subject's expiry date is before the given day
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expiry_date.is_some_and(|date| date < today)
+    }
+
+    /// Whether the subject is paused at the given time
+    pub fn is_paused(&self, now: DateTime<Utc>) -> bool {
+        self.paused_until.is_some_and(|until| until > now)
+    }
+
+    /// Check whether the subject's day restriction allows checking on the given weekday
+    pub fn can_check_on(&self, weekday: Weekday) -> bool {
+        let is_weekend = matches!(weekday, Weekday::Sat | Weekday::Sun);
+        if self.check_on_weekdays_only {
+            !is_weekend
+        } else if self.check_on_weekends_only {
+            is_weekend
+        } else {
+            true
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -273,7 +885,9 @@ impl Config {
     pub fn default_with_email(email: &str) -> Self {
         Config {
             email: EmailConfig {
-                to: email.to_string(),
+                to: vec![email.to_string()],
+                cc: vec![],
+                bcc: vec![],
                 from: format!("radar@{}", email.split('@').nth(1).unwrap_or("example.com")),
                 smtp_host: "smtp.example.com".to_string(),
                 smtp_port: 587,
@@ -281,6 +895,10 @@ impl Config {
                 smtp_password_command: "echo 'your-password-here'".to_string(),
                 smtp_timeout_seconds: 30,
                 digest_mode: false,
+                smtp_auth_mechanism: SmtpAuthMechanism::Plain,
+                smtp_tls_mode: SmtpTlsMode::Starttls,
+                html: false,
+                sendmail_path: None,
             },
             backend: Backend::Claude,
             claude: ClaudeConfig {
@@ -289,6 +907,13 @@ impl Config {
                 max_searches_per_run: 20,
                 timeout_seconds: 60,
                 total_run_timeout_seconds: 600,
+                context_window_strategy: ContextStrategy::Stateless,
+                context_exchanges: 3,
+                max_calls_per_minute: None,
+                parallel: None,
+                retry_attempts: default_retry_attempts(),
+                retry_initial_delay_ms: default_retry_initial_delay_ms(),
+                retry_jitter: false,
             },
             perplexity: PerplexityConfig {
                 api_key_command: String::new(),
@@ -296,14 +921,48 @@ impl Config {
                 timeout_seconds: 30,
                 max_searches_per_run: 20,
                 total_run_timeout_seconds: 300,
+                retry_attempts: default_retry_attempts(),
+                retry_initial_delay_ms: default_retry_initial_delay_ms(),
+                retry_jitter: false,
             },
+            openai: OpenAiConfig {
+                api_key_command: String::new(),
+                model: default_openai_model(),
+                timeout_seconds: 60,
+                max_searches_per_run: 20,
+                total_run_timeout_seconds: 300,
+                enable_web_search: false,
+            },
+            ollama: OllamaConfig::default(),
             settings: Settings {
                 log_level: LogLevel::Quiet,
                 log_format: LogFormat::Text,
                 imminent_threshold_days: 7,
                 max_history_entries: 50,
+                require_source_url: false,
+                store_raw_responses: false,
+                dedup_window_hours: 1,
+                cloud_sync: None,
+                internet_check_url: None,
+                prometheus_pushgateway_url: None,
+                prometheus_job_name: None,
+                prometheus_auth_command: None,
+                default_notification_ttl_hours: None,
+                pending_notification_max_age_hours: default_pending_notification_max_age_hours(),
+                default_check_interval_hours: None,
+                ics_reminder_minutes: None,
+                ics_reminder_action: IcsReminderAction::Display,
+                max_state_backups: default_max_state_backups(),
+                auto_archive_released: false,
+                ai_backend_fallback: Vec::new(),
+                fallback_on_timeout: false,
             },
             subjects: vec![],
+            webhook: None,
+            telegram: None,
+            cache: None,
+            groups: Vec::new(),
+            archived_subjects: vec![],
         }
     }
 
@@ -329,6 +988,58 @@ impl Config {
         self.subjects.iter_mut().find(|s| s.key.to_lowercase() == lower)
     }
 
+    /// Point `related_subjects` and group members that name `old_key` at `new_key` instead
+    pub fn replace_subject_references(&mut self, old_key: &str, new_key: &str) {
+        let replace = |keys: &mut Vec<String>, owner: Option<&str>| {
+            for key in keys.iter_mut().filter(|k| k.eq_ignore_ascii_case(old_key)) {
+                *key = new_key.to_string();
+            }
+            let mut seen = std::collections::HashSet::new();
+            keys.retain(|k| {
+                owner.is_none_or(|o| !k.eq_ignore_ascii_case(o)) && seen.insert(k.to_lowercase())
+            });
+        };
+        for subject in &mut self.subjects {
+            let owner = subject.key.clone();
+            replace(&mut subject.related_subjects, Some(&owner));
+        }
+        for group in &mut self.groups {
+            replace(&mut group.subjects, None);
+        }
+    }
+
+    /// Find a group by name
+    pub fn find_group(&self, name: &str) -> Option<&SubjectGroup> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// The group a subject belongs to: the one it names, else the first listing its key
+    pub fn group_of(&self, subject: &Subject) -> Option<&SubjectGroup> {
+        match subject.group {
+            Some(ref name) => self.find_group(name),
+            None => self
+                .groups
+                .iter()
+                .find(|g| g.subjects.iter().any(|k| k.eq_ignore_ascii_case(&subject.key))),
+        }
+    }
+
+    /// The subject's own check interval, else its group's, else the default
+    pub fn check_interval_hours(&self, subject: &Subject) -> Option<u32> {
+        subject
+            .check_interval_hours
+            .or_else(|| self.group_of(subject).and_then(|g| g.check_interval_hours))
+            .or(self.settings.default_check_interval_hours)
+    }
+
+    /// The subject's own notification criteria, else its group's
+    pub fn notification_criteria<'a>(&'a self, subject: &'a Subject) -> Option<&'a NotificationCriteria> {
+        subject
+            .notification_criteria
+            .as_ref()
+            .or_else(|| self.group_of(subject)?.notification_criteria.as_ref())
+    }
+
     /// Check if a key is already in use
     pub fn key_exists(&self, key: &str) -> bool {
         let lower = key.to_lowercase();
@@ -355,16 +1066,21 @@ impl Config {
 
     /// Validate the entire configuration
     pub fn validate(&self) -> Result<Vec<String>, Vec<String>> {
-        let warnings = Vec::new();
+        let mut warnings = Vec::new();
         let mut errors = Vec::new();
 
         // Validate email config
         if self.email.to.is_empty() {
             errors.push("Email 'to' address is required".to_string());
         }
-        if self.email.smtp_host.is_empty() {
+        if self.email.smtp_host.is_empty() && self.email.sendmail_path.is_none() {
             errors.push("SMTP host is required".to_string());
         }
+        if let Some(ref webhook) = self.webhook {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                errors.push(format!("Webhook url '{}' must start with http:// or https://", webhook.url));
+            }
+        }
 
         // Validate subjects
         let mut seen_keys: std::collections::HashSet<String> = std::collections::HashSet::new();
@@ -379,6 +1095,37 @@ impl Config {
             if let Err(e) = subject.validate() {
                 errors.push(format!("Subject '{}' (index {}): {}", subject.name, i, e));
             }
+
+            for related in &subject.related_subjects {
+                if !self.subjects.iter().any(|s| s.key.eq_ignore_ascii_case(related)) {
+                    warnings.push(format!("Subject '{}' relates to unknown subject '{}'", subject.key, related));
+                }
+            }
+
+            // Expired subjects stay valid until the next check archives them
+            if let Some(date) = subject.expiry_date.filter(|_| subject.is_expired(chrono::Local::now().date_naive())) {
+                warnings.push(format!("Subject '{}' expired on {} and is archived on the next check", subject.key, date));
+            }
+
+            if let Some(ref group) = subject.group {
+                if self.find_group(group).is_none() {
+                    errors.push(format!("Subject '{}' belongs to unknown group '{}'", subject.key, group));
+                }
+            }
+        }
+
+        let mut seen_groups = std::collections::HashSet::new();
+        for group in &self.groups {
+            if group.name.is_empty() {
+                errors.push("Group names must not be empty".to_string());
+            } else if !seen_groups.insert(group.name.as_str()) {
+                errors.push(format!("Duplicate group name: {}", group.name));
+            }
+            for key in &group.subjects {
+                if !self.key_exists(key) {
+                    warnings.push(format!("Group '{}' lists unknown subject '{}'", group.name, key));
+                }
+            }
         }
 
         if errors.is_empty() {
@@ -387,4 +1134,99 @@ impl Config {
             Err(errors)
         }
     }
+
+    /// Move subjects from the legacy archived_subjects list into subjects, marked as archived
+    pub fn migrate_archived_subjects(&mut self) {
+        for subject in std::mem::take(&mut self.archived_subjects) {
+            if !self.subjects.iter().any(|s| s.id == subject.id) {
+                self.subjects.push(Subject { archived: true, ..subject });
+            }
+        }
+    }
+
+    /// Archive subjects whose expiry date is before `today`, returning the newly archived ones
+    pub fn archive_expired(&mut self, today: NaiveDate) -> Vec<Subject> {
+        let mut expired = Vec::new();
+        for subject in self.subjects.iter_mut().filter(|s| !s.archived && s.is_expired(today)) {
+            subject.archived = true;
+            expired.push(subject.clone());
+        }
+        expired
+    }
+
+    /// Check the configuration for security issues
+    pub fn audit(&self) -> Vec<AuditFinding> {
+        let mut findings = Vec::new();
+
+        if is_inline_secret(&self.email.smtp_password_command) {
+            findings.push(AuditFinding {
+                warning: "smtp_password_command prints the SMTP password stored in plain text".to_string(),
+                remediation: "Fetch the password from a keychain or password manager (e.g. 'pass show email/smtp')".to_string(),
+            });
+        }
+
+        if is_inline_secret(&self.perplexity.api_key_command) {
+            findings.push(AuditFinding {
+                warning: "api_key_command prints the Perplexity API key stored in plain text".to_string(),
+                remediation: "Fetch the key from a keychain or password manager (e.g. 'pass show perplexity/api-key')".to_string(),
+            });
+        }
+
+        if is_inline_secret(&self.openai.api_key_command) {
+            findings.push(AuditFinding {
+                warning: "api_key_command prints the OpenAI API key stored in plain text".to_string(),
+                remediation: "Fetch the key from a keychain or password manager (e.g. 'pass show openai/api-key')".to_string(),
+            });
+        }
+
+        if self.telegram.as_ref().is_some_and(|t| is_inline_secret(&t.bot_token_command)) {
+            findings.push(AuditFinding {
+                warning: "bot_token_command prints the Telegram bot token stored in plain text".to_string(),
+                remediation: "Fetch the token from a keychain or password manager (e.g. 'pass show telegram/bot')".to_string(),
+            });
+        }
+
+        if let Some(ref webhook) = self.webhook {
+            if webhook.secret_command.as_deref().is_some_and(is_inline_secret) {
+                findings.push(AuditFinding {
+                    warning: "secret_command prints the webhook signing secret stored in plain text".to_string(),
+                    remediation: "Fetch the secret from a keychain or password manager (e.g. 'pass show webhook/secret')".to_string(),
+                });
+            }
+            if webhook.headers.keys().any(|name| name.eq_ignore_ascii_case("authorization")) {
+                findings.push(AuditFinding {
+                    warning: "Webhook Authorization header is stored in plain text in the config".to_string(),
+                    remediation: "Sign requests with secret_command instead of a literal token header".to_string(),
+                });
+            }
+        }
+
+        for subject in &self.subjects {
+            if let Some(ref feed) = subject.rss_feed {
+                if feed.to_lowercase().starts_with("http://") {
+                    findings.push(AuditFinding {
+                        warning: format!("Subject '{}' watches a feed over unencrypted http: {}", subject.key, feed),
+                        remediation: "Use the https:// URL of the feed".to_string(),
+                    });
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+/// A security issue found by `Config::audit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditFinding {
+    pub warning: String,
+    pub remediation: String,
+}
+
+/// Whether a secret command just prints a literal value
+fn is_inline_secret(command: &str) -> bool {
+    matches!(
+        command.split_whitespace().next(),
+        Some("echo") | Some("printf")
+    )
 }
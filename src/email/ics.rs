@@ -9,6 +9,26 @@ pub struct IcsEvent {
     pub description: String,
     pub date: NaiveDate,
     pub url: Option<String>,
+    /// Add a VALARM firing this many minutes before the event starts
+    pub valarm_minutes_before: Option<u32>,
+    pub valarm_action: AlarmAction,
+}
+
+/// What a calendar client does when an event's reminder fires
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AlarmAction {
+    /// Show an alert on the device
+    #[default]
+    Display,
+    /// Email the reminder to these addresses
+    Email(Vec<String>),
+}
+
+/// Reminder added to every generated event, from settings.ics_reminder_minutes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcsReminder {
+    pub minutes_before: u32,
+    pub action: AlarmAction,
 }
 
 impl IcsEvent {
@@ -27,6 +47,7 @@ impl IcsEvent {
             .as_ref()
             .map(|u| format!("URL:{}\r\n", ics_escape(u)))
             .unwrap_or_default();
+        let alarm = self.valarm_minutes_before.map(|m| self.valarm(m)).unwrap_or_default();
 
         format!(
             "BEGIN:VCALENDAR\r\n\
@@ -41,6 +62,7 @@ impl IcsEvent {
              DESCRIPTION:{description}\r\n\
              SEQUENCE:{sequence}\r\n\
              {url_line}\
+             {alarm}\
              END:VEVENT\r\n\
              END:VCALENDAR\r\n",
             uid = self.uid,
@@ -50,6 +72,32 @@ impl IcsEvent {
             description = ics_escape(&self.description),
             sequence = self.sequence,
             url_line = url_line,
+            alarm = alarm,
+        )
+    }
+
+    /// VALARM component triggering `minutes` before the start
+    fn valarm(&self, minutes: u32) -> String {
+        let action = match &self.valarm_action {
+            AlarmAction::Display => "ACTION:DISPLAY\r\n".to_string(),
+            // EMAIL alarms need a summary and at least one attendee to send to
+            AlarmAction::Email(recipients) => {
+                let attendees: String = recipients
+                    .iter()
+                    .map(|r| format!("ATTENDEE:mailto:{}\r\n", r))
+                    .collect();
+                format!("ACTION:EMAIL\r\nSUMMARY:{}\r\n{}", ics_escape(&self.summary), attendees)
+            }
+        };
+        format!(
+            "BEGIN:VALARM\r\n\
+             {action}\
+             DESCRIPTION:{description}\r\n\
+             TRIGGER:-PT{minutes}M\r\n\
+             END:VALARM\r\n",
+            action = action,
+            description = ics_escape(&self.summary),
+            minutes = minutes,
         )
     }
 }
@@ -67,3 +115,33 @@ fn ics_escape(s: &str) -> String {
         .replace(',', "\\,")
         .replace('\n', "\\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(valarm_minutes_before: Option<u32>, valarm_action: AlarmAction) -> IcsEvent {
+        IcsEvent {
+            uid: "headsup-test@headsup".to_string(),
+            sequence: 1,
+            summary: "GTA 6 Release".to_string(),
+            description: "Out now".to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 5, 26).unwrap(),
+            url: None,
+            valarm_minutes_before,
+            valarm_action,
+        }
+    }
+
+    #[test]
+    fn test_valarm() {
+        assert!(!event(None, AlarmAction::Display).to_ics().contains("VALARM"));
+
+        let ics = event(Some(60), AlarmAction::Display).to_ics();
+        assert!(ics.contains("BEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:GTA 6 Release\r\nTRIGGER:-PT60M\r\nEND:VALARM\r\nEND:VEVENT"));
+
+        let ics = event(Some(1440), AlarmAction::Email(vec!["me@example.com".to_string()])).to_ics();
+        assert!(ics.contains("ACTION:EMAIL\r\nSUMMARY:GTA 6 Release\r\nATTENDEE:mailto:me@example.com\r\n"));
+        assert!(ics.contains("TRIGGER:-PT1440M\r\n"));
+    }
+}
@@ -3,46 +3,78 @@ mod templates;
 
 pub use templates::*;
 
-use crate::config::EmailConfig;
+use crate::config::{Config, EmailConfig, IcsReminderAction, SmtpAuthMechanism, SmtpTlsMode};
 use crate::error::{HeadsupError, Result};
 use lettre::message::header::ContentType;
 use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
-use lettre::transport::smtp::authentication::Credentials;
-use lettre::{Message, SmtpTransport, Transport};
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::{Message, SendmailTransport, SmtpTransport, Transport};
+use std::path::Path;
 use std::time::Duration;
 
+/// Reminder to add to ICS attachments, when settings.ics_reminder_minutes is set
+pub fn ics_reminder(config: &Config) -> Option<ics::IcsReminder> {
+    let action = match config.settings.ics_reminder_action {
+        IcsReminderAction::Display => ics::AlarmAction::Display,
+        IcsReminderAction::Email => ics::AlarmAction::Email(
+            config.email.to.iter()
+                .map(|to| to.parse::<Mailbox>().map(|m| m.email.to_string()).unwrap_or_else(|_| to.clone()))
+                .collect(),
+        ),
+    };
+    Some(ics::IcsReminder {
+        minutes_before: config.settings.ics_reminder_minutes?,
+        action,
+    })
+}
+
 /// Send an email using the configured SMTP settings
 pub fn send_email(config: &EmailConfig, content: &EmailContent) -> Result<()> {
-    // Get password from command
-    let password = crate::config::get_smtp_password(&config.smtp_password_command)?;
-
     // Parse addresses
-    let to_mailbox: Mailbox = config
-        .to
-        .parse()
-        .map_err(|e| HeadsupError::Email(format!("Invalid 'to' address: {}", e)))?;
     let from_mailbox: Mailbox = config
         .from
         .parse()
         .map_err(|e| HeadsupError::Email(format!("Invalid 'from' address: {}", e)))?;
 
-    let builder = Message::builder()
+    let mut builder = Message::builder()
         .from(from_mailbox)
-        .to(to_mailbox)
         .subject(&content.subject);
+    for mailbox in parse_mailboxes("to", &config.to).map_err(HeadsupError::Email)? {
+        builder = builder.to(mailbox);
+    }
+    for mailbox in parse_mailboxes("cc", &config.cc).map_err(HeadsupError::Email)? {
+        builder = builder.cc(mailbox);
+    }
+    for mailbox in parse_mailboxes("bcc", &config.bcc).map_err(HeadsupError::Email)? {
+        builder = builder.bcc(mailbox);
+    }
+
+    // With email.html, the plain text and HTML bodies are sent as alternatives
+    let html_body = content.html_body.as_ref().filter(|_| config.html);
 
-    // Build message: multipart if attachments present, plain text otherwise
-    let message = if content.attachments.is_empty() {
+    // Build message: multipart if attachments or HTML present, plain text otherwise
+    let message = if content.attachments.is_empty() && html_body.is_none() {
         builder
             .header(ContentType::TEXT_PLAIN)
             .body(content.body.clone())
             .map_err(|e| HeadsupError::Email(format!("Failed to build email: {}", e)))?
+    } else if content.attachments.is_empty() {
+        builder
+            .multipart(MultiPart::alternative_plain_html(
+                content.body.clone(),
+                html_body.cloned().unwrap_or_default(),
+            ))
+            .map_err(|e| HeadsupError::Email(format!("Failed to build email: {}", e)))?
     } else {
-        let text_part = SinglePart::builder()
-            .header(ContentType::TEXT_PLAIN)
-            .body(content.body.clone());
-
-        let mut multipart = MultiPart::mixed().singlepart(text_part);
+        let mut multipart = match html_body {
+            Some(html) => MultiPart::mixed()
+                .multipart(MultiPart::alternative_plain_html(content.body.clone(), html.clone())),
+            None => MultiPart::mixed().singlepart(
+                SinglePart::builder()
+                    .header(ContentType::TEXT_PLAIN)
+                    .body(content.body.clone()),
+            ),
+        };
 
         for attachment in &content.attachments {
             let content_type: ContentType = attachment
@@ -59,15 +91,31 @@ pub fn send_email(config: &EmailConfig, content: &EmailContent) -> Result<()> {
             .map_err(|e| HeadsupError::Email(format!("Failed to build email: {}", e)))?
     };
 
+    // A local MTA takes care of delivery and needs no SMTP credentials
+    if let Some(ref path) = config.sendmail_path {
+        return SendmailTransport::new_with_command(path)
+            .send(&message)
+            .map_err(|e| HeadsupError::Email(format!("Failed to send email via {}: {}", path.display(), e)));
+    }
+
+    // Get password from command
+    let password = crate::config::get_smtp_password(&config.smtp_password_command)?;
+
     // Build transport
     let creds = Credentials::new(config.smtp_username.clone(), password);
 
-    let mailer = SmtpTransport::starttls_relay(&config.smtp_host)
+    let relay = match config.smtp_tls_mode {
+        SmtpTlsMode::Starttls => SmtpTransport::starttls_relay(&config.smtp_host),
+        SmtpTlsMode::Tls => SmtpTransport::relay(&config.smtp_host),
+    };
+
+    let mailer = relay
         .map_err(|e| {
             HeadsupError::SmtpConnection(format!("Failed to create SMTP transport: {}", e))
         })?
         .port(config.smtp_port)
         .credentials(creds)
+        .authentication(vec![auth_mechanism(config.smtp_auth_mechanism)])
         .timeout(Some(Duration::from_secs(config.smtp_timeout_seconds)))
         .build();
 
@@ -79,9 +127,30 @@ pub fn send_email(config: &EmailConfig, content: &EmailContent) -> Result<()> {
     Ok(())
 }
 
+/// Parse every address of a recipient list, naming the list in the error
+fn parse_mailboxes(field: &str, addresses: &[String]) -> std::result::Result<Vec<Mailbox>, String> {
+    addresses
+        .iter()
+        .map(|address| {
+            address
+                .parse()
+                .map_err(|e| format!("Invalid '{}' address '{}': {}", field, address, e))
+        })
+        .collect()
+}
+
+/// Map the configured authentication mechanism to lettre's
+fn auth_mechanism(mechanism: SmtpAuthMechanism) -> Mechanism {
+    match mechanism {
+        SmtpAuthMechanism::Plain => Mechanism::Plain,
+        SmtpAuthMechanism::Login => Mechanism::Login,
+        SmtpAuthMechanism::XOAuth2 => Mechanism::Xoauth2,
+    }
+}
+
 /// Send a test email
 pub fn send_test_email(config: &EmailConfig) -> Result<()> {
-    let content = build_test_email();
+    let content = build_test_email(config);
     send_email(config, &content)
 }
 
@@ -98,22 +167,44 @@ pub fn validate_email_config(config: &EmailConfig) -> Result<()> {
             "Email 'from' address is required".to_string(),
         ));
     }
-    if config.smtp_host.is_empty() {
+    if let Some(ref path) = config.sendmail_path {
+        if !is_executable(path) {
+            return Err(HeadsupError::ConfigInvalid(format!(
+                "sendmail_path {} is not an executable file",
+                path.display()
+            )));
+        }
+    } else if config.smtp_host.is_empty() {
         return Err(HeadsupError::ConfigInvalid(
             "SMTP host is required".to_string(),
         ));
-    }
-    if config.smtp_password_command.is_empty() {
+    } else if config.smtp_password_command.is_empty() {
         return Err(HeadsupError::ConfigInvalid(
             "SMTP password command is required".to_string(),
         ));
+    } else if crate::config::pass_entry(&config.smtp_password_command) == Some("") {
+        return Err(HeadsupError::ConfigInvalid(
+            "smtp_password_command 'pass:' names no entry (e.g. pass:email/smtp)".to_string(),
+        ));
+    } else if crate::config::pass_entry(&config.smtp_password_command).is_some() && !crate::config::on_path("pass") {
+        return Err(HeadsupError::ConfigInvalid(
+            "smtp_password_command uses pass:, but pass is not on PATH (see https://www.passwordstore.org)".to_string(),
+        ));
+    }
+
+    if config.sendmail_path.is_none()
+        && config.smtp_auth_mechanism == SmtpAuthMechanism::XOAuth2
+        && config.smtp_tls_mode != SmtpTlsMode::Tls
+    {
+        tracing::warn!(
+            "XOAUTH2 sends a bearer token; consider smtp_tls_mode = \"tls\" to use implicit TLS"
+        );
     }
 
     // Validate email format
-    let _: Mailbox = config
-        .to
-        .parse()
-        .map_err(|e| HeadsupError::ConfigInvalid(format!("Invalid 'to' address: {}", e)))?;
+    parse_mailboxes("to", &config.to).map_err(HeadsupError::ConfigInvalid)?;
+    parse_mailboxes("cc", &config.cc).map_err(HeadsupError::ConfigInvalid)?;
+    parse_mailboxes("bcc", &config.bcc).map_err(HeadsupError::ConfigInvalid)?;
     let _: Mailbox = config
         .from
         .parse()
@@ -121,3 +212,19 @@ pub fn validate_email_config(config: &EmailConfig) -> Result<()> {
 
     Ok(())
 }
+
+/// Whether the path is a file the current user may execute
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
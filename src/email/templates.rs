@@ -1,7 +1,11 @@
 use crate::claude::{QuestionResponse, RecurringResponse, ReleaseResponse};
-use crate::config::Subject;
-use crate::email::ics::{self, IcsEvent};
-use crate::state::{DatePrecision, PendingNotification, QuestionState, RecurringState, ReleaseState};
+use crate::config::{EmailConfig, Subject};
+use crate::email::ics::{self, IcsEvent, IcsReminder};
+use crate::state::{
+    hypothesis_event, Confidence, DatePrecision, PendingNotification, QuestionState, RecurringState,
+    ReleaseState,
+};
+use crate::util::html::{html_escape, is_web_url};
 
 const SEPARATOR: &str = "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━";
 const FOOTER: &str = "This is an automated message from Headsup.";
@@ -10,6 +14,8 @@ const FOOTER: &str = "This is an automated message from Headsup.";
 pub struct EmailContent {
     pub subject: String,
     pub body: String,
+    /// HTML version of the body, sent alongside the plain text when email.html is enabled
+    pub html_body: Option<String>,
     pub attachments: Vec<EmailAttachment>,
 }
 
@@ -25,6 +31,7 @@ fn build_release_ics_attachment(
     subject: &Subject,
     response: &ReleaseResponse,
     previous_state: Option<&ReleaseState>,
+    reminder: Option<&IcsReminder>,
 ) -> Option<EmailAttachment> {
     if response.release_date_precision != DatePrecision::Exact {
         return None;
@@ -48,6 +55,8 @@ fn build_release_ics_attachment(
         description: response.summary.clone(),
         date,
         url: response.source_url.clone(),
+        valarm_minutes_before: reminder.map(|r| r.minutes_before),
+        valarm_action: reminder.map(|r| r.action.clone()).unwrap_or_default(),
     };
 
     Some(EmailAttachment {
@@ -62,6 +71,7 @@ fn build_recurring_ics_attachment(
     subject: &Subject,
     response: &RecurringResponse,
     previous_state: Option<&RecurringState>,
+    reminder: Option<&IcsReminder>,
 ) -> Option<EmailAttachment> {
     if response.date_precision != DatePrecision::Exact {
         return None;
@@ -92,6 +102,8 @@ fn build_recurring_ics_attachment(
         description: response.summary.clone(),
         date,
         url: response.source_url.clone(),
+        valarm_minutes_before: reminder.map(|r| r.minutes_before),
+        valarm_action: reminder.map(|r| r.action.clone()).unwrap_or_default(),
     };
 
     Some(EmailAttachment {
@@ -101,6 +113,52 @@ fn build_recurring_ics_attachment(
     })
 }
 
+/// Colored inline badge for a confidence level
+fn html_confidence_badge(confidence: &Confidence) -> String {
+    let color = match confidence {
+        Confidence::Official => "#1a7f37",
+        Confidence::Reliable => "#0969da",
+        Confidence::Rumor => "#bc4c00",
+        Confidence::Speculation | Confidence::Unknown => "#57606a",
+    };
+    format!(
+        r#"<span style="background:{};color:#fff;border-radius:10px;padding:2px 8px;font-size:12px">{}</span>"#,
+        color, confidence
+    )
+}
+
+/// Clickable source link, or a note that there is none
+fn html_source_link(source_url: Option<&str>) -> String {
+    match source_url {
+        Some(url) if is_web_url(url) => format!(r#"<a href="{0}">{0}</a>"#, html_escape(url)),
+        Some(url) => html_escape(url),
+        None => "No source URL available".to_string(),
+    }
+}
+
+/// Wrap a heading and (label, HTML value) sections in a minimal styled document
+fn html_document(heading: &str, sections: &[(&str, String)]) -> String {
+    let sections: String = sections
+        .iter()
+        .map(|(label, value)| {
+            format!(
+                r#"<p style="margin:16px 0 4px;color:#57606a;font-size:13px">{}</p><div>{}</div>"#,
+                label, value
+            )
+        })
+        .collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html><body style="font-family:-apple-system,Helvetica,Arial,sans-serif;color:#1f2328;max-width:600px">
+<h2 style="margin-bottom:4px">{}</h2>
+{}
+<hr style="border:none;border-top:1px solid #d0d7de;margin-top:24px">
+<p style="color:#57606a;font-size:12px">{}</p>
+</body></html>"#,
+        heading, sections, FOOTER
+    )
+}
+
 /// Simple slug helper for filenames
 fn slug(s: &str) -> String {
     s.chars()
@@ -115,6 +173,7 @@ pub fn build_release_email(
     subject: &Subject,
     response: &ReleaseResponse,
     previous_state: Option<&ReleaseState>,
+    reminder: Option<&IcsReminder>,
 ) -> EmailContent {
     let event_type = determine_release_event_type(response, previous_state);
     let email_subject = format!("[Headsup] {} - {}", subject.name, event_type);
@@ -160,13 +219,28 @@ Confidence: {confidence}
         footer = FOOTER
     );
 
-    let attachments = build_release_ics_attachment(subject, response, previous_state)
+    let previous_html = previous_state
+        .and_then(|state| state.known_release_date.as_ref().map(|date| (date, &state.confidence)))
+        .map(|(date, confidence)| format!("{} ({})", html_escape(date), confidence))
+        .unwrap_or_else(|| "No release date was previously known.".to_string());
+    let html_body = html_document(
+        &format!("<b>{}</b> - {}", html_escape(&subject.name), event_type),
+        &[
+            ("New Information", html_escape(&response.summary)),
+            ("Previous Status", previous_html),
+            ("Source", html_source_link(response.source_url.as_deref())),
+            ("Confidence", html_confidence_badge(&response.confidence)),
+        ],
+    );
+
+    let attachments = build_release_ics_attachment(subject, response, previous_state, reminder)
         .into_iter()
         .collect();
 
     EmailContent {
         subject: email_subject,
         body,
+        html_body: Some(html_body),
         attachments,
     }
 }
@@ -180,8 +254,7 @@ pub fn build_question_email(
     let event_type = determine_question_event_type(response, previous_state);
     let email_subject = format!("[Headsup] {} - {}", subject.name, event_type);
 
-    let question = subject.question.as_ref()
-        .map(|q| q.as_str())
+    let question = subject.question.as_deref()
         .unwrap_or("Unknown question");
 
     let previous_info = if let Some(state) = previous_state {
@@ -198,6 +271,22 @@ pub fn build_question_email(
         .map(|a| format!("Answer:\n  {}", a))
         .unwrap_or_else(|| "Answer:\n  No answer found.".to_string());
 
+    let expected_answer = previous_state
+        .and_then(|s| s.expected_answer.as_ref())
+        .or(subject.initial_expected_answer.as_ref());
+    let hypothesis_info = match (expected_answer, response.found_answer.as_ref()) {
+        (Some(expected), Some(found)) => {
+            let outcome = if hypothesis_event(expected, found) == "hypothesis_confirmed" {
+                "confirmed"
+            } else {
+                "contradicted"
+            };
+            format!("Your Hypothesis:\n  {} ({})\n\n", expected, outcome)
+        }
+        (Some(expected), None) => format!("Your Hypothesis:\n  {} (not yet known)\n\n", expected),
+        _ => String::new(),
+    };
+
     let source_info = response.source_url.as_ref()
         .map(|url| format!("Source:\n  {}", url))
         .unwrap_or_else(|| "Source:\n  No source URL available".to_string());
@@ -212,7 +301,7 @@ Question:
 
 {answer_info}
 
-{previous_info}
+{hypothesis_info}{previous_info}
 
 {source_info}
 
@@ -226,15 +315,44 @@ Confidence: {confidence}
         event_type = event_type,
         question = question,
         answer_info = answer_info,
+        hypothesis_info = hypothesis_info,
         previous_info = previous_info,
         source_info = source_info,
         confidence = response.confidence,
         footer = FOOTER
     );
 
+    let mut sections = vec![
+        ("Question", html_escape(question)),
+        (
+            "Answer",
+            response.found_answer.as_deref().map(html_escape).unwrap_or_else(|| "No answer found.".to_string()),
+        ),
+    ];
+    if let Some(expected) = expected_answer {
+        let outcome = match response.found_answer.as_ref() {
+            Some(found) if hypothesis_event(expected, found) == "hypothesis_confirmed" => "confirmed",
+            Some(_) => "contradicted",
+            None => "not yet known",
+        };
+        sections.push(("Your Hypothesis", format!("{} ({})", html_escape(expected), outcome)));
+    }
+    let previous_html = previous_state
+        .and_then(|state| state.current_answer.as_ref().map(|answer| (answer, &state.confidence)))
+        .map(|(answer, confidence)| format!("{} ({})", html_escape(answer), confidence))
+        .unwrap_or_else(|| "No answer was previously known.".to_string());
+    sections.push(("Previous Status", previous_html));
+    sections.push(("Source", html_source_link(response.source_url.as_deref())));
+    sections.push(("Confidence", html_confidence_badge(&response.confidence)));
+    let html_body = html_document(
+        &format!("<b>{}</b> - {}", html_escape(&subject.name), event_type),
+        &sections,
+    );
+
     EmailContent {
         subject: email_subject,
         body,
+        html_body: Some(html_body),
         attachments: vec![],
     }
 }
@@ -244,6 +362,7 @@ pub fn build_recurring_email(
     subject: &Subject,
     response: &RecurringResponse,
     previous_state: Option<&RecurringState>,
+    reminder: Option<&IcsReminder>,
 ) -> EmailContent {
     let event_type = determine_recurring_event_type(response, previous_state);
     let email_subject = format!("[Headsup] {} - {}", subject.name, event_type);
@@ -299,22 +418,43 @@ Details:
         footer = FOOTER
     );
 
-    let attachments = build_recurring_ics_attachment(subject, response, previous_state)
+    let previous_html = previous_state
+        .and_then(|state| state.last_occurrence_date.as_deref())
+        .map(html_escape)
+        .unwrap_or_else(|| "No previous event recorded.".to_string());
+    let html_body = html_document(
+        &format!("<b>{}</b> - {}", html_escape(&subject.name), event_type),
+        &[
+            ("Event", html_escape(event_name)),
+            ("Date", html_escape(response.next_occurrence_date.as_deref().unwrap_or("Unknown"))),
+            ("Details", html_escape(&response.summary)),
+            ("Previous Event", previous_html),
+            ("Source", html_source_link(response.source_url.as_deref())),
+        ],
+    );
+
+    let attachments = build_recurring_ics_attachment(subject, response, previous_state, reminder)
         .into_iter()
         .collect();
 
     EmailContent {
         subject: email_subject,
         body,
+        html_body: Some(html_body),
         attachments,
     }
 }
 
 /// Build a digest email combining multiple notifications
-pub fn build_digest_email(notifications: &[PendingNotification], subjects: &[Subject]) -> EmailContent {
+pub fn build_digest_email(
+    notifications: &[PendingNotification],
+    subjects: &[Subject],
+    reminder: Option<&IcsReminder>,
+) -> EmailContent {
     let email_subject = format!("[Headsup] {} Updates", notifications.len());
 
     let mut items = Vec::new();
+    let mut html_items = Vec::new();
     let mut attachments = Vec::new();
 
     for notif in notifications {
@@ -327,20 +467,30 @@ pub fn build_digest_email(notifications: &[PendingNotification], subjects: &[Sub
             notif.event_type,
             notif.summary
         ));
+        html_items.push(format!(
+            "<li style=\"margin-bottom:12px\"><b>{}</b> ({}) {}<br>{}{}</li>",
+            html_escape(subject_name),
+            html_escape(&notif.event_type),
+            notif.confidence.as_ref().map(html_confidence_badge).unwrap_or_default(),
+            html_escape(&notif.summary),
+            notif.source_url.as_deref()
+                .map(|url| format!("<br>{}", html_source_link(Some(url))))
+                .unwrap_or_default()
+        ));
 
         // Try to generate ICS for applicable notification types
         if let Some(subj) = subject {
             match notif.event_type.as_str() {
                 "release_update" => {
                     if let Ok(response) = serde_json::from_value::<ReleaseResponse>(notif.payload.clone()) {
-                        if let Some(att) = build_release_ics_attachment(subj, &response, None) {
+                        if let Some(att) = build_release_ics_attachment(subj, &response, None, reminder) {
                             attachments.push(att);
                         }
                     }
                 }
                 "recurring_update" => {
                     if let Ok(response) = serde_json::from_value::<RecurringResponse>(notif.payload.clone()) {
-                        if let Some(att) = build_recurring_ics_attachment(subj, &response, None) {
+                        if let Some(att) = build_recurring_ics_attachment(subj, &response, None, reminder) {
                             attachments.push(att);
                         }
                     }
@@ -366,15 +516,27 @@ Headsup - {count} Updates
         footer = FOOTER
     );
 
+    let html_body = html_document(
+        &format!("Headsup - {} Updates", notifications.len()),
+        &[("Updates", format!("<ul style=\"padding-left:20px\">{}</ul>", html_items.concat()))],
+    );
+
     EmailContent {
         subject: email_subject,
         body,
+        html_body: Some(html_body),
         attachments,
     }
 }
 
 /// Build a test email
-pub fn build_test_email() -> EmailContent {
+pub fn build_test_email(config: &EmailConfig) -> EmailContent {
+    let recipients = format!(
+        "Sent to {} recipient(s), {} in cc and {} in bcc.",
+        config.to.len(),
+        config.cc.len(),
+        config.bcc.len()
+    );
     EmailContent {
         subject: "[Headsup] Test Email".to_string(),
         body: format!(
@@ -386,12 +548,25 @@ This is a test email to verify your SMTP configuration is working correctly.
 
 If you're reading this, your email settings are configured properly!
 
+{recipients}
+
 {separator}
 
 {footer}"#,
             separator = SEPARATOR,
+            recipients = recipients,
             footer = FOOTER
         ),
+        html_body: Some(html_document(
+            "Headsup - Test Email",
+            &[(
+                "SMTP",
+                format!(
+                    "If you're reading this, your email settings are configured properly!<br>{}",
+                    recipients
+                ),
+            )],
+        )),
         attachments: vec![],
     }
 }
@@ -1,3 +1,4 @@
+use crate::config::SubjectType;
 use std::process::ExitCode;
 use thiserror::Error;
 
@@ -36,6 +37,16 @@ pub enum HeadsupError {
     #[error("State file locked by another process")]
     StateLocked,
 
+    #[error("Config file locked by another process")]
+    ConfigLocked,
+
+    #[error("State for '{subject_key}' is a {found} subject but config says {expected} (run 'headsup state verify --fix')")]
+    SubjectTypeMismatch {
+        subject_key: String,
+        expected: SubjectType,
+        found: SubjectType,
+    },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -57,12 +68,27 @@ pub enum HeadsupError {
     #[error("Claude response parse error: {0}")]
     ClaudeParseError(String),
 
+    #[error("Response did not cite a source URL")]
+    MissingSourceUrl,
+
     #[error("Perplexity error: {0}")]
     Perplexity(String),
 
     #[error("Perplexity timeout after {0} seconds")]
     PerplexityTimeout(u64),
 
+    #[error("OpenAI error: {0}")]
+    OpenAi(String),
+
+    #[error("OpenAI timeout after {0} seconds")]
+    OpenAiTimeout(u64),
+
+    #[error("Ollama error: {0}")]
+    Ollama(String),
+
+    #[error("Ollama timeout after {0} seconds")]
+    OllamaTimeout(u64),
+
     #[error("Email error: {0}")]
     Email(String),
 
@@ -78,6 +104,27 @@ pub enum HeadsupError {
     #[error("Password command failed: {0}")]
     PasswordCommand(String),
 
+    #[error("Secret Service error: {0}")]
+    SecretService(String),
+
+    #[error("Feed error: {0}")]
+    Feed(String),
+
+    #[error("Webhook error: {0}")]
+    Webhook(String),
+
+    #[error("Telegram error: {0}")]
+    Telegram(String),
+
+    #[error("Cloud sync error: {0}")]
+    Sync(String),
+
+    #[error("Metrics error: {0}")]
+    Metrics(String),
+
+    #[error("No internet connection after {0} seconds")]
+    NoConnectivity(u64),
+
     #[error("User cancelled operation")]
     UserCancelled,
 }
@@ -95,19 +142,75 @@ impl HeadsupError {
             | HeadsupError::Json(_)
             | HeadsupError::State(_)
             | HeadsupError::StateLocked
+            | HeadsupError::ConfigLocked
+            | HeadsupError::SubjectTypeMismatch { .. }
             | HeadsupError::SubjectNotFound(_)
             | HeadsupError::SubjectKeyExists(_)
             | HeadsupError::PasswordCommand(_)
+            | HeadsupError::SecretService(_)
+            | HeadsupError::Feed(_)
+            | HeadsupError::Webhook(_)
+            | HeadsupError::Telegram(_)
+            | HeadsupError::Sync(_)
+            | HeadsupError::Metrics(_)
             | HeadsupError::UserCancelled => ExitStatus::GeneralError,
 
             HeadsupError::Email(_) | HeadsupError::SmtpConnection(_) => ExitStatus::EmailDeliveryFailed,
 
             HeadsupError::ClaudeTimeout(_)
-            | HeadsupError::PerplexityTimeout(_) => ExitStatus::Timeout,
+            | HeadsupError::PerplexityTimeout(_)
+            | HeadsupError::OpenAiTimeout(_)
+            | HeadsupError::OllamaTimeout(_)
+            | HeadsupError::NoConnectivity(_) => ExitStatus::Timeout,
 
             HeadsupError::Claude(_)
             | HeadsupError::ClaudeParseError(_)
-            | HeadsupError::Perplexity(_) => ExitStatus::GeneralError,
+            | HeadsupError::MissingSourceUrl
+            | HeadsupError::Perplexity(_)
+            | HeadsupError::OpenAi(_)
+            | HeadsupError::Ollama(_) => ExitStatus::GeneralError,
+        }
+    }
+}
+
+impl HeadsupError {
+    /// Short machine-readable name of the error kind
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            HeadsupError::Config(_) => "config",
+            HeadsupError::ConfigNotFound(_) => "config_not_found",
+            HeadsupError::ConfigInvalid(_) => "config_invalid",
+            HeadsupError::State(_) => "state",
+            HeadsupError::StateLocked => "state_locked",
+            HeadsupError::ConfigLocked => "config_locked",
+            HeadsupError::SubjectTypeMismatch { .. } => "subject_type_mismatch",
+            HeadsupError::Io(_) => "io",
+            HeadsupError::TomlParse(_) => "toml_parse",
+            HeadsupError::TomlSerialize(_) => "toml_serialize",
+            HeadsupError::Json(_) => "json",
+            HeadsupError::Claude(_) => "claude",
+            HeadsupError::ClaudeTimeout(_) => "claude_timeout",
+            HeadsupError::ClaudeParseError(_) => "claude_parse_error",
+            HeadsupError::MissingSourceUrl => "missing_source_url",
+            HeadsupError::Perplexity(_) => "perplexity",
+            HeadsupError::PerplexityTimeout(_) => "perplexity_timeout",
+            HeadsupError::OpenAi(_) => "openai",
+            HeadsupError::OpenAiTimeout(_) => "openai_timeout",
+            HeadsupError::Ollama(_) => "ollama",
+            HeadsupError::OllamaTimeout(_) => "ollama_timeout",
+            HeadsupError::Email(_) => "email",
+            HeadsupError::SmtpConnection(_) => "smtp_connection",
+            HeadsupError::SubjectNotFound(_) => "subject_not_found",
+            HeadsupError::SubjectKeyExists(_) => "subject_key_exists",
+            HeadsupError::PasswordCommand(_) => "password_command",
+            HeadsupError::SecretService(_) => "secret_service",
+            HeadsupError::Feed(_) => "feed",
+            HeadsupError::Webhook(_) => "webhook",
+            HeadsupError::Telegram(_) => "telegram",
+            HeadsupError::Sync(_) => "sync",
+            HeadsupError::Metrics(_) => "metrics",
+            HeadsupError::NoConnectivity(_) => "no_connectivity",
+            HeadsupError::UserCancelled => "user_cancelled",
         }
     }
 }
@@ -0,0 +1,141 @@
+use crate::error::{HeadsupError, Result};
+use reqwest::Client;
+use std::time::Duration;
+use url::Url;
+
+/// Timeout for fetching a feed
+const FEED_TIMEOUT_SECS: u64 = 30;
+
+/// Fetch an RSS/Atom feed and return its title
+pub async fn fetch_feed_title(feed_url: &str) -> Result<String> {
+    let (url, body) = fetch_document(feed_url).await?;
+
+    Ok(extract_title(&body)
+        .or_else(|| url.host_str().map(|h| h.to_string()))
+        .unwrap_or_else(|| feed_url.to_string()))
+}
+
+/// Fetch a web page and return its <title>
+pub async fn fetch_page_title(page_url: &str) -> Result<String> {
+    let (url, body) = fetch_document(page_url).await?;
+
+    extract_title(&body)
+        .ok_or_else(|| HeadsupError::Feed(format!("No title found at {}", url)))
+}
+
+/// Fetch an http(s) document and return its parsed URL and body
+async fn fetch_document(feed_url: &str) -> Result<(Url, String)> {
+    let url = parse_feed_url(feed_url)?;
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(FEED_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| HeadsupError::Feed(format!("Failed to create HTTP client: {}", e)))?;
+
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|e| HeadsupError::Feed(format!("Failed to fetch {}: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(HeadsupError::Feed(format!(
+            "Feed returned status {}",
+            response.status()
+        )));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| HeadsupError::Feed(format!("Failed to read feed: {}", e)))?;
+
+    Ok((url, body))
+}
+
+/// Parse and validate a feed URL (http or https only)
+pub fn parse_feed_url(feed_url: &str) -> Result<Url> {
+    let url = Url::parse(feed_url)
+        .map_err(|e| HeadsupError::Feed(format!("Invalid feed URL '{}': {}", feed_url, e)))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(HeadsupError::Feed(format!(
+            "Unsupported feed URL scheme '{}'",
+            scheme
+        ))),
+    }
+}
+
+/// Extract the first <title> element of an RSS/Atom or HTML document
+fn extract_title(xml: &str) -> Option<String> {
+    let start = xml.find("<title")?;
+    let content_start = start + xml[start..].find('>')? + 1;
+    let content_end = content_start + xml[content_start..].find("</title>")?;
+    let mut rest = &xml[content_start..content_end];
+
+    // CDATA sections are literal text; entities are only decoded outside them
+    let mut title = String::new();
+    while let Some(cdata_start) = rest.find("<![CDATA[") {
+        title.push_str(&decode_entities(&rest[..cdata_start]));
+        let cdata = &rest[cdata_start + "<![CDATA[".len()..];
+        let cdata_end = cdata.find("]]>").unwrap_or(cdata.len());
+        title.push_str(&cdata[..cdata_end]);
+        rest = cdata.get(cdata_end + "]]>".len()..).unwrap_or_default();
+    }
+    title.push_str(&decode_entities(rest));
+    let title = title.trim();
+
+    if title.is_empty() {
+        None
+    } else {
+        Some(title.to_string())
+    }
+}
+
+/// Decode the predefined XML entities (and the common `&#39;`)
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_title_atom() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>tag:github.com,2008:https://github.com/neovim/neovim/releases</id>
+  <title>Release notes from neovim</title>
+  <entry><title>Nightly</title></entry>
+</feed>"#;
+        assert_eq!(extract_title(xml).as_deref(), Some("Release notes from neovim"));
+    }
+
+    #[test]
+    fn test_extract_title_rss_cdata() {
+        let xml = "<rss><channel><title type=\"text\"><![CDATA[Tom &amp; Jerry News]]></title></channel></rss>";
+        assert_eq!(extract_title(xml).as_deref(), Some("Tom &amp; Jerry News"));
+
+        let xml = "<rss><channel><title>R&amp;D <![CDATA[<b>&lt;</b>]]></title></channel></rss>";
+        assert_eq!(extract_title(xml).as_deref(), Some("R&D <b>&lt;</b>"));
+    }
+
+    #[test]
+    fn test_extract_title_html() {
+        let html = "<html><head><meta charset=\"utf-8\"><title>\n  Grand Theft Auto V on Steam\n</title></head></html>";
+        assert_eq!(extract_title(html).as_deref(), Some("Grand Theft Auto V on Steam"));
+    }
+
+    #[test]
+    fn test_parse_feed_url() {
+        assert!(parse_feed_url("https://example.com/feed.atom").is_ok());
+        assert!(parse_feed_url("ftp://example.com/feed").is_err());
+        assert!(parse_feed_url("not a url").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::config::{Config, Subject, SubjectType};
+use crate::state::{ReleaseStatus, State, SubjectState};
+use std::fmt::Write;
+
+/// Render subjects and their `related_subjects` links as a Graphviz DOT graph
+pub fn generate_dot_graph(config: &Config, state: &State) -> String {
+    let mut out = String::from("digraph headsup {\n    rankdir=LR;\n    node [style=filled, fontname=\"Helvetica\"];\n\n");
+
+    for subject in &config.subjects {
+        let _ = writeln!(
+            out,
+            "    \"{}\" [label=\"{}\", shape={}, fillcolor=\"{}\"];",
+            dot_escape(&subject.key),
+            dot_escape(&subject.name),
+            node_shape(subject.subject_type),
+            node_color(subject, state.subjects.get(&subject.id)),
+        );
+    }
+
+    let edges: Vec<(&str, &str)> = config
+        .subjects
+        .iter()
+        .flat_map(|s| s.related_subjects.iter().map(move |r| (s.key.as_str(), r.as_str())))
+        .filter(|(_, to)| config.subjects.iter().any(|s| s.key == *to))
+        .collect();
+    if !edges.is_empty() {
+        out.push('\n');
+    }
+    for (from, to) in edges {
+        let _ = writeln!(out, "    \"{}\" -> \"{}\";", dot_escape(from), dot_escape(to));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn node_shape(subject_type: SubjectType) -> &'static str {
+    match subject_type {
+        SubjectType::Release => "box",
+        SubjectType::Question => "ellipse",
+        SubjectType::Recurring => "hexagon",
+    }
+}
+
+/// Fill color following the status colors of the HTML report
+fn node_color(subject: &Subject, state: Option<&SubjectState>) -> &'static str {
+    if !subject.enabled {
+        return "#dddddd";
+    }
+    if subject.last_error.is_some() {
+        return "#fce8e6";
+    }
+    match state {
+        Some(SubjectState::Release(rs)) => match rs.status {
+            ReleaseStatus::Released => "#e6f4ea",
+            ReleaseStatus::Announced => "#e8f0fe",
+            ReleaseStatus::Delayed => "#fef7e0",
+            ReleaseStatus::Cancelled => "#fce8e6",
+            ReleaseStatus::Unknown => "#f8f9fa",
+        },
+        Some(SubjectState::Question(qs)) if qs.current_answer.is_some() => "#e6f4ea",
+        Some(SubjectState::Question(_)) => "#fef7e0",
+        Some(SubjectState::Recurring(rs)) if rs.next_occurrence_date.is_some() => "#e8f0fe",
+        _ => "#f8f9fa",
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_dot_graph() {
+        let mut config = Config::default_with_email("user@example.com");
+        config.subjects.push(Subject {
+            key: "gta6-director".to_string(),
+            name: "GTA 6 \"director\"".to_string(),
+            subject_type: SubjectType::Question,
+            related_subjects: vec!["gta6".to_string(), "missing".to_string()],
+            ..Default::default()
+        });
+        config.subjects.push(Subject {
+            key: "gta6".to_string(),
+            name: "GTA 6".to_string(),
+            ..Default::default()
+        });
+
+        let dot = generate_dot_graph(&config, &State::default());
+        assert!(dot.starts_with("digraph headsup {"));
+        assert!(dot.contains("\"gta6-director\" [label=\"GTA 6 \\\"director\\\"\", shape=ellipse"));
+        assert!(dot.contains("\"gta6-director\" -> \"gta6\";"));
+        assert!(!dot.contains("\"missing\""));
+    }
+}
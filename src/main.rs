@@ -1,14 +1,26 @@
+mod cache;
 mod cli;
 mod claude;
 mod config;
 mod email;
 mod error;
+mod feed;
+mod graph;
+mod metrics;
+mod ollama;
+mod openai;
 mod perplexity;
+mod report;
 mod state;
+mod sync;
+mod telegram;
+mod templates;
 mod ui;
+mod util;
+mod webhook;
 
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{CheckOptions, CheckOutput, Cli, Commands, NotifyOptions};
 use error::{ExitStatus, HeadsupError};
 use std::process::ExitCode;
 
@@ -41,18 +53,101 @@ async fn run_command(cli: Cli) -> Result<ExitStatus, HeadsupError> {
     match cli.command {
         Some(Commands::Check {
             subject,
+            tag,
+            group,
             dry_run: cmd_dry_run,
             force,
             no_notify,
+            webhook_only,
+            benchmark,
+            ai_timeout,
+            fail_on_no_sources,
+            annotate_response,
+            max_latency,
+            report,
+            save_fixtures,
+            result_file,
+            tag_all,
+            webhook_on_failure,
+            cloud_sync,
+            rate_limit_backend,
+            parallel,
+            emit_metrics,
+            metrics_file,
+            status_file,
+            status_format,
+            wait_for_internet,
+            timeout,
+            cache_prompts,
+            save_responses,
+            output,
+            pushgateway_url,
+            json,
+            compare_backends,
+            no_fallback,
         }) => {
-            cli::run_check(subject, dry_run || cmd_dry_run, force, no_notify).await
+            cli::run_check(CheckOptions {
+                subject_key: subject,
+                tag,
+                group,
+                dry_run: dry_run || cmd_dry_run,
+                force,
+                no_notify,
+                webhook_only,
+                benchmark,
+                ai_timeout,
+                fail_on_no_sources,
+                annotate_response,
+                max_latency,
+                report,
+                save_fixtures,
+                result_file,
+                tag_all,
+                webhook_on_failure,
+                cloud_sync,
+                rate_limit_backend,
+                parallel,
+                metrics: emit_metrics.zip(metrics_file),
+                status_file,
+                status_format,
+                wait_for_internet: wait_for_internet.then_some(timeout),
+                cache_prompts,
+                save_responses,
+                prometheus_push: output == Some(CheckOutput::PrometheusPush),
+                pushgateway_url,
+                json,
+                compare_backends,
+                no_fallback,
+            })
+            .await
         }
 
         Some(Commands::Notify {
             dry_run: cmd_dry_run,
             digest,
+            filter_confidence,
+            group_by_date,
+            consolidate,
+            format,
+            url,
+            webhook_only,
+            body_only,
+            subject,
+            subject_line,
         }) => {
-            cli::run_notify(dry_run || cmd_dry_run, digest)
+            cli::run_notify(NotifyOptions {
+                dry_run: dry_run || cmd_dry_run,
+                digest,
+                filter_confidence,
+                group_by_date,
+                consolidate,
+                format,
+                webhook_url: url,
+                webhook_only,
+                body_only: subject.filter(|_| body_only),
+                subject_line,
+            })
+            .await
         }
 
         Some(Commands::Subjects { command }) => {
@@ -70,17 +165,31 @@ async fn run_command(cli: Cli) -> Result<ExitStatus, HeadsupError> {
             Ok(ExitStatus::Success)
         }
 
+        Some(Commands::Cache { command }) => {
+            cli::run_cache(command)?;
+            Ok(ExitStatus::Success)
+        }
+
         Some(Commands::History {
+            command,
             subject,
             limit,
+            since,
+            until,
+            format,
             json,
         }) => {
-            cli::run_history(subject, limit, json)?;
+            cli::run_history(command, subject, limit, since, until, format, json)?;
             Ok(ExitStatus::Success)
         }
 
-        Some(Commands::Init { force, email }) => {
-            cli::run_init(force, email)?;
+        Some(Commands::Stats { by_day, last_n_days }) => {
+            cli::run_stats(by_day, last_n_days)?;
+            Ok(ExitStatus::Success)
+        }
+
+        Some(Commands::Init { force, email, from_export }) => {
+            cli::run_init(force, email, from_export)?;
             Ok(ExitStatus::Success)
         }
 
@@ -89,13 +198,18 @@ async fn run_command(cli: Cli) -> Result<ExitStatus, HeadsupError> {
             Ok(ExitStatus::Success)
         }
 
+        Some(Commands::TestTelegram) => {
+            run_test_telegram().await?;
+            Ok(ExitStatus::Success)
+        }
+
         None => {
             // No command - check if config exists, run init if not
             if !config::config_exists()? {
                 ui::print_info("Welcome to Headsup!");
                 ui::print_info("Let's set up your configuration.");
                 println!();
-                cli::run_init(false, None)?;
+                cli::run_init(false, None, None)?;
             } else {
                 // Show help
                 use clap::CommandFactory;
@@ -116,7 +230,20 @@ fn run_test_email() -> Result<(), HeadsupError> {
     ui::print_info("Sending test email...");
     email::send_test_email(&config.email)?;
 
-    ui::print_success(&format!("Test email sent to {}", config.email.to));
+    ui::print_success(&format!("Test email sent to {}", config.email.to.join(", ")));
+    Ok(())
+}
+
+async fn run_test_telegram() -> Result<(), HeadsupError> {
+    let config = config::load_config()?;
+    let telegram = config.telegram.as_ref().ok_or_else(|| {
+        HeadsupError::Config("No [telegram] section in the config".to_string())
+    })?;
+
+    ui::print_info("Sending test message...");
+    telegram::send_test_message(telegram).await?;
+
+    ui::print_success(&format!("Test message sent to chat {}", telegram.chat_id));
     Ok(())
 }
 
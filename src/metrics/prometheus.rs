@@ -0,0 +1,116 @@
+use crate::cli::CheckResult;
+use crate::config::Config;
+use crate::state::State;
+use std::collections::HashMap;
+use std::fmt::Write;
+use uuid::Uuid;
+
+/// Render the metrics of a check run in the Prometheus text exposition format
+pub fn render(
+    config: &Config,
+    state: &State,
+    results: &[CheckResult],
+    latencies: &HashMap<Uuid, u64>,
+) -> String {
+    let subject_key = |id: &Uuid| {
+        config.subjects.iter()
+            .find(|s| s.id == *id)
+            .map(|s| escape_label(&s.key))
+            .unwrap_or_else(|| id.to_string())
+    };
+
+    let mut out = String::new();
+
+    out.push_str("# HELP headsup_checks_total Subject checks in the last run, by outcome.\n");
+    out.push_str("# TYPE headsup_checks_total gauge\n");
+    for result in results {
+        let status = if result.success { "success" } else { "failure" };
+        let _ = writeln!(
+            out,
+            "headsup_checks_total{{subject=\"{}\",status=\"{}\"}} 1",
+            subject_key(&result.subject_id),
+            status
+        );
+    }
+
+    out.push_str("# HELP headsup_notifications_total Notifications sent in the last run, by channel.\n");
+    out.push_str("# TYPE headsup_notifications_total gauge\n");
+    for result in results.iter().filter(|r| r.notified) {
+        let _ = writeln!(
+            out,
+            "headsup_notifications_total{{subject=\"{}\",channel=\"email\"}} 1",
+            subject_key(&result.subject_id)
+        );
+    }
+
+    out.push_str("# HELP headsup_check_duration_ms Time the backend took to answer, in milliseconds.\n");
+    out.push_str("# TYPE headsup_check_duration_ms gauge\n");
+    for result in results {
+        if let Some(ms) = latencies.get(&result.subject_id) {
+            let _ = writeln!(
+                out,
+                "headsup_check_duration_ms{{subject=\"{}\"}} {}",
+                subject_key(&result.subject_id),
+                ms
+            );
+        }
+    }
+
+    out.push_str("# HELP headsup_consecutive_failures Checks in a row that failed.\n");
+    out.push_str("# TYPE headsup_consecutive_failures gauge\n");
+    for result in results {
+        let failures = state.consecutive_failures.get(&result.subject_id).copied().unwrap_or(0);
+        let _ = writeln!(
+            out,
+            "headsup_consecutive_failures{{subject=\"{}\"}} {}",
+            subject_key(&result.subject_id),
+            failures
+        );
+    }
+
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Subject;
+
+    #[test]
+    fn test_render() {
+        let mut config = Config::default_with_email("user@example.com");
+        let subject = Subject {
+            key: "gta6".to_string(),
+            ..Default::default()
+        };
+        let id = subject.id;
+        config.subjects.push(subject);
+
+        let mut state = State::default();
+        state.consecutive_failures.insert(id, 2);
+
+        let results = vec![CheckResult {
+            subject_id: id,
+            subject_name: "GTA 6".to_string(),
+            success: false,
+            notified: false,
+            error: Some("timeout".to_string()),
+            error_type: Some("claude_timeout"),
+            channel_notification: None,
+            backend_used: "claude".to_string(),
+        }];
+        let latencies = HashMap::from([(id, 1500)]);
+
+        let out = render(&config, &state, &results, &latencies);
+        assert!(out.contains("headsup_checks_total{subject=\"gta6\",status=\"failure\"} 1\n"));
+        assert!(out.contains("headsup_check_duration_ms{subject=\"gta6\"} 1500\n"));
+        assert!(out.contains("headsup_consecutive_failures{subject=\"gta6\"} 2\n"));
+        assert!(!out.contains("headsup_notifications_total{"));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::error::{HeadsupError, Result};
+use crate::util::secret::run_secret_command;
+use reqwest::Client;
+use std::process::Command;
+use std::time::Duration;
+
+/// Job label used when settings.prometheus_job_name is not set
+pub const DEFAULT_JOB_NAME: &str = "headsup";
+
+/// Timeout for pushgateway requests
+const PUSH_TIMEOUT_SECS: u64 = 10;
+
+/// POST metrics in the text exposition format to `<url>/metrics/job/<job>/instance/<hostname>`
+pub async fn push(url: &str, job: &str, auth_command: Option<&str>, metrics: String) -> Result<()> {
+    let target = format!(
+        "{}/metrics/job/{}/instance/{}",
+        url.trim_end_matches('/'),
+        job,
+        hostname()
+    );
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(PUSH_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| HeadsupError::Metrics(format!("Failed to create HTTP client: {}", e)))?;
+
+    let mut request = client
+        .post(&target)
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .body(metrics);
+    if let Some(command) = auth_command {
+        request = request.header("Authorization", authorization_header(command)?);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| HeadsupError::Metrics(format!("Push to {} failed: {}", target, e)))?;
+
+    if !response.status().is_success() {
+        return Err(HeadsupError::Metrics(format!(
+            "Pushgateway returned status {}",
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Run the auth command, which prints the full header value (e.g. `Bearer <token>`)
+fn authorization_header(command: &str) -> Result<String> {
+    run_secret_command(command, "auth command").map_err(HeadsupError::Metrics)
+}
+
+/// Name of this machine, used as the instance label
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| {
+            Command::new("hostname")
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
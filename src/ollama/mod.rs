@@ -0,0 +1,20 @@
+mod process;
+
+pub use process::execute_ollama;
+
+use crate::config::{OllamaConfig, Subject};
+use crate::claude::{build_check_prompt, parse_response, ClaudeResponse};
+use crate::error::Result;
+use crate::state::SubjectState;
+
+/// Check a subject using a local Ollama model and return the parsed response along with the raw output
+pub async fn check_subject(
+    config: &OllamaConfig,
+    subject: &Subject,
+    state: Option<&SubjectState>,
+) -> Result<(ClaudeResponse, String)> {
+    let prompt = build_check_prompt(subject, state, false);
+    let raw = execute_ollama(config, &prompt).await?;
+    let response = parse_response(subject.subject_type, &raw)?;
+    Ok((response, raw))
+}
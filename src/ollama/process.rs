@@ -0,0 +1,97 @@
+use crate::config::OllamaConfig;
+use crate::error::{HeadsupError, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Serialize)]
+struct GenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    stream: bool,
+    /// Constrain the output to valid JSON
+    format: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
+/// Run the prompt through Ollama's /api/generate endpoint
+pub async fn execute_ollama(config: &OllamaConfig, prompt: &str) -> Result<String> {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds))
+        .build()
+        .map_err(|e| HeadsupError::Ollama(format!("Failed to create HTTP client: {}", e)))?;
+
+    let url = generate_url(config);
+    let request = GenerateRequest {
+        model: &config.model,
+        prompt,
+        stream: false,
+        format: "json",
+    };
+
+    let response = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                HeadsupError::OllamaTimeout(config.timeout_seconds)
+            } else if e.is_connect() {
+                HeadsupError::Ollama(format!("Could not connect to {} (is Ollama running?)", config.url))
+            } else {
+                HeadsupError::Ollama(format!("Request failed: {}", e))
+            }
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(HeadsupError::Ollama(format!(
+            "API returned status {}: {}",
+            status, body
+        )));
+    }
+
+    let generated: GenerateResponse = response
+        .json()
+        .await
+        .map_err(|e| HeadsupError::Ollama(format!("Failed to parse response: {}", e)))?;
+
+    if generated.response.trim().is_empty() {
+        return Err(HeadsupError::Ollama("Empty response".to_string()));
+    }
+
+    Ok(generated.response)
+}
+
+fn generate_url(config: &OllamaConfig) -> String {
+    format!("{}/api/generate", config.url.trim_end_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_url() {
+        let mut config = OllamaConfig {
+            url: "http://localhost:11434/".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(generate_url(&config), "http://localhost:11434/api/generate");
+        config.url = "http://gpu-box:11434".to_string();
+        assert_eq!(generate_url(&config), "http://gpu-box:11434/api/generate");
+    }
+
+    #[test]
+    fn test_generate_request() {
+        let request = GenerateRequest { model: "llama3.1", prompt: "prompt", stream: false, format: "json" };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value, serde_json::json!({ "model": "llama3.1", "prompt": "prompt", "stream": false, "format": "json" }));
+    }
+}
@@ -0,0 +1,20 @@
+mod process;
+
+pub use process::execute_openai;
+
+use crate::config::{OpenAiConfig, Subject};
+use crate::claude::{build_check_prompt, parse_response, ClaudeResponse};
+use crate::error::Result;
+use crate::state::SubjectState;
+
+/// Check a subject using the OpenAI API and return the parsed response along with the raw output
+pub async fn check_subject(
+    config: &OpenAiConfig,
+    subject: &Subject,
+    state: Option<&SubjectState>,
+) -> Result<(ClaudeResponse, String)> {
+    let prompt = build_check_prompt(subject, state, config.enable_web_search);
+    let raw = execute_openai(config, &prompt).await?;
+    let response = parse_response(subject.subject_type, &raw)?;
+    Ok((response, raw))
+}
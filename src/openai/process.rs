@@ -0,0 +1,149 @@
+use crate::config::OpenAiConfig;
+use crate::error::{HeadsupError, Result};
+use crate::util::secret::run_secret_command;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+#[derive(Debug, Serialize)]
+struct OpenAiRequest {
+    model: String,
+    messages: Vec<Message>,
+    /// Present (empty) to let search-capable models browse the web
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_search_options: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// Execute an OpenAI chat completion with the given prompt
+pub async fn execute_openai(config: &OpenAiConfig, prompt: &str) -> Result<String> {
+    let timeout_duration = Duration::from_secs(config.timeout_seconds);
+
+    // Get API key from command
+    let api_key = get_api_key(&config.api_key_command)?;
+
+    let client = Client::builder()
+        .timeout(timeout_duration)
+        .build()
+        .map_err(|e| HeadsupError::OpenAi(format!("Failed to create HTTP client: {}", e)))?;
+
+    let request = build_request(config, prompt);
+
+    let response = client
+        .post(OPENAI_API_URL)
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                HeadsupError::OpenAiTimeout(config.timeout_seconds)
+            } else {
+                HeadsupError::OpenAi(format!("Request failed: {}", e))
+            }
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(HeadsupError::OpenAi(format!(
+            "API returned status {}: {}",
+            status, body
+        )));
+    }
+
+    let openai_response: OpenAiResponse = response
+        .json()
+        .await
+        .map_err(|e| HeadsupError::OpenAi(format!("Failed to parse response: {}", e)))?;
+
+    response_content(openai_response)
+}
+
+fn build_request(config: &OpenAiConfig, prompt: &str) -> OpenAiRequest {
+    OpenAiRequest {
+        model: config.model.clone(),
+        messages: vec![Message {
+            role: "user".to_string(),
+            content: prompt.to_string(),
+        }],
+        web_search_options: config.enable_web_search.then(|| serde_json::json!({})),
+    }
+}
+
+/// The text of the first choice, which must not be empty
+fn response_content(response: OpenAiResponse) -> Result<String> {
+    let content = response
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|c| c.message.content)
+        .ok_or_else(|| HeadsupError::OpenAi("No response content".to_string()))?;
+
+    if content.trim().is_empty() {
+        return Err(HeadsupError::OpenAi("Empty response".to_string()));
+    }
+
+    Ok(content)
+}
+
+/// Get API key by executing the configured command
+fn get_api_key(command: &str) -> Result<String> {
+    if command.is_empty() {
+        return Err(HeadsupError::OpenAi(
+            "OpenAI API key command not configured".to_string(),
+        ));
+    }
+
+    run_secret_command(command, "API key command").map_err(HeadsupError::OpenAi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_request() {
+        let mut config = OpenAiConfig::default();
+        let request = serde_json::to_value(build_request(&config, "prompt")).unwrap();
+        assert_eq!(request["messages"][0]["content"], "prompt");
+        assert!(request.get("web_search_options").is_none());
+
+        config.enable_web_search = true;
+        let request = serde_json::to_value(build_request(&config, "prompt")).unwrap();
+        assert_eq!(request["web_search_options"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_response_content() {
+        let parse = |body: &str| response_content(serde_json::from_str(body).unwrap());
+        assert_eq!(parse(r#"{"choices":[{"message":{"content":"{\"status\":\"released\"}"}}]}"#).unwrap(), r#"{"status":"released"}"#);
+        assert!(parse(r#"{"choices":[]}"#).is_err());
+        assert!(parse(r#"{"choices":[{"message":{"content":null}}]}"#).is_err());
+        assert!(parse(r#"{"choices":[{"message":{"content":"  "}}]}"#).is_err());
+    }
+}
@@ -11,42 +11,42 @@ use crate::claude::{
 use crate::error::Result;
 use crate::state::SubjectState;
 
-/// Check a subject using Perplexity API and return the response
+/// Check a subject using Perplexity API and return the parsed response along with the raw output
 pub async fn check_subject(
     config: &PerplexityConfig,
     subject: &Subject,
     state: Option<&SubjectState>,
-) -> Result<ClaudeResponse> {
+) -> Result<(ClaudeResponse, String)> {
     match subject.subject_type {
         SubjectType::Release => {
             let release_state = state.and_then(|s| match s {
                 SubjectState::Release(rs) => Some(rs),
                 _ => None,
             });
-            let prompt = build_release_prompt(subject, release_state);
+            let prompt = build_release_prompt(subject, release_state, true);
             let raw = execute_perplexity(config, &prompt).await?;
             let response = parse_release_response(&raw)?;
-            Ok(ClaudeResponse::Release(response))
+            Ok((ClaudeResponse::Release(response), raw))
         }
         SubjectType::Question => {
             let question_state = state.and_then(|s| match s {
                 SubjectState::Question(qs) => Some(qs),
                 _ => None,
             });
-            let prompt = build_question_prompt(subject, question_state);
+            let prompt = build_question_prompt(subject, question_state, true);
             let raw = execute_perplexity(config, &prompt).await?;
             let response = parse_question_response(&raw)?;
-            Ok(ClaudeResponse::Question(response))
+            Ok((ClaudeResponse::Question(response), raw))
         }
         SubjectType::Recurring => {
             let recurring_state = state.and_then(|s| match s {
                 SubjectState::Recurring(rs) => Some(rs),
                 _ => None,
             });
-            let prompt = build_recurring_prompt(subject, recurring_state);
+            let prompt = build_recurring_prompt(subject, recurring_state, true);
             let raw = execute_perplexity(config, &prompt).await?;
             let response = parse_recurring_response(&raw)?;
-            Ok(ClaudeResponse::Recurring(response))
+            Ok((ClaudeResponse::Recurring(response), raw))
         }
     }
 }
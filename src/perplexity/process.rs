@@ -1,8 +1,8 @@
 use crate::config::PerplexityConfig;
 use crate::error::{HeadsupError, Result};
+use crate::util::secret::run_secret_command;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
 use std::time::Duration;
 
 const PERPLEXITY_API_URL: &str = "https://api.perplexity.ai/chat/completions";
@@ -104,27 +104,5 @@ fn get_api_key(command: &str) -> Result<String> {
         ));
     }
 
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .output()
-        .map_err(|e| HeadsupError::Perplexity(format!("Failed to execute API key command: {}", e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(HeadsupError::Perplexity(format!(
-            "API key command failed: {}",
-            stderr.trim()
-        )));
-    }
-
-    let api_key = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-    if api_key.is_empty() {
-        return Err(HeadsupError::Perplexity(
-            "API key command returned empty result".to_string(),
-        ));
-    }
-
-    Ok(api_key)
+    run_secret_command(command, "API key command").map_err(HeadsupError::Perplexity)
 }
@@ -0,0 +1,201 @@
+use crate::cli::CheckResult;
+use crate::config::{Config, Subject};
+use crate::state::{Confidence, HistoryEntry, State, SubjectState};
+use crate::util::html::{html_escape, is_web_url};
+use chrono::Utc;
+
+const STYLE: &str = "body { font-family: -apple-system, Helvetica, Arial, sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+.summary { color: #555; margin-bottom: 1.5em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: 0.5em 0.75em; border-bottom: 1px solid #ddd; text-align: left; }
+th { background: #f4f4f4; }
+tr.released, tr.answered { background: #e6f4ea; }
+tr.announced, tr.scheduled { background: #e8f0fe; }
+tr.delayed, tr.open { background: #fef7e0; }
+tr.cancelled, tr.failed { background: #fce8e6; }
+tr.unknown { background: #f8f9fa; }
+.trend { font-weight: bold; text-align: center; }
+a { color: #1a73e8; }";
+
+/// A single row of the report table
+struct ReportRow {
+    name: String,
+    subject_type: String,
+    status: String,
+    confidence: String,
+    known_date: String,
+    last_checked: String,
+    trend: &'static str,
+    source_url: Option<String>,
+}
+
+/// Generate a self-contained HTML report of all subjects
+pub fn generate_html_report(config: &Config, state: &State, results: &[CheckResult]) -> String {
+    let succeeded = results.iter().filter(|r| r.success).count();
+    let failed = results.len() - succeeded;
+
+    let rows: String = config
+        .subjects
+        .iter()
+        .map(|subject| {
+            let failed = results.iter().any(|r| r.subject_id == subject.id && !r.success);
+            render_row(&build_row(subject, state.subjects.get(&subject.id), failed))
+        })
+        .collect();
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>Headsup Report</title>
+<style>
+{style}
+</style>
+</head>
+<body>
+<h1>Headsup Report</h1>
+<p class=\"summary\">Generated {generated}. Checked {checked} subjects: {succeeded} succeeded, {failed} failed.</p>
+<table>
+<thead>
+<tr><th>Name</th><th>Type</th><th>Status</th><th>Confidence</th><th>Known date</th><th>Last checked</th><th>Trend</th></tr>
+</thead>
+<tbody>
+{rows}</tbody>
+</table>
+</body>
+</html>
+",
+        style = STYLE,
+        generated = Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        checked = results.len(),
+        succeeded = succeeded,
+        failed = failed,
+        rows = rows,
+    )
+}
+
+fn build_row(subject: &Subject, state: Option<&SubjectState>, failed: bool) -> ReportRow {
+    let (status, confidence, known_date) = match state {
+        Some(SubjectState::Release(rs)) => (
+            rs.status.to_string(),
+            rs.confidence,
+            rs.known_release_date.clone(),
+        ),
+        Some(SubjectState::Question(qs)) => (
+            if qs.current_answer.is_some() { "Answered" } else { "Open" }.to_string(),
+            qs.confidence,
+            None,
+        ),
+        Some(SubjectState::Recurring(rs)) => (
+            if rs.next_occurrence_date.is_some() { "Scheduled" } else { "Unknown" }.to_string(),
+            rs.confidence,
+            rs.next_occurrence_date.clone(),
+        ),
+        None => ("Unknown".to_string(), Confidence::Unknown, None),
+    };
+
+    let history = state.map(|s| s.history()).unwrap_or_default();
+    let source_url = history
+        .iter()
+        .rev()
+        .find_map(|e| e.source_url.clone())
+        .or_else(|| subject.last_found_source.clone());
+
+    ReportRow {
+        name: subject.name.clone(),
+        subject_type: subject.subject_type.to_string(),
+        status: if failed { "Failed".to_string() } else { status },
+        confidence: confidence.to_string(),
+        known_date: known_date.unwrap_or_else(|| "-".to_string()),
+        last_checked: state
+            .and_then(|s| s.last_checked())
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "never".to_string()),
+        trend: confidence_trend(history),
+        source_url,
+    }
+}
+
+fn render_row(row: &ReportRow) -> String {
+    let name = match &row.source_url {
+        Some(url) if is_web_url(url) => format!(
+            "<a href=\"{}\">{}</a>",
+            html_escape(url),
+            html_escape(&row.name)
+        ),
+        _ => html_escape(&row.name),
+    };
+
+    format!(
+        "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"trend\">{}</td></tr>\n",
+        row.status.to_lowercase(),
+        name,
+        html_escape(&row.subject_type),
+        html_escape(&row.status),
+        html_escape(&row.confidence),
+        html_escape(&row.known_date),
+        html_escape(&row.last_checked),
+        row.trend,
+    )
+}
+
+/// Compare the confidence of the two most recent checks
+fn confidence_trend(history: &[HistoryEntry]) -> &'static str {
+    let mut recent = history
+        .iter()
+        .rev()
+        .filter_map(|e| e.details.get("confidence")?.as_str()?.parse::<Confidence>().ok());
+
+    match (recent.next(), recent.next()) {
+        (Some(latest), Some(previous)) if latest.is_higher_than(&previous) => "&#9650;",
+        (Some(latest), Some(previous)) if previous.is_higher_than(&latest) => "&#9660;",
+        (Some(_), Some(_)) => "&#9654;",
+        _ => "-",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(confidence: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            event: "check".to_string(),
+            details: serde_json::json!({ "confidence": confidence }),
+            source_url: None,
+            raw_response: None,
+            raw_ai_output: None,
+            latency_ms: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_row_links_only_web_urls() {
+        let mut row = ReportRow {
+            name: "GTA 6".to_string(),
+            subject_type: "release".to_string(),
+            status: "Announced".to_string(),
+            confidence: "official".to_string(),
+            known_date: "-".to_string(),
+            last_checked: "never".to_string(),
+            trend: "-",
+            source_url: Some("https://example.com/?a=1&b=2".to_string()),
+        };
+        assert!(render_row(&row).contains(r#"<a href="https://example.com/?a=1&amp;b=2">GTA 6</a>"#));
+
+        row.source_url = Some("javascript:alert(1)".to_string());
+        assert!(!render_row(&row).contains("href"));
+    }
+
+    #[test]
+    fn test_confidence_trend() {
+        assert_eq!(confidence_trend(&[]), "-");
+        assert_eq!(confidence_trend(&[entry("rumor"), entry("official")]), "&#9650;");
+        assert_eq!(confidence_trend(&[entry("official"), entry("rumor")]), "&#9660;");
+        assert_eq!(confidence_trend(&[entry("rumor"), entry("rumor")]), "&#9654;");
+    }
+}
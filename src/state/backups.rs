@@ -0,0 +1,134 @@
+use super::{verify_integrity, write_atomic, FileLock, LOCK_TIMEOUT_SECS};
+use crate::config;
+use crate::error::{HeadsupError, Result};
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Directory holding automatic and `state backup` copies of the state file
+pub fn backups_dir() -> Result<PathBuf> {
+    Ok(config::data_dir()?.join("backups"))
+}
+
+/// Copy the state file into the backups directory, keeping at most `max_backups` copies
+///
+/// Returns `None` when there is no state file yet.
+pub fn create_backup(max_backups: u32) -> Result<Option<PathBuf>> {
+    backup_into(&config::state_path()?, &backups_dir()?, max_backups)
+}
+
+fn backup_into(path: &Path, dir: &Path, max_backups: u32) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(dir)?;
+    let backup_path = dir.join(format!("state-{}.json", Local::now().format("%Y%m%d-%H%M%S%.3f")));
+    fs::copy(path, &backup_path)?;
+    rotate(dir, max_backups)?;
+    Ok(Some(backup_path))
+}
+
+/// Backups in the backups directory, oldest first
+pub fn list_backups() -> Result<Vec<PathBuf>> {
+    backups_in(&backups_dir()?)
+}
+
+fn backups_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| is_backup(path))
+        .collect();
+    // Timestamped names sort chronologically
+    backups.sort();
+    Ok(backups)
+}
+
+/// Replace the state file with a backup after checking that the backup is a valid state
+///
+/// With `max_backups`, the state being replaced is backed up first; that path is returned.
+pub fn restore_backup(backup: &Path, max_backups: Option<u32>) -> Result<Option<PathBuf>> {
+    restore_into(&config::state_path()?, &backups_dir()?, backup, max_backups)
+}
+
+fn restore_into(path: &Path, dir: &Path, backup: &Path, max_backups: Option<u32>) -> Result<Option<PathBuf>> {
+    verify_integrity(backup)?;
+    // Read first: rotating for the pre-restore backup may delete `backup` itself
+    let content = fs::read_to_string(backup)?;
+    let _lock = FileLock::acquire(path, Duration::from_secs(LOCK_TIMEOUT_SECS))?;
+    let previous = match max_backups {
+        Some(max_backups) => backup_into(path, dir, max_backups)?,
+        None => None,
+    };
+    write_atomic(path, &content)?;
+    Ok(previous)
+}
+
+fn rotate(dir: &Path, max_backups: u32) -> Result<()> {
+    let backups = backups_in(dir)?;
+    let excess = backups.len().saturating_sub(max_backups.max(1) as usize);
+    for old in &backups[..excess] {
+        fs::remove_file(old).map_err(|e| {
+            HeadsupError::State(format!("Failed to remove old backup in {}: {}", dir.display(), e))
+        })?;
+    }
+    Ok(())
+}
+
+fn is_backup(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("state-") && name.ends_with(".json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_removes_oldest_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        for stamp in ["20260101-000000.000", "20260102-000000.000", "20260103-000000.000"] {
+            fs::write(dir.path().join(format!("state-{}.json", stamp)), "{}").unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        rotate(dir.path(), 2).unwrap();
+
+        let names: Vec<_> = backups_in(dir.path())
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["state-20260102-000000.000.json", "state-20260103-000000.000.json"]);
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_restore_oldest_backup_at_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = dir.path().join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        let state = |runs: &str| {
+            let mut value = serde_json::to_value(crate::state::State::default()).unwrap();
+            value["last_run"] = serde_json::json!(runs);
+            serde_json::to_string(&value).unwrap()
+        };
+        let oldest = backups.join("state-20260101-000000.000.json");
+        fs::write(&oldest, state("2026-01-01T00:00:00Z")).unwrap();
+        fs::write(backups.join("state-20260102-000000.000.json"), state("2026-01-02T00:00:00Z")).unwrap();
+        let path = dir.path().join("state.json");
+        fs::write(&path, state("2026-01-03T00:00:00Z")).unwrap();
+
+        let previous = restore_into(&path, &backups, &oldest, Some(2)).unwrap().unwrap();
+
+        assert!(fs::read_to_string(&path).unwrap().contains("2026-01-01T00:00:00Z"));
+        assert!(fs::read_to_string(previous).unwrap().contains("2026-01-03T00:00:00Z"));
+        assert_eq!(backups_in(&backups).unwrap().len(), 2);
+    }
+}
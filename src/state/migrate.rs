@@ -0,0 +1,66 @@
+use super::STATE_VERSION;
+use crate::error::{HeadsupError, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Turns a state file of one version into the next version
+pub type MigrationFn = fn(Value) -> Value;
+
+/// `MIGRATIONS[v]` upgrades a version `v` file to version `v + 1`
+const MIGRATIONS: &[MigrationFn] = &[v0_add_version];
+
+/// Files from before versioning have no `version` field
+fn v0_add_version(value: Value) -> Value {
+    value
+}
+
+/// Version recorded in a raw state file (0 when missing)
+pub fn version_of(value: &Value) -> u32 {
+    value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+/// Bring raw state JSON up to `STATE_VERSION`
+///
+/// Files written by a newer headsup are returned unchanged; unknown fields are ignored
+/// when they are deserialized.
+pub fn migrate(mut value: Value) -> Result<Value> {
+    if !value.is_object() {
+        return Err(HeadsupError::State("State file is not a JSON object".to_string()));
+    }
+
+    let from = version_of(&value);
+    for version in from..STATE_VERSION {
+        let step = MIGRATIONS.get(version as usize).ok_or_else(|| {
+            HeadsupError::State(format!("No migration from state version {}", version))
+        })?;
+        value = step(value);
+        value["version"] = Value::from(version + 1);
+    }
+    Ok(value)
+}
+
+/// Copy the state file to `state.json.v<version>.bak` before it is rewritten
+pub fn backup(path: &Path, version: u32) -> Result<PathBuf> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    let backup_path = path.with_file_name(name);
+    fs::copy(path, &backup_path)?;
+    Ok(backup_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_unversioned_state() {
+        let migrated = migrate(json!({ "last_run": null })).unwrap();
+        assert_eq!(version_of(&migrated), STATE_VERSION);
+        assert!(serde_json::from_value::<super::super::State>(migrated).is_ok());
+
+        let newer = json!({ "version": STATE_VERSION + 1, "last_run": null });
+        assert_eq!(migrate(newer.clone()).unwrap(), newer);
+    }
+}
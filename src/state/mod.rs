@@ -1,12 +1,20 @@
+mod backups;
 mod lock;
+mod migrate;
 mod types;
 
+pub use backups::{backups_dir, create_backup, list_backups, restore_backup};
 pub use lock::FileLock;
+pub use migrate::{migrate, version_of};
+use migrate::backup;
 pub use types::*;
 
 use crate::config;
-use crate::error::Result;
+use crate::error::{HeadsupError, Result};
+use chrono::Utc;
 use std::fs;
+use std::io::Write;
+use std::path::Path;
 use std::time::Duration;
 
 /// Default lock timeout in seconds
@@ -17,13 +25,45 @@ pub fn load_state() -> Result<(State, FileLock)> {
     let path = config::state_path()?;
     let lock = FileLock::acquire(&path, Duration::from_secs(LOCK_TIMEOUT_SECS))?;
 
-    let state = if path.exists() {
-        let content = fs::read_to_string(&path)?;
-        serde_json::from_str(&content)?
+    let mut state: State = if path.exists() {
+        let raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        let from = version_of(&raw);
+        let migrated = migrate(raw)?;
+        // The next save overwrites the file in the new format
+        if from < STATE_VERSION {
+            let backup_path = backup(&path, from)?;
+            tracing::info!("Migrated state from version {} (backup at {})", from, backup_path.display());
+        }
+        serde_json::from_value(migrated)?
     } else {
-        State::default()
+        let mut state = State::default();
+        state.add_diagnostic(DiagnosticLevel::Info, "No state file found, starting fresh".to_string());
+        state
     };
 
+    if state.schema_version != SCHEMA_VERSION {
+        let message = format!(
+            "State file was written by headsup {}, current version is {}",
+            if state.schema_version.is_empty() { "unknown" } else { &state.schema_version },
+            SCHEMA_VERSION
+        );
+        tracing::info!("{}", message);
+        state.add_diagnostic(DiagnosticLevel::Info, message);
+    }
+
+    // Orphans are only reported here; 'headsup state prune' removes them
+    if let Ok(config) = config::load_config() {
+        let orphans = state.subjects.keys()
+            .filter(|id| !config.subjects.iter().any(|s| s.id == **id))
+            .count();
+        if orphans > 0 {
+            state.add_diagnostic(
+                DiagnosticLevel::Warn,
+                format!("{} orphaned subject state entries (run 'headsup state prune')", orphans),
+            );
+        }
+    }
+
     Ok((state, lock))
 }
 
@@ -32,30 +72,87 @@ pub fn load_state_readonly() -> Result<State> {
     let path = config::state_path()?;
 
     if path.exists() {
-        let content = fs::read_to_string(&path)?;
-        Ok(serde_json::from_str(&content)?)
+        let raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        Ok(serde_json::from_value(migrate(raw)?)?)
     } else {
         Ok(State::default())
     }
 }
 
 /// Save state to file (lock must be held)
-pub fn save_state(state: &State, _lock: &FileLock) -> Result<()> {
+pub fn save_state(state: &mut State, _lock: &FileLock) -> Result<()> {
     let path = config::state_path()?;
 
+    state.schema_version = SCHEMA_VERSION.to_string();
+    state.last_written = Some(Utc::now());
+
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
 
     let content = serde_json::to_string_pretty(state)?;
-    fs::write(&path, content)?;
+    write_atomic(&path, &content)?;
+    verify_integrity(&path)
+}
+
+/// Write to a temporary file next to `path`, then move it into place
+///
+/// A crash leaves either the old file or the new one, never a partial write.
+pub fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    replace_file(&tmp_path, path)
+}
+
+/// rename() atomically replaces the target on POSIX file systems
+#[cfg(not(windows))]
+fn replace_file(from: &Path, to: &Path) -> Result<()> {
+    fs::rename(from, to)?;
+    Ok(())
+}
+
+/// Move the old file aside under a random name first, so a failure never loses both
+#[cfg(windows)]
+fn replace_file(from: &Path, to: &Path) -> Result<()> {
+    if !to.exists() {
+        fs::rename(from, to)?;
+        return Ok(());
+    }
+    let mut old_name = to.file_name().unwrap_or_default().to_os_string();
+    old_name.push(format!(".{}.old", &uuid::Uuid::new_v4().simple().to_string()[..8]));
+    let old_path = to.with_file_name(old_name);
+
+    fs::rename(to, &old_path)?;
+    if let Err(e) = fs::rename(from, to) {
+        fs::rename(&old_path, to)?;
+        return Err(e.into());
+    }
+    fs::remove_file(old_path)?;
+    Ok(())
+}
+
+/// Check that the state file at `path` reads back as a valid state, without changing it
+pub fn verify_integrity(path: &Path) -> Result<()> {
+    let raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| HeadsupError::State(format!("{} is not valid JSON: {}", path.display(), e)))?;
+    serde_json::from_value::<State>(migrate(raw)?)
+        .map_err(|e| HeadsupError::State(format!("{} does not match the state format: {}", path.display(), e)))?;
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Config, Subject, SubjectType};
+    use crate::error::HeadsupError;
     use std::path::PathBuf;
     use tempfile::tempdir;
 
@@ -92,4 +189,179 @@ mod tests {
         let (loaded, _lock) = load_state_from(&path).unwrap();
         assert_eq!(loaded.version, STATE_VERSION);
     }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        fs::write(&path, "{ partial").unwrap();
+        assert!(verify_integrity(&path).is_err());
+
+        let content = serde_json::to_string_pretty(&State::default()).unwrap();
+        write_atomic(&path, &content).unwrap();
+        verify_integrity(&path).unwrap();
+        assert!(!dir.path().join("state.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_hypothesis_event() {
+        assert_eq!(hypothesis_event("Aaron Taylor-Johnson", "aaron taylor-johnson "), "hypothesis_confirmed");
+        assert_eq!(hypothesis_event("Aaron Taylor-Johnson", "Callum Turner"), "hypothesis_contradicted");
+    }
+
+    #[test]
+    fn test_diagnostics_are_capped() {
+        let mut state = State::default();
+        for i in 0..MAX_DIAGNOSTICS + 5 {
+            state.add_diagnostic(DiagnosticLevel::Info, format!("message {}", i));
+        }
+        assert_eq!(state.startup_diagnostics.len(), MAX_DIAGNOSTICS);
+        assert_eq!(state.startup_diagnostics[0].message, "message 5");
+    }
+
+    #[test]
+    fn test_check_history_is_capped() {
+        let mut state = State::default();
+        let first = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        for i in 0..MAX_CHECK_HISTORY_DAYS as u64 + 3 {
+            state.record_check_run(first + chrono::Days::new(i), "claude", 2, 1, 0);
+        }
+        state.record_check_run(first + chrono::Days::new(3), "perplexity", 1, 0, 1);
+
+        assert_eq!(state.check_history.len(), MAX_CHECK_HISTORY_DAYS);
+        let (oldest, day) = state.check_history.first_key_value().unwrap();
+        assert_eq!(*oldest, first + chrono::Days::new(3));
+        assert_eq!(day.checks, 3);
+        assert_eq!(day.notifications, 1);
+        assert_eq!(day.backends["claude"], 2);
+        assert_eq!(day.backends["perplexity"], 1);
+    }
+
+    #[test]
+    fn test_get_or_create_type_mismatch() {
+        let subject = Subject {
+            key: "bond".to_string(),
+            subject_type: SubjectType::Question,
+            ..Default::default()
+        };
+        let mut state = State::default();
+        state.get_or_create_release(&subject).unwrap();
+
+        assert!(matches!(
+            state.get_or_create_question(&subject),
+            Err(HeadsupError::SubjectTypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_merge_subject() {
+        let keep = Subject { key: "gta6".to_string(), ..Default::default() };
+        let duplicate = Subject { key: "gta-6".to_string(), ..Default::default() };
+        let mut state = State::default();
+        state.get_or_create_release(&keep).unwrap();
+        state.get_or_create_release(&duplicate).unwrap().last_checked = Some(Utc::now());
+        state.record_failure(duplicate.id);
+
+        state.merge_subject(keep.id, duplicate.id);
+        assert_eq!(state.subjects.len(), 1);
+        assert!(state.subjects[&keep.id].last_checked().is_some());
+        assert!(state.consecutive_failures.is_empty());
+    }
+
+    #[test]
+    fn test_queue_notification_dedup() {
+        let notification = |summary: &str, created_at| PendingNotification {
+            notification_id: uuid::Uuid::new_v4(),
+            subject_id: uuid::Uuid::nil(),
+            event_type: "release_update".to_string(),
+            created_at,
+            summary: summary.to_string(),
+            source_url: None,
+            confidence: None,
+            payload: serde_json::Value::Null,
+            ttl_hours: Some(2),
+            expires_at: None,
+        };
+        let now = Utc::now();
+        let mut state = State::default();
+
+        let first = state.queue_notification(notification("first", now - chrono::Duration::minutes(30)), 1);
+        assert_eq!(state.queue_notification(notification("second", now), 1), first);
+        assert_eq!(state.pending_notifications.len(), 1);
+        assert_eq!(state.pending_notifications[0].summary, "second");
+
+        state.queue_notification(notification("third", now + chrono::Duration::hours(2)), 1);
+        assert_eq!(state.pending_notifications.len(), 2);
+
+        // The merged notification keeps the first one's queue time
+        assert!(!state.pending_notifications[0].is_expired(now + chrono::Duration::hours(1), 24));
+        assert!(state.pending_notifications[0].is_expired(now + chrono::Duration::hours(2), 24));
+
+        // Without a TTL the max age applies, and an explicit expiry time wins over both
+        state.pending_notifications[1].ttl_hours = None;
+        assert!(!state.pending_notifications[1].is_expired(now + chrono::Duration::hours(20), 24));
+        assert!(state.pending_notifications[1].is_expired(now + chrono::Duration::hours(27), 24));
+        assert!(!state.pending_notifications[1].is_expired(now + chrono::Duration::hours(27), 0));
+        state.pending_notifications[1].expires_at = Some(now + chrono::Duration::hours(3));
+        assert_eq!(state.discard_expired_notifications(now + chrono::Duration::hours(3), 0).len(), 2);
+        assert!(state.pending_notifications.is_empty());
+    }
+
+    #[test]
+    fn test_add_history_subject_limit() {
+        let entry = || HistoryEntry {
+            timestamp: Utc::now(),
+            event: "check".to_string(),
+            details: serde_json::json!({}),
+            source_url: None,
+            raw_response: None,
+            raw_ai_output: None,
+            latency_ms: None,
+            tags: Vec::new(),
+        };
+        let mut config = Config::default_with_email("user@example.com");
+        config.settings.max_history_entries = 2;
+        let subject = Subject {
+            key: "rate".to_string(),
+            max_history_entries: Some(5),
+            ..Default::default()
+        };
+        let mut state = State::default();
+        state.get_or_create_release(&subject).unwrap();
+        config.subjects.push(subject.clone());
+
+        for _ in 0..10 {
+            state.add_history(subject.id, entry(), &config);
+        }
+        assert_eq!(state.subjects[&subject.id].history().len(), 5);
+    }
+
+    #[test]
+    fn test_failed_checks_do_not_push_out_history() {
+        let entry = |event: &str| HistoryEntry {
+            timestamp: Utc::now(),
+            event: event.to_string(),
+            details: serde_json::json!({}),
+            source_url: None,
+            raw_response: None,
+            raw_ai_output: None,
+            latency_ms: None,
+            tags: Vec::new(),
+        };
+        let mut config = Config::default_with_email("user@example.com");
+        config.settings.max_history_entries = 2;
+        let subject = Subject::default();
+        let mut state = State::default();
+        state.get_or_create_release(&subject).unwrap();
+        config.subjects.push(subject.clone());
+
+        state.add_history(subject.id, entry("check"), &config);
+        state.add_history(subject.id, entry("check"), &config);
+        for _ in 0..5 {
+            state.add_history(subject.id, entry(FAILED_EVENT), &config);
+        }
+        let history = state.subjects[&subject.id].history();
+        assert_eq!(history.iter().filter(|e| e.event == "check").count(), 2);
+        assert_eq!(history.iter().filter(|e| e.event == FAILED_EVENT).count(), 2);
+    }
 }
@@ -1,28 +1,105 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::config::{Config, Subject, SubjectType};
+use crate::error::{self, HeadsupError};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 /// The current state file version
 pub const STATE_VERSION: u32 = 1;
 
+/// The headsup version that writes the state file
+pub const SCHEMA_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Maximum number of startup diagnostics kept in the state file
+pub const MAX_DIAGNOSTICS: usize = 50;
+
+/// Maximum number of days kept in the daily check history
+pub const MAX_CHECK_HISTORY_DAYS: usize = 365;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     pub version: u32,
-    pub last_run: Option<DateTime<Utc>>,
+    /// Version of the headsup binary that last wrote this file
     #[serde(default)]
+    pub schema_version: String,
+    #[serde(default)]
+    pub last_written: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+    #[serde(default, serialize_with = "serialize_sorted")]
     pub subjects: HashMap<Uuid, SubjectState>,
     #[serde(default)]
     pub pending_notifications: Vec<PendingNotification>,
+    /// Messages recorded while loading the state, for troubleshooting
+    #[serde(default)]
+    pub startup_diagnostics: Vec<DiagnosticMessage>,
+    /// Number of checks in a row that failed, per subject
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub consecutive_failures: HashMap<Uuid, u32>,
+    /// Channels ("email", "discord") each notification was delivered through, by notification ID
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub notification_channels: HashMap<Uuid, Vec<String>>,
+    /// Aggregate check activity per local calendar day
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub check_history: BTreeMap<NaiveDate, DailyCheckSummary>,
+}
+
+/// Totals of all check runs on one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyCheckSummary {
+    pub checks: u32,
+    pub failures: u32,
+    pub notifications: u32,
+    /// Checks per backend ("claude", "perplexity")
+    #[serde(default)]
+    pub backends: HashMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticMessage {
+    pub level: DiagnosticLevel,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Warn,
+    Info,
+}
+
+impl std::fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticLevel::Warn => write!(f, "warn"),
+            DiagnosticLevel::Info => write!(f, "info"),
+        }
+    }
+}
+
+/// Serialize subject states ordered by UUID so the state file is deterministic
+fn serialize_sorted<S: Serializer>(
+    subjects: &HashMap<Uuid, SubjectState>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let sorted: BTreeMap<&Uuid, &SubjectState> = subjects.iter().collect();
+    sorted.serialize(serializer)
 }
 
 impl Default for State {
     fn default() -> Self {
         State {
             version: STATE_VERSION,
+            schema_version: SCHEMA_VERSION.to_string(),
+            last_written: None,
             last_run: None,
             subjects: HashMap::new(),
             pending_notifications: Vec::new(),
+            startup_diagnostics: Vec::new(),
+            consecutive_failures: HashMap::new(),
+            notification_channels: HashMap::new(),
+            check_history: BTreeMap::new(),
         }
     }
 }
@@ -43,6 +120,47 @@ impl SubjectState {
             SubjectState::Recurring(s) => s.last_checked,
         }
     }
+
+    /// The subject type this state was recorded for
+    pub fn subject_type(&self) -> SubjectType {
+        match self {
+            SubjectState::Release(_) => SubjectType::Release,
+            SubjectState::Question(_) => SubjectType::Question,
+            SubjectState::Recurring(_) => SubjectType::Recurring,
+        }
+    }
+
+    pub fn context_summary(&self) -> Option<&str> {
+        match self {
+            SubjectState::Release(s) => s.context_summary.as_deref(),
+            SubjectState::Question(s) => s.context_summary.as_deref(),
+            SubjectState::Recurring(s) => s.context_summary.as_deref(),
+        }
+    }
+
+    pub fn archived_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            SubjectState::Release(s) => s.archived_at,
+            SubjectState::Question(s) => s.archived_at,
+            SubjectState::Recurring(s) => s.archived_at,
+        }
+    }
+
+    pub fn set_archived_at(&mut self, archived_at: Option<DateTime<Utc>>) {
+        match self {
+            SubjectState::Release(s) => s.archived_at = archived_at,
+            SubjectState::Question(s) => s.archived_at = archived_at,
+            SubjectState::Recurring(s) => s.archived_at = archived_at,
+        }
+    }
+
+    pub fn history(&self) -> &[HistoryEntry] {
+        match self {
+            SubjectState::Release(s) => &s.history,
+            SubjectState::Question(s) => &s.history,
+            SubjectState::Recurring(s) => &s.history,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +180,12 @@ pub struct ReleaseState {
     pub ics_uid: Option<String>,
     #[serde(default)]
     pub ics_sequence: u32,
+    /// Summary of the latest findings, carried into the next prompt
+    #[serde(default)]
+    pub context_summary: Option<String>,
+    /// When the subject was archived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub history: Vec<HistoryEntry>,
 }
@@ -80,6 +204,8 @@ impl Default for ReleaseState {
             last_notified_value: None,
             ics_uid: None,
             ics_sequence: 0,
+            context_summary: None,
+            archived_at: None,
             history: Vec::new(),
         }
     }
@@ -96,10 +222,31 @@ pub struct QuestionState {
     pub last_notified_summary: Option<String>,
     #[serde(default)]
     pub last_notified_value: Option<String>,
+    /// Answer the user expects, compared against each new answer
+    #[serde(default)]
+    pub expected_answer: Option<String>,
+    /// Summary of the latest findings, carried into the next prompt
+    #[serde(default)]
+    pub context_summary: Option<String>,
+    /// When the subject was archived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub history: Vec<HistoryEntry>,
 }
 
+/// History event recording a check that returned an error
+pub const FAILED_EVENT: &str = "check_failed";
+
+/// History event recording whether a found answer matches the expected one
+pub fn hypothesis_event(expected: &str, found: &str) -> &'static str {
+    if expected.trim().eq_ignore_ascii_case(found.trim()) {
+        "hypothesis_confirmed"
+    } else {
+        "hypothesis_contradicted"
+    }
+}
+
 impl Default for QuestionState {
     fn default() -> Self {
         QuestionState {
@@ -110,6 +257,9 @@ impl Default for QuestionState {
             last_notified: None,
             last_notified_summary: None,
             last_notified_value: None,
+            expected_answer: None,
+            context_summary: None,
+            archived_at: None,
             history: Vec::new(),
         }
     }
@@ -134,6 +284,12 @@ pub struct RecurringState {
     pub ics_uid: Option<String>,
     #[serde(default)]
     pub ics_sequence: u32,
+    /// Summary of the latest findings, carried into the next prompt
+    #[serde(default)]
+    pub context_summary: Option<String>,
+    /// When the subject was archived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub history: Vec<HistoryEntry>,
 }
@@ -154,6 +310,8 @@ impl Default for RecurringState {
             last_notified_value: None,
             ics_uid: None,
             ics_sequence: 0,
+            context_summary: None,
+            archived_at: None,
             history: Vec::new(),
         }
     }
@@ -168,6 +326,22 @@ pub struct HistoryEntry {
     pub source_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub raw_response: Option<String>,
+    /// Unparsed backend output, stored only when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_ai_output: Option<String>,
+    /// How long the backend took to answer, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Labels attached to the run that produced this entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl HistoryEntry {
+    /// ID of the notification this entry produced, if any
+    pub fn notification_id(&self) -> Option<Uuid> {
+        self.details.get("notification_id")?.as_str()?.parse().ok()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -189,6 +363,11 @@ impl DatePrecision {
         self_rank < other_rank
     }
 
+    /// Check if this precision is the same as or more precise than another
+    pub fn is_at_least(&self, other: &DatePrecision) -> bool {
+        self.rank() <= other.rank()
+    }
+
     fn rank(&self) -> u8 {
         match self {
             DatePrecision::Exact => 1,
@@ -231,6 +410,11 @@ impl Confidence {
         self_rank < other_rank
     }
 
+    /// Check if this confidence is the same as or higher than another
+    pub fn is_at_least(&self, other: &Confidence) -> bool {
+        self.rank() <= other.rank()
+    }
+
     fn rank(&self) -> u8 {
         match self {
             Confidence::Official => 1,
@@ -254,6 +438,24 @@ impl std::fmt::Display for Confidence {
     }
 }
 
+impl std::str::FromStr for Confidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "official" => Ok(Confidence::Official),
+            "reliable" => Ok(Confidence::Reliable),
+            "rumor" => Ok(Confidence::Rumor),
+            "speculation" => Ok(Confidence::Speculation),
+            "unknown" => Ok(Confidence::Unknown),
+            _ => Err(format!(
+                "Invalid confidence '{}' (expected official, reliable, rumor, speculation, or unknown)",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ReleaseStatus {
@@ -277,41 +479,92 @@ impl std::fmt::Display for ReleaseStatus {
     }
 }
 
+impl std::str::FromStr for ReleaseStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "announced" => Ok(ReleaseStatus::Announced),
+            "delayed" => Ok(ReleaseStatus::Delayed),
+            "released" => Ok(ReleaseStatus::Released),
+            "cancelled" => Ok(ReleaseStatus::Cancelled),
+            "unknown" => Ok(ReleaseStatus::Unknown),
+            _ => Err(format!(
+                "Invalid status '{}' (expected announced, delayed, released, cancelled, or unknown)",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingNotification {
+    #[serde(default = "Uuid::new_v4")]
+    pub notification_id: Uuid,
     pub subject_id: Uuid,
     pub event_type: String,
     pub created_at: DateTime<Utc>,
     pub summary: String,
     pub source_url: Option<String>,
+    #[serde(default)]
+    pub confidence: Option<Confidence>,
     pub payload: serde_json::Value,
+    /// Drop the notification unsent once it has been queued this many hours
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_hours: Option<u32>,
+    /// Drop the notification unsent after this time, regardless of its TTL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl PendingNotification {
+    /// Whether the notification has passed its expiry time or outlived its TTL
+    ///
+    /// Notifications without a TTL use `max_age_hours` instead; 0 keeps them until sent.
+    pub fn is_expired(&self, now: DateTime<Utc>, max_age_hours: u32) -> bool {
+        let ttl = self.ttl_hours.or((max_age_hours > 0).then_some(max_age_hours));
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+            || ttl.is_some_and(|ttl| now - self.created_at > chrono::Duration::hours(ttl as i64))
+    }
 }
 
 impl State {
     /// Get or create state for a subject
-    pub fn get_or_create_release(&mut self, id: Uuid) -> &mut ReleaseState {
-        self.subjects.entry(id).or_insert_with(|| SubjectState::Release(ReleaseState::default()));
-        match self.subjects.get_mut(&id).unwrap() {
-            SubjectState::Release(state) => state,
-            _ => panic!("Subject type mismatch"),
+    pub fn get_or_create_release(&mut self, subject: &Subject) -> error::Result<&mut ReleaseState> {
+        self.subjects.entry(subject.id).or_insert_with(|| SubjectState::Release(ReleaseState::default()));
+        match self.subjects.get_mut(&subject.id).unwrap() {
+            SubjectState::Release(state) => Ok(state),
+            other => Err(HeadsupError::SubjectTypeMismatch {
+                subject_key: subject.key.clone(),
+                expected: SubjectType::Release,
+                found: other.subject_type(),
+            }),
         }
     }
 
     /// Get or create state for a question subject
-    pub fn get_or_create_question(&mut self, id: Uuid) -> &mut QuestionState {
-        self.subjects.entry(id).or_insert_with(|| SubjectState::Question(QuestionState::default()));
-        match self.subjects.get_mut(&id).unwrap() {
-            SubjectState::Question(state) => state,
-            _ => panic!("Subject type mismatch"),
+    pub fn get_or_create_question(&mut self, subject: &Subject) -> error::Result<&mut QuestionState> {
+        self.subjects.entry(subject.id).or_insert_with(|| SubjectState::Question(QuestionState::default()));
+        match self.subjects.get_mut(&subject.id).unwrap() {
+            SubjectState::Question(state) => Ok(state),
+            other => Err(HeadsupError::SubjectTypeMismatch {
+                subject_key: subject.key.clone(),
+                expected: SubjectType::Question,
+                found: other.subject_type(),
+            }),
         }
     }
 
     /// Get or create state for a recurring subject
-    pub fn get_or_create_recurring(&mut self, id: Uuid) -> &mut RecurringState {
-        self.subjects.entry(id).or_insert_with(|| SubjectState::Recurring(RecurringState::default()));
-        match self.subjects.get_mut(&id).unwrap() {
-            SubjectState::Recurring(state) => state,
-            _ => panic!("Subject type mismatch"),
+    pub fn get_or_create_recurring(&mut self, subject: &Subject) -> error::Result<&mut RecurringState> {
+        self.subjects.entry(subject.id).or_insert_with(|| SubjectState::Recurring(RecurringState::default()));
+        match self.subjects.get_mut(&subject.id).unwrap() {
+            SubjectState::Recurring(state) => Ok(state),
+            other => Err(HeadsupError::SubjectTypeMismatch {
+                subject_key: subject.key.clone(),
+                expected: SubjectType::Recurring,
+                found: other.subject_type(),
+            }),
         }
     }
 
@@ -330,8 +583,32 @@ impl State {
         orphans
     }
 
-    /// Add a history entry for a subject
-    pub fn add_history(&mut self, id: Uuid, entry: HistoryEntry, max_entries: u32) {
+    /// Fold the state of a removed duplicate subject into the subject that replaces it
+    ///
+    /// The more recently checked state wins; queued notifications move to the kept subject.
+    pub fn merge_subject(&mut self, keep: Uuid, duplicate: Uuid) {
+        if let Some(duplicate_state) = self.subjects.remove(&duplicate) {
+            let newer = self.subjects.get(&keep)
+                .is_none_or(|s| duplicate_state.last_checked() > s.last_checked());
+            if newer {
+                self.subjects.insert(keep, duplicate_state);
+            }
+        }
+        self.consecutive_failures.remove(&duplicate);
+        for notification in self.pending_notifications.iter_mut().filter(|n| n.subject_id == duplicate) {
+            notification.subject_id = keep;
+        }
+    }
+
+    /// Add a history entry for a subject, keeping at most the subject's (or the global) limit
+    ///
+    /// Failed checks are capped separately, so a run of errors never pushes out real history.
+    pub fn add_history(&mut self, id: Uuid, entry: HistoryEntry, config: &Config) {
+        let max_entries = config.subjects.iter()
+            .find(|s| s.id == id)
+            .and_then(|s| s.max_history_entries)
+            .unwrap_or(config.settings.max_history_entries);
+
         if let Some(state) = self.subjects.get_mut(&id) {
             let history = match state {
                 SubjectState::Release(s) => &mut s.history,
@@ -339,15 +616,49 @@ impl State {
                 SubjectState::Recurring(s) => &mut s.history,
             };
 
+            let failed = entry.event == FAILED_EVENT;
+            let same_kind = |e: &HistoryEntry| (e.event == FAILED_EVENT) == failed;
             history.push(entry);
 
-            // Prune old entries
-            while history.len() > max_entries as usize {
-                history.remove(0);
+            // Prune old entries, along with the delivery records they point to
+            while history.iter().filter(|e| same_kind(e)).count() > max_entries as usize {
+                let Some(oldest) = history.iter().position(same_kind) else {
+                    break;
+                };
+                let removed = history.remove(oldest);
+                if let Some(notification_id) = removed.notification_id() {
+                    self.notification_channels.remove(&notification_id);
+                }
             }
         }
     }
 
+    /// Point the subject's latest history entry at the notification it produced
+    pub fn link_notification(&mut self, id: Uuid, notification_id: Uuid) {
+        let history = match self.subjects.get_mut(&id) {
+            Some(SubjectState::Release(s)) => &mut s.history,
+            Some(SubjectState::Question(s)) => &mut s.history,
+            Some(SubjectState::Recurring(s)) => &mut s.history,
+            None => return,
+        };
+        if let Some(serde_json::Value::Object(details)) = history.last_mut().map(|e| &mut e.details) {
+            details.insert("notification_id".to_string(), serde_json::json!(notification_id));
+        }
+    }
+
+    /// Record that a notification was delivered through a channel
+    pub fn record_notification_channel(&mut self, notification_id: Uuid, channel: &str) {
+        let channels = self.notification_channels.entry(notification_id).or_default();
+        if !channels.iter().any(|c| c == channel) {
+            channels.push(channel.to_string());
+        }
+    }
+
+    /// Channels the notification behind a history entry was delivered through
+    pub fn channels_for(&self, entry: &HistoryEntry) -> Option<&[String]> {
+        self.notification_channels.get(&entry.notification_id()?).map(|c| c.as_slice())
+    }
+
     /// Clear pending notifications
     pub fn clear_pending_notifications(&mut self) -> Vec<PendingNotification> {
         std::mem::take(&mut self.pending_notifications)
@@ -357,4 +668,79 @@ impl State {
     pub fn add_pending_notification(&mut self, notification: PendingNotification) {
         self.pending_notifications.push(notification);
     }
+
+    /// Queue a notification, updating an identical one queued within the window instead.
+    /// Returns the ID of the queued notification
+    pub fn queue_notification(&mut self, notification: PendingNotification, dedup_window_hours: u32) -> Uuid {
+        let window_start = notification.created_at - chrono::Duration::hours(dedup_window_hours as i64);
+        let existing = self.pending_notifications.iter_mut().find(|n| {
+            dedup_window_hours > 0
+                && n.subject_id == notification.subject_id
+                && n.event_type == notification.event_type
+                && n.created_at >= window_start
+        });
+
+        match existing {
+            Some(existing) => {
+                existing.summary = notification.summary;
+                existing.source_url = notification.source_url;
+                existing.confidence = notification.confidence;
+                existing.payload = notification.payload;
+                existing.ttl_hours = notification.ttl_hours;
+                existing.expires_at = notification.expires_at;
+                existing.notification_id
+            }
+            None => {
+                let id = notification.notification_id;
+                self.pending_notifications.push(notification);
+                id
+            }
+        }
+    }
+
+    /// Remove and return pending notifications that expired unsent
+    pub fn discard_expired_notifications(&mut self, now: DateTime<Utc>, max_age_hours: u32) -> Vec<PendingNotification> {
+        let (expired, live) = std::mem::take(&mut self.pending_notifications)
+            .into_iter()
+            .partition(|n| n.is_expired(now, max_age_hours));
+        self.pending_notifications = live;
+        expired
+    }
+
+    /// Count another failed check for a subject and return the new streak length
+    pub fn record_failure(&mut self, id: Uuid) -> u32 {
+        let count = self.consecutive_failures.entry(id).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Record a successful check, ending any failure streak
+    pub fn record_success(&mut self, id: Uuid) {
+        self.consecutive_failures.remove(&id);
+    }
+
+    /// Add a check run to the day's totals, keeping only the most recent days
+    pub fn record_check_run(&mut self, date: NaiveDate, backend: &str, checks: u32, failures: u32, notifications: u32) {
+        let day = self.check_history.entry(date).or_default();
+        day.checks += checks;
+        day.failures += failures;
+        day.notifications += notifications;
+        *day.backends.entry(backend.to_string()).or_insert(0) += checks;
+
+        while self.check_history.len() > MAX_CHECK_HISTORY_DAYS {
+            self.check_history.pop_first();
+        }
+    }
+
+    /// Record a startup diagnostic, keeping only the most recent entries
+    pub fn add_diagnostic(&mut self, level: DiagnosticLevel, message: String) {
+        self.startup_diagnostics.push(DiagnosticMessage {
+            level,
+            message,
+            timestamp: Utc::now(),
+        });
+
+        let excess = self.startup_diagnostics.len().saturating_sub(MAX_DIAGNOSTICS);
+        self.startup_diagnostics.drain(..excess);
+    }
 }
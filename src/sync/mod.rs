@@ -0,0 +1,145 @@
+pub mod s3;
+
+use crate::config::{self, CloudSyncConfig};
+use crate::error::Result;
+use crate::state::{self, State, SubjectState};
+use chrono::{DateTime, Utc};
+use std::fs;
+
+/// Merge the remote state into `state`. Returns whether anything was taken from the remote copy
+pub fn pull_state(sync: &CloudSyncConfig, state: &mut State) -> Result<bool> {
+    let remote_path = config::state_path()?.with_extension("remote.json");
+    if !s3::download(sync, &remote_path)? {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(&remote_path)?;
+    let _ = fs::remove_file(&remote_path);
+    let raw: serde_json::Value = serde_json::from_str(&content)?;
+    let remote: State = serde_json::from_value(state::migrate(raw)?)?;
+
+    Ok(merge_state(state, remote))
+}
+
+/// Take each subject from whichever copy checked it last, and add remote notifications
+/// this machine has neither queued nor delivered
+fn merge_state(local: &mut State, remote: State) -> bool {
+    let mut changed = false;
+
+    for (id, remote_subject) in remote.subjects {
+        let local_checked = local.subjects.get(&id).and_then(SubjectState::last_checked);
+        if local.subjects.contains_key(&id) && remote_subject.last_checked() <= local_checked {
+            continue;
+        }
+        match remote.consecutive_failures.get(&id) {
+            Some(&failures) => local.consecutive_failures.insert(id, failures),
+            None => local.consecutive_failures.remove(&id),
+        };
+        local.subjects.insert(id, remote_subject);
+        changed = true;
+    }
+
+    for notification in remote.pending_notifications {
+        let id = notification.notification_id;
+        let known = local.pending_notifications.iter().any(|n| n.notification_id == id)
+            || local.notification_channels.contains_key(&id);
+        if !known {
+            local.pending_notifications.push(notification);
+            changed = true;
+        }
+    }
+    for (id, channels) in remote.notification_channels {
+        local.notification_channels.entry(id).or_insert(channels);
+    }
+    for (day, summary) in remote.check_history {
+        local.check_history.entry(day).or_insert(summary);
+    }
+
+    if is_newer_run(remote.last_run, local.last_run) {
+        local.last_run = remote.last_run;
+    }
+    changed
+}
+
+/// Upload the saved state file
+pub fn push_state(sync: &CloudSyncConfig) -> Result<()> {
+    s3::upload(sync, &config::state_path()?)
+}
+
+/// Whether run time `candidate` is more recent than `current`
+fn is_newer_run(candidate: Option<DateTime<Utc>>, current: Option<DateTime<Utc>>) -> bool {
+    match (candidate, current) {
+        (Some(candidate), Some(current)) => candidate > current,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PendingNotification, ReleaseState};
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    fn checked_at(last_checked: DateTime<Utc>) -> SubjectState {
+        SubjectState::Release(ReleaseState { last_checked: Some(last_checked), ..Default::default() })
+    }
+
+    fn notification(subject_id: Uuid) -> PendingNotification {
+        PendingNotification {
+            notification_id: Uuid::new_v4(),
+            subject_id,
+            event_type: "date_changed".to_string(),
+            created_at: Utc::now(),
+            summary: String::new(),
+            source_url: None,
+            confidence: None,
+            payload: serde_json::json!({}),
+            ttl_hours: None,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_is_newer_run() {
+        let now = Utc::now();
+        assert!(is_newer_run(Some(now), Some(now - Duration::hours(1))));
+        assert!(!is_newer_run(Some(now - Duration::hours(1)), Some(now)));
+        assert!(is_newer_run(Some(now), None));
+        assert!(!is_newer_run(None, Some(now)));
+    }
+
+    #[test]
+    fn test_merge_state() {
+        let now = Utc::now();
+        let (checked_here, checked_there, only_remote) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let mut local = State { last_run: Some(now - Duration::hours(2)), ..Default::default() };
+        local.subjects.insert(checked_here, checked_at(now));
+        local.subjects.insert(checked_there, checked_at(now - Duration::hours(5)));
+        let queued_here = notification(checked_here);
+        let delivered_here = notification(checked_here);
+        local.pending_notifications.push(queued_here.clone());
+        local.notification_channels.insert(delivered_here.notification_id, vec!["email".to_string()]);
+
+        let mut remote = State { last_run: Some(now - Duration::hours(1)), ..Default::default() };
+        remote.subjects.insert(checked_here, checked_at(now - Duration::hours(3)));
+        remote.subjects.insert(checked_there, checked_at(now - Duration::hours(1)));
+        remote.subjects.insert(only_remote, checked_at(now - Duration::hours(1)));
+        let queued_there = notification(checked_there);
+        remote.pending_notifications = vec![queued_here.clone(), delivered_here, queued_there.clone()];
+
+        assert!(merge_state(&mut local, remote));
+        assert_eq!(local.subjects[&checked_here].last_checked(), Some(now));
+        assert_eq!(local.subjects[&checked_there].last_checked(), Some(now - Duration::hours(1)));
+        assert!(local.subjects.contains_key(&only_remote));
+        let pending: Vec<Uuid> = local.pending_notifications.iter().map(|n| n.notification_id).collect();
+        assert_eq!(pending, vec![queued_here.notification_id, queued_there.notification_id]);
+        assert_eq!(local.last_run, Some(now - Duration::hours(1)));
+
+        // Merging the same copy again changes nothing
+        let again = local.clone();
+        assert!(!merge_state(&mut local, again));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::config::CloudSyncConfig;
+use crate::error::{HeadsupError, Result};
+use crate::util::secret::run_secret_command;
+use serde::Deserialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Credentials in the AWS `credential_process` JSON format
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    #[serde(default)]
+    session_token: Option<String>,
+}
+
+/// Download the remote state to `dest`. Returns false if the object does not exist yet
+pub fn download(sync: &CloudSyncConfig, dest: &Path) -> Result<bool> {
+    if !object_exists(sync)? {
+        return Ok(false);
+    }
+    run_aws(sync, &["s3", "cp", &object_url(sync), &dest.to_string_lossy(), "--only-show-errors"])?;
+    Ok(true)
+}
+
+/// Upload `src` to the configured bucket and key
+pub fn upload(sync: &CloudSyncConfig, src: &Path) -> Result<()> {
+    run_aws(sync, &["s3", "cp", &src.to_string_lossy(), &object_url(sync), "--only-show-errors"])?;
+    Ok(())
+}
+
+fn object_url(sync: &CloudSyncConfig) -> String {
+    format!("s3://{}/{}", sync.s3_bucket, object_key(sync))
+}
+
+fn object_key(sync: &CloudSyncConfig) -> &str {
+    sync.s3_key.trim_start_matches('/')
+}
+
+/// Whether the state object exists, by listing its key (a missing object is not an error there)
+fn object_exists(sync: &CloudSyncConfig) -> Result<bool> {
+    let key = object_key(sync);
+    let stdout = run_aws(sync, &[
+        "s3api", "list-objects-v2",
+        "--bucket", &sync.s3_bucket,
+        "--prefix", key,
+        "--query", "Contents[].Key",
+        "--output", "json",
+    ])?;
+    Ok(listed_keys(&stdout)?.iter().any(|k| k == key))
+}
+
+/// Keys printed by `list-objects-v2 --query Contents[].Key`, which prints `null` when nothing matches
+fn listed_keys(stdout: &str) -> Result<Vec<String>> {
+    let keys: Option<Vec<String>> = serde_json::from_str(stdout.trim())
+        .map_err(|e| HeadsupError::Sync(format!("Unexpected aws s3api output: {}", e)))?;
+    Ok(keys.unwrap_or_default())
+}
+
+/// Run the aws CLI and return its stdout
+fn run_aws(sync: &CloudSyncConfig, args: &[&str]) -> Result<String> {
+    let mut command = Command::new("aws");
+    command.args(args);
+    if let Some(ref endpoint) = sync.endpoint_url {
+        command.args(["--endpoint-url", endpoint]);
+    }
+
+    if let Some(ref credentials_command) = sync.aws_credentials_command {
+        let credentials = get_credentials(credentials_command)?;
+        command
+            .env("AWS_ACCESS_KEY_ID", credentials.access_key_id)
+            .env("AWS_SECRET_ACCESS_KEY", credentials.secret_access_key);
+        if let Some(token) = credentials.session_token {
+            command.env("AWS_SESSION_TOKEN", token);
+        }
+    }
+
+    let output = command.output()
+        .map_err(|e| HeadsupError::Sync(format!("Failed to run aws CLI: {}", e)))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(HeadsupError::Sync(format!(
+            "aws {} failed: {}",
+            args.iter().take(2).copied().collect::<Vec<_>>().join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+fn get_credentials(command: &str) -> Result<Credentials> {
+    let output = run_secret_command(command, "credentials command").map_err(HeadsupError::Sync)?;
+    serde_json::from_str(&output)
+        .map_err(|e| HeadsupError::Sync(format!("Credentials command output is not credential_process JSON: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listed_keys() {
+        assert!(listed_keys("null\n").unwrap().is_empty());
+        assert_eq!(listed_keys(r#"["headsup/state.json", "headsup/state.json.bak"]"#).unwrap().len(), 2);
+        assert!(listed_keys("An error occurred").is_err());
+    }
+}
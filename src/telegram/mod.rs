@@ -0,0 +1,138 @@
+use crate::config::{Subject, TelegramConfig};
+use crate::error::{HeadsupError, Result};
+use crate::state::PendingNotification;
+use crate::util::secret::run_secret_command;
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Base URL of the Telegram Bot API
+const API_URL: &str = "https://api.telegram.org";
+
+/// Timeout for Bot API requests
+const TELEGRAM_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Serialize)]
+struct SendMessage<'a> {
+    chat_id: &'a str,
+    text: &'a str,
+    parse_mode: &'static str,
+}
+
+/// Send one notification as a MarkdownV2 message
+pub async fn send_notification(
+    config: &TelegramConfig,
+    notification: &PendingNotification,
+    subjects: &[Subject],
+) -> Result<()> {
+    send_message(config, &format_notification(notification, subjects)).await
+}
+
+/// Send a test message
+pub async fn send_test_message(config: &TelegramConfig) -> Result<()> {
+    let text = format!(
+        "*{}*\n\n{}",
+        escape_markdown("Headsup - Test Message"),
+        escape_markdown("If you're reading this, your Telegram settings are configured properly!")
+    );
+    send_message(config, &text).await
+}
+
+/// Call sendMessage with a MarkdownV2 text
+async fn send_message(config: &TelegramConfig, text: &str) -> Result<()> {
+    let token = get_bot_token(&config.bot_token_command)?;
+    let client = Client::builder()
+        .timeout(Duration::from_secs(TELEGRAM_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| HeadsupError::Telegram(format!("Failed to create HTTP client: {}", e)))?;
+
+    // The token is part of the URL, so it is stripped from errors
+    let response = client
+        .post(format!("{}/bot{}/sendMessage", API_URL, token))
+        .json(&SendMessage {
+            chat_id: &config.chat_id,
+            text,
+            parse_mode: "MarkdownV2",
+        })
+        .send()
+        .await
+        .map_err(|e| HeadsupError::Telegram(format!("Request failed: {}", e.without_url())))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(HeadsupError::Telegram(format!("Bot API returned status {}: {}", status, body)));
+    }
+
+    Ok(())
+}
+
+/// Bold subject name, the summary, italic confidence and a source link
+pub fn format_notification(notification: &PendingNotification, subjects: &[Subject]) -> String {
+    let subject_name = subjects.iter()
+        .find(|s| s.id == notification.subject_id)
+        .map(|s| s.name.as_str())
+        .unwrap_or("Unknown");
+
+    let mut text = format!(
+        "*{}* {}\n\n{}",
+        escape_markdown(subject_name),
+        escape_markdown(&format!("({})", notification.event_type)),
+        escape_markdown(&notification.summary)
+    );
+    if let Some(confidence) = notification.confidence {
+        text.push_str(&format!("\n\n_{}_", escape_markdown(&format!("Confidence: {}", confidence))));
+    }
+    if let Some(ref url) = notification.source_url {
+        text.push_str(&format!("\n[Source]({})", url.replace('\\', "\\\\").replace(')', "\\)")));
+    }
+    text
+}
+
+/// Escape the characters MarkdownV2 reserves outside of entities
+fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Run the bot token command and return its trimmed output
+fn get_bot_token(command: &str) -> Result<String> {
+    run_secret_command(command, "bot token command").map_err(HeadsupError::Telegram)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Confidence;
+
+    #[test]
+    fn test_format_notification() {
+        let subject = Subject {
+            name: "GTA 6".to_string(),
+            ..Default::default()
+        };
+        let notification = PendingNotification {
+            notification_id: uuid::Uuid::new_v4(),
+            subject_id: subject.id,
+            event_type: "release_update".to_string(),
+            created_at: chrono::Utc::now(),
+            summary: "Release moved to 2026-11-19.".to_string(),
+            source_url: Some("https://example.com/(news)".to_string()),
+            confidence: Some(Confidence::Official),
+            payload: serde_json::Value::Null,
+            ttl_hours: None,
+            expires_at: None,
+        };
+
+        assert_eq!(
+            format_notification(&notification, &[subject]),
+            "*GTA 6* \\(release\\_update\\)\n\nRelease moved to 2026\\-11\\-19\\.\n\n_Confidence: Official announcement_\n[Source](https://example.com/(news\\))"
+        );
+    }
+}
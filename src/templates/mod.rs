@@ -0,0 +1,3 @@
+mod question;
+
+pub use question::*;
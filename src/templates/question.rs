@@ -0,0 +1,47 @@
+use clap::ValueEnum;
+
+/// Built-in question patterns for common tracking questions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum QuestionTemplate {
+    Casting,
+    Director,
+    ReleasePlatform,
+    SequelConfirmed,
+    PriceAnnounced,
+}
+
+impl QuestionTemplate {
+    /// The question pattern, with a `{subject}` placeholder
+    pub fn question(&self) -> &'static str {
+        match self {
+            QuestionTemplate::Casting => "Who will be cast as {subject}?",
+            QuestionTemplate::Director => "Who will direct {subject}?",
+            QuestionTemplate::ReleasePlatform => "Which platforms will {subject} be released on?",
+            QuestionTemplate::SequelConfirmed => "Has a sequel to {subject} been officially confirmed?",
+            QuestionTemplate::PriceAnnounced => "What is the announced price of {subject}?",
+        }
+    }
+
+    /// Fill in the question pattern for a subject name
+    pub fn fill(&self, subject: &str) -> String {
+        self.question().replace("{subject}", subject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_template() {
+        assert_eq!(
+            QuestionTemplate::Casting.fill("James Bond"),
+            "Who will be cast as James Bond?"
+        );
+        assert_eq!(
+            QuestionTemplate::Director.fill("Dune Messiah"),
+            "Who will direct Dune Messiah?"
+        );
+    }
+}
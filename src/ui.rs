@@ -163,3 +163,8 @@ pub fn parse_category_option(option: &str) -> crate::config::Category {
 pub fn is_interactive() -> bool {
     atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout)
 }
+
+/// Width of the terminal stdout is attached to, if any
+pub fn terminal_width() -> Option<usize> {
+    console::Term::stdout().size_checked().map(|(_, width)| width as usize)
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Exponential backoff between retries of a failed call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// Retries after the first attempt
+    pub attempts: u32,
+    pub initial_delay_ms: u64,
+    /// Add up to half the delay at random, so parallel checks don't retry in lockstep
+    pub jitter: bool,
+}
+
+impl Backoff {
+    /// Delay before the given retry (1 for the first), doubling each time
+    pub fn delay(&self, retry: u32) -> Duration {
+        let base = self.initial_delay_ms.saturating_mul(1u64 << retry.saturating_sub(1).min(20));
+        let jitter = if self.jitter {
+            (Uuid::new_v4().as_u128() % (base as u128 / 2 + 1)) as u64
+        } else {
+            0
+        };
+        Duration::from_millis(base.saturating_add(jitter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles() {
+        let backoff = Backoff { attempts: 3, initial_delay_ms: 1000, jitter: false };
+        assert_eq!(backoff.delay(1), Duration::from_millis(1000));
+        assert_eq!(backoff.delay(2), Duration::from_millis(2000));
+        assert_eq!(backoff.delay(3), Duration::from_millis(4000));
+
+        let jittered = Backoff { jitter: true, ..backoff };
+        let delay = jittered.delay(2);
+        assert!(delay >= Duration::from_millis(2000) && delay <= Duration::from_millis(3000));
+    }
+}
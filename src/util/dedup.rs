@@ -0,0 +1,117 @@
+use crate::config::Subject;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Two subjects whose search terms overlap enough to be considered duplicates
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicatePair {
+    /// The subject that comes first in the config (kept when merging)
+    pub keep: Uuid,
+    /// The later subject (removed when merging)
+    pub duplicate: Uuid,
+    pub similarity: f64,
+}
+
+/// Normalized set of search terms (case and surrounding whitespace ignored)
+fn term_set(terms: &[String]) -> HashSet<String> {
+    terms
+        .iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Jaccard similarity of two search-term lists (1.0 means identical sets)
+pub fn jaccard_similarity(a: &[String], b: &[String]) -> f64 {
+    let a = term_set(a);
+    let b = term_set(b);
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    let intersection = a.intersection(&b).count() as f64;
+    let union = a.union(&b).count() as f64;
+    intersection / union
+}
+
+/// Find pairs of subjects of the same type whose search terms are at least `threshold` similar
+pub fn find_duplicates(subjects: &[Subject], threshold: f64) -> Vec<DuplicatePair> {
+    let mut pairs = Vec::new();
+    for (i, first) in subjects.iter().enumerate() {
+        for second in &subjects[i + 1..] {
+            // A release and a question about the same thing track different facts
+            if first.subject_type != second.subject_type
+                || first.search_terms.is_empty()
+                || second.search_terms.is_empty()
+            {
+                continue;
+            }
+            let similarity = jaccard_similarity(&first.search_terms, &second.search_terms);
+            if similarity >= threshold {
+                pairs.push(DuplicatePair {
+                    keep: first.id,
+                    duplicate: second.id,
+                    similarity,
+                });
+            }
+        }
+    }
+    pairs
+}
+
+/// Fold a duplicate subject into the one being kept
+pub fn merge_subject(keep: &mut Subject, duplicate: &Subject) {
+    let existing = term_set(&keep.search_terms);
+    for term in &duplicate.search_terms {
+        if !existing.contains(&term.trim().to_lowercase()) {
+            keep.search_terms.push(term.clone());
+        }
+    }
+    if keep.notes.is_none() {
+        keep.notes = duplicate.notes.clone();
+    }
+    keep.enabled = keep.enabled || duplicate.enabled;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(key: &str, terms: &[&str]) -> Subject {
+        Subject {
+            key: key.to_string(),
+            search_terms: terms.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let subjects = vec![
+            subject("a", &["GTA 6", "Grand Theft Auto VI"]),
+            subject("b", &["grand theft auto vi", "gta 6"]),
+            subject("c", &["GTA 6", "Rockstar"]),
+            subject("d", &[]),
+        ];
+
+        let exact = find_duplicates(&subjects, 1.0);
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].keep, subjects[0].id);
+        assert_eq!(exact[0].duplicate, subjects[1].id);
+
+        let near = find_duplicates(&subjects, 0.3);
+        assert_eq!(near.len(), 3);
+
+        let mut question = subject("e", &["GTA 6", "Grand Theft Auto VI"]);
+        question.subject_type = crate::config::SubjectType::Question;
+        let mixed = vec![subjects[0].clone(), question];
+        assert!(find_duplicates(&mixed, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_merge_subject() {
+        let mut keep = subject("a", &["GTA 6"]);
+        let duplicate = subject("b", &["gta 6", "Rockstar"]);
+        merge_subject(&mut keep, &duplicate);
+        assert_eq!(keep.search_terms, vec!["GTA 6", "Rockstar"]);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::error::{HeadsupError, Result};
+use chrono::Duration;
+
+/// Parse a duration like `30m`, `24h`, `7d` or `2w`
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let invalid = || {
+        HeadsupError::Config(format!(
+            "Invalid duration '{}' (expected a number followed by m, h, d or w, e.g. 7d)",
+            value
+        ))
+    };
+
+    let value = value.trim();
+    let unit_start = value.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(unit_start);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+/// Format a duration with its two largest units, e.g. `3d 4h` or `12m`
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_duration("24h").unwrap(), Duration::hours(24));
+        assert_eq!(parse_duration("7d").unwrap(), Duration::days(7));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::weeks(2));
+        assert!(parse_duration("7").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("7y").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::minutes(12)), "12m");
+        assert_eq!(format_duration(Duration::minutes(312)), "5h 12m");
+        assert_eq!(format_duration(Duration::hours(76)), "3d 4h");
+    }
+}
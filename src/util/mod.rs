@@ -0,0 +1,8 @@
+pub mod backoff;
+pub mod dedup;
+pub mod duration;
+pub mod html;
+pub mod network;
+pub mod rate_limiter;
+pub mod scheduler;
+pub mod secret;
@@ -0,0 +1,31 @@
+use crate::error::{HeadsupError, Result};
+use reqwest::Client;
+use std::time::{Duration, Instant};
+
+/// URL probed when settings.internet_check_url is not set
+pub const DEFAULT_INTERNET_CHECK_URL: &str = "https://1.1.1.1";
+
+/// Time between connectivity probes
+const POLL_INTERVAL_SECS: u64 = 5;
+
+/// Poll `url` with HEAD requests until one gets a response or `timeout` passes
+pub async fn wait_for_internet(url: &str, timeout: Duration) -> Result<()> {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(POLL_INTERVAL_SECS))
+        .build()
+        .map_err(|e| HeadsupError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+    let start = Instant::now();
+    loop {
+        // Any HTTP response, even an error status, means the network is up
+        match client.head(url).send().await {
+            Ok(_) => return Ok(()),
+            Err(e) => tracing::debug!("Connectivity check against {} failed: {}", url, e),
+        }
+
+        if start.elapsed() + Duration::from_secs(POLL_INTERVAL_SECS) > timeout {
+            return Err(HeadsupError::NoConnectivity(timeout.as_secs()));
+        }
+        tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+    }
+}
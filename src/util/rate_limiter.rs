@@ -0,0 +1,72 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket limiting how many backend calls start per minute
+#[derive(Debug)]
+pub struct RateLimiter {
+    calls_per_minute: u32,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter with a full bucket, so the first `calls_per_minute` calls start immediately
+    pub fn new(calls_per_minute: u32) -> Self {
+        let calls_per_minute = calls_per_minute.max(1);
+        RateLimiter {
+            calls_per_minute,
+            bucket: Mutex::new(Bucket {
+                tokens: calls_per_minute as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a call may start
+    pub async fn acquire(&self) {
+        while let Some(wait) = self.try_take(Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token if one is available, otherwise return how long until the next one
+    fn try_take(&self, now: Instant) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let per_second = self.calls_per_minute as f64 / 60.0;
+
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(self.calls_per_minute as f64);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(2);
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_take(now), None);
+        assert_eq!(limiter.try_take(now), None);
+        let wait = limiter.try_take(now).unwrap();
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+
+        // Two calls per minute refill one token every 30 seconds
+        assert_eq!(limiter.try_take(now + Duration::from_secs(30)), None);
+    }
+}
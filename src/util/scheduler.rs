@@ -0,0 +1,127 @@
+use crate::state::{HistoryEntry, FAILED_EVENT};
+use chrono::{DateTime, Datelike, Duration, Utc};
+
+/// Schedules checks around the times of year and week a subject has historically had updates
+pub struct SmartScheduler;
+
+impl SmartScheduler {
+    /// Updates needed before the history is trusted; below this the subject is always due
+    const MIN_UPDATES: usize = 3;
+    /// Longest gap between two checks, even in quiet periods
+    const MAX_INTERVAL_DAYS: i64 = 7;
+    /// Share of the peak density at which a day counts as an active period
+    const ACTIVE_THRESHOLD: f64 = 0.5;
+    const MONTH_BANDWIDTH: f64 = 1.0;
+    const WEEKDAY_BANDWIDTH: f64 = 1.0;
+
+    /// When the subject should next be checked, given its history
+    ///
+    /// Days whose month and weekday resemble past updates are checked daily,
+    /// quiet periods only once every `MAX_INTERVAL_DAYS`.
+    pub fn next_check_time(history: &[HistoryEntry]) -> DateTime<Utc> {
+        // Failed checks are retried on the next run rather than rescheduled
+        let Some(last_check) = history.iter().filter(|e| e.event != FAILED_EVENT).map(|e| e.timestamp).max() else {
+            return Utc::now();
+        };
+
+        let updates: Vec<DateTime<Utc>> = history
+            .iter()
+            .filter(|e| is_update(e))
+            .map(|e| e.timestamp)
+            .collect();
+        if updates.len() < Self::MIN_UPDATES {
+            return last_check;
+        }
+
+        // Every (month, weekday) combination, to scale densities against the busiest one
+        let peak = (0..12)
+            .flat_map(|month| (0..7).map(move |weekday| (month, weekday)))
+            .map(|(month, weekday)| Self::density(month, weekday, &updates))
+            .fold(0.0, f64::max);
+
+        (1..=Self::MAX_INTERVAL_DAYS)
+            .map(|days| last_check + Duration::days(days))
+            .find(|t| {
+                let density = Self::density(t.month0(), t.weekday().num_days_from_monday(), &updates);
+                density >= peak * Self::ACTIVE_THRESHOLD
+            })
+            .unwrap_or(last_check + Duration::days(Self::MAX_INTERVAL_DAYS))
+    }
+
+    /// Gaussian kernel density of past updates at a month (0-11) and weekday (0-6)
+    fn density(month: u32, weekday: u32, updates: &[DateTime<Utc>]) -> f64 {
+        updates
+            .iter()
+            .map(|u| {
+                let m = circular_distance(month, u.month0(), 12) / Self::MONTH_BANDWIDTH;
+                let w = circular_distance(weekday, u.weekday().num_days_from_monday(), 7) / Self::WEEKDAY_BANDWIDTH;
+                (-0.5 * (m * m + w * w)).exp()
+            })
+            .sum()
+    }
+}
+
+/// Whether a history entry recorded something worth notifying about
+fn is_update(entry: &HistoryEntry) -> bool {
+    entry.notification_id().is_some()
+        || entry.details.get("should_notify").and_then(|v| v.as_bool()) == Some(true)
+}
+
+/// Distance between two positions on a cycle of the given length
+fn circular_distance(a: u32, b: u32, period: u32) -> f64 {
+    let diff = a.abs_diff(b);
+    diff.min(period - diff) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(timestamp: DateTime<Utc>, should_notify: bool) -> HistoryEntry {
+        HistoryEntry {
+            timestamp,
+            event: "check".to_string(),
+            details: serde_json::json!({ "should_notify": should_notify }),
+            source_url: None,
+            raw_response: None,
+            raw_ai_output: None,
+            latency_ms: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn june_updates() -> Vec<HistoryEntry> {
+        // Mondays in June of three years
+        [(2022, 6), (2023, 5), (2024, 10)]
+            .iter()
+            .map(|&(year, day)| entry(Utc.with_ymd_and_hms(year, 6, day, 17, 0, 0).unwrap(), true))
+            .collect()
+    }
+
+    #[test]
+    fn test_due_without_enough_updates() {
+        let last = Utc.with_ymd_and_hms(2025, 1, 6, 12, 0, 0).unwrap();
+        let history = vec![entry(last - Duration::days(30), true), entry(last, false)];
+        assert_eq!(SmartScheduler::next_check_time(&history), last);
+    }
+
+    #[test]
+    fn test_checks_daily_in_active_period() {
+        let mut history = june_updates();
+        let last = Utc.with_ymd_and_hms(2025, 6, 8, 12, 0, 0).unwrap();
+        history.push(entry(last, false));
+        assert_eq!(SmartScheduler::next_check_time(&history), last + Duration::days(1));
+    }
+
+    #[test]
+    fn test_backs_off_in_quiet_period() {
+        let mut history = june_updates();
+        let last = Utc.with_ymd_and_hms(2025, 12, 1, 12, 0, 0).unwrap();
+        history.push(entry(last, false));
+        assert_eq!(
+            SmartScheduler::next_check_time(&history),
+            last + Duration::days(SmartScheduler::MAX_INTERVAL_DAYS)
+        );
+    }
+}
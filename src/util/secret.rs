@@ -0,0 +1,49 @@
+use std::process::Command;
+
+/// Run a user-configured secret command through the shell and return its trimmed output
+///
+/// `what` names the command in error messages (e.g. "API key command"); callers wrap the
+/// message in their own error variant.
+pub fn run_secret_command(command: &str, what: &str) -> Result<String, String> {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| format!("Failed to execute {}: {}", what, e))?;
+
+    let what = capitalize(what);
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", what, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if secret.is_empty() {
+        return Err(format!("{} returned empty output", what));
+    }
+    Ok(secret)
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_secret_command() {
+        assert_eq!(run_secret_command("echo '  s3cret  '", "secret command").unwrap(), "s3cret");
+        assert_eq!(
+            run_secret_command("true", "bot token command").unwrap_err(),
+            "Bot token command returned empty output"
+        );
+        let failed = run_secret_command("echo denied >&2; exit 3", "API key command").unwrap_err();
+        assert_eq!(failed, "API key command failed: denied");
+    }
+}
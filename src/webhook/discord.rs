@@ -0,0 +1,128 @@
+use crate::config::Subject;
+use crate::error::Result;
+use crate::state::{Confidence, PendingNotification};
+use crate::webhook;
+use serde::Serialize;
+
+/// Discord accepts at most this many embeds per message
+const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+
+#[derive(Debug, Serialize)]
+pub struct DiscordMessage {
+    pub embeds: Vec<Embed>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Embed {
+    pub title: String,
+    pub description: String,
+    pub color: u32,
+    pub fields: Vec<EmbedField>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
+
+/// Post notifications to a Discord webhook, either batched or one message each
+pub async fn send_notifications(
+    url: &str,
+    notifications: &[PendingNotification],
+    subjects: &[Subject],
+    digest: bool,
+) -> Result<usize> {
+    let embeds: Vec<Embed> = notifications.iter().map(|n| build_embed(n, subjects)).collect();
+    let batch_size = if digest { MAX_EMBEDS_PER_MESSAGE } else { 1 };
+
+    let mut sent = 0;
+    let mut embeds = embeds.into_iter().peekable();
+    while embeds.peek().is_some() {
+        let message = DiscordMessage {
+            embeds: embeds.by_ref().take(batch_size).collect(),
+        };
+        webhook::post_json(url, &message).await?;
+        sent += 1;
+    }
+
+    Ok(sent)
+}
+
+/// Build a Discord embed for one notification
+pub fn build_embed(notification: &PendingNotification, subjects: &[Subject]) -> Embed {
+    let title = subjects.iter()
+        .find(|s| s.id == notification.subject_id)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let mut fields = Vec::new();
+    if let Some(ref url) = notification.source_url {
+        fields.push(EmbedField {
+            name: "Source".to_string(),
+            value: url.clone(),
+            inline: false,
+        });
+    }
+    let date = ["found_release_date", "next_occurrence_date"]
+        .iter()
+        .find_map(|key| notification.payload.get(*key)?.as_str())
+        .map(|d| d.to_string())
+        .unwrap_or_else(|| notification.created_at.format("%Y-%m-%d").to_string());
+    fields.push(EmbedField {
+        name: "Date".to_string(),
+        value: date,
+        inline: true,
+    });
+
+    Embed {
+        title,
+        description: notification.summary.clone(),
+        color: confidence_color(notification.confidence),
+        fields,
+    }
+}
+
+/// Embed color for a confidence level
+fn confidence_color(confidence: Option<Confidence>) -> u32 {
+    match confidence {
+        Some(Confidence::Official) => 0x2ECC71,
+        Some(Confidence::Reliable) => 0xF1C40F,
+        Some(Confidence::Rumor) => 0xE67E22,
+        Some(Confidence::Speculation) => 0xE74C3C,
+        Some(Confidence::Unknown) | None => 0x95A5A6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_build_embed() {
+        let subject = Subject {
+            name: "GTA 6".to_string(),
+            ..Default::default()
+        };
+        let notification = PendingNotification {
+            notification_id: uuid::Uuid::new_v4(),
+            subject_id: subject.id,
+            event_type: "release_update".to_string(),
+            created_at: Utc::now(),
+            summary: "Release date confirmed".to_string(),
+            source_url: Some("https://www.rockstargames.com".to_string()),
+            confidence: Some(Confidence::Official),
+            payload: serde_json::json!({ "found_release_date": "2026-05-26" }),
+            ttl_hours: None,
+            expires_at: None,
+        };
+
+        let embed = build_embed(&notification, &[subject]);
+        assert_eq!(embed.title, "GTA 6");
+        assert_eq!(embed.color, 0x2ECC71);
+        assert_eq!(embed.fields.len(), 2);
+        assert_eq!(embed.fields[1].value, "2026-05-26");
+    }
+}
@@ -0,0 +1,175 @@
+pub mod discord;
+
+use crate::config::{Subject, WebhookConfig, WebhookMethod};
+use crate::error::{HeadsupError, Result};
+use crate::state::{Confidence, PendingNotification};
+use crate::util::secret::run_secret_command;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Url};
+use ring::hmac;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Timeout for webhook requests
+const WEBHOOK_TIMEOUT_SECS: u64 = 5;
+
+/// Header carrying the HMAC-SHA256 of the JSON body (query string for GET)
+const SIGNATURE_HEADER: &str = "X-Headsup-Signature";
+
+/// Payload sent when a subject check fails
+#[derive(Debug, Serialize)]
+pub struct FailurePayload {
+    pub subject_key: String,
+    pub error: String,
+    pub error_type: String,
+    pub consecutive_failures: u32,
+}
+
+/// Notification sent to the configured [webhook] endpoint
+#[derive(Debug, Serialize)]
+pub struct NotificationPayload {
+    pub subject_key: String,
+    pub subject_name: String,
+    pub event_type: String,
+    pub summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    pub created_at: DateTime<Utc>,
+    /// The backend response behind the notification
+    pub details: serde_json::Value,
+}
+
+impl NotificationPayload {
+    pub fn new(notification: &PendingNotification, subjects: &[Subject]) -> Self {
+        let subject = subjects.iter().find(|s| s.id == notification.subject_id);
+        NotificationPayload {
+            subject_key: subject.map(|s| s.key.clone()).unwrap_or_default(),
+            subject_name: subject.map(|s| s.name.clone()).unwrap_or_else(|| "Unknown".to_string()),
+            event_type: notification.event_type.clone(),
+            summary: notification.summary.clone(),
+            source_url: notification.source_url.clone(),
+            confidence: notification.confidence,
+            created_at: notification.created_at,
+            details: notification.payload.clone(),
+        }
+    }
+}
+
+fn client() -> Result<Client> {
+    Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| HeadsupError::Webhook(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// POST a JSON payload to a webhook URL
+pub async fn post_json<T: Serialize>(url: &str, payload: &T) -> Result<()> {
+    let response = client()?
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| HeadsupError::Webhook(format!("Request to {} failed: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(HeadsupError::Webhook(format!(
+            "Webhook returned status {}",
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Send one notification to the configured webhook, signing it when a secret is set
+pub async fn send_notification(
+    config: &WebhookConfig,
+    notification: &PendingNotification,
+    subjects: &[Subject],
+) -> Result<()> {
+    let payload = NotificationPayload::new(notification, subjects);
+    let mut url = Url::parse(&config.url)
+        .map_err(|e| HeadsupError::Webhook(format!("Invalid webhook url '{}': {}", config.url, e)))?;
+
+    let client = client()?;
+    let (mut request, signed) = match config.method {
+        WebhookMethod::Post => {
+            let body = serde_json::to_string(&payload)?;
+            let request = client
+                .post(url)
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+            (request, body)
+        }
+        WebhookMethod::Get => {
+            {
+                let mut query = url.query_pairs_mut();
+                query.append_pair("subject_key", &payload.subject_key);
+                query.append_pair("subject_name", &payload.subject_name);
+                query.append_pair("event_type", &payload.event_type);
+                query.append_pair("summary", &payload.summary);
+                if let Some(ref source_url) = payload.source_url {
+                    query.append_pair("source_url", source_url);
+                }
+                if let Some(confidence) = payload.confidence {
+                    query.append_pair("confidence", &confidence.to_string());
+                }
+            }
+            let query = url.query().unwrap_or_default().to_string();
+            (client.get(url), query)
+        }
+    };
+
+    for (name, value) in &config.headers {
+        request = request.header(name, value);
+    }
+    if let Some(ref command) = config.secret_command {
+        let secret = read_secret(command)?;
+        request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(&secret, &signed)));
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| HeadsupError::Webhook(format!("Request to {} failed: {}", config.url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(HeadsupError::Webhook(format!(
+            "Webhook returned status {}",
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Hex-encoded HMAC-SHA256 of `message` keyed with `secret`
+fn sign(secret: &str, message: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::sign(&key, message.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Run the secret command and return its trimmed output
+fn read_secret(command: &str) -> Result<String> {
+    run_secret_command(command, "secret command").map_err(HeadsupError::Webhook)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}